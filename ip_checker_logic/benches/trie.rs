@@ -0,0 +1,83 @@
+// Prefix trie with an internet sized table, run with `cargo bench --bench trie`.
+// Set PREFIXES to try other table sizes
+use std::hint::black_box;
+use std::net::Ipv4Addr;
+use std::time::{Duration, Instant};
+
+use ip_checker_logic::trie::PrefixTrie;
+use ip_checker_logic::{IpAddress, NetworkAddress, Prefix};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const LOOKUPS: usize = 1_000_000;
+
+fn report(name: &str, count: usize, elapsed: Duration) {
+    println!(
+        "{:<24} {:>9} ops in {:>8.1?} ({:>6.0} ns/op)",
+        name,
+        count,
+        elapsed,
+        elapsed.as_nanos() as f64 / count as f64
+    );
+}
+
+// Mostly /24s with some shorter prefixes, roughly like a real table
+fn random_prefix(rng: &mut StdRng) -> (NetworkAddress, Prefix) {
+    let length = match rng.gen_range(0..100) {
+        0..=59 => 24,
+        60..=79 => rng.gen_range(20..24),
+        80..=94 => rng.gen_range(16..20),
+        _ => rng.gen_range(8..16),
+    };
+    let prefix = Prefix::new(length).unwrap();
+    let network = NetworkAddress::from_u32(rng.gen(), length).unwrap();
+    (network, prefix)
+}
+
+fn main() {
+    let size: usize = std::env::var("PREFIXES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1_000_000);
+    let mut rng = StdRng::seed_from_u64(1);
+    let prefixes: Vec<(NetworkAddress, Prefix)> =
+        (0..size).map(|_| random_prefix(&mut rng)).collect();
+    let addresses: Vec<IpAddress> = (0..LOOKUPS)
+        .map(|_| IpAddress::new(Ipv4Addr::from(rng.gen::<u32>()).to_string()))
+        .collect();
+
+    let mut trie = PrefixTrie::new();
+    let start = Instant::now();
+    for (index, (network, prefix)) in prefixes.iter().enumerate() {
+        trie.insert(network, prefix, index);
+    }
+    report("insert", size, start.elapsed());
+    println!("{} distinct prefixes", trie.len());
+
+    let start = Instant::now();
+    for (network, prefix) in &prefixes {
+        black_box(trie.get(network, prefix));
+    }
+    report("exact match", size, start.elapsed());
+
+    let start = Instant::now();
+    let mut found = 0;
+    for address in &addresses {
+        if black_box(trie.longest_match_value(address)).is_some() {
+            found += 1;
+        }
+    }
+    report("longest prefix match", LOOKUPS, start.elapsed());
+    println!("{} of {} addresses routed", found, LOOKUPS);
+
+    let start = Instant::now();
+    let count = trie.iter().count();
+    report("ordered iteration", count, start.elapsed());
+
+    let start = Instant::now();
+    for (network, prefix) in &prefixes {
+        black_box(trie.remove(network, prefix));
+    }
+    report("remove", size, start.elapsed());
+    assert!(trie.is_empty());
+}
@@ -0,0 +1,263 @@
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use rand::Rng;
+use thiserror::Error;
+
+use crate::ip_address::{IpAddress, IpAddressError};
+use crate::wildcard_mask::{WildcardMask, WildcardMaskError};
+use crate::{IpCalculator, IpModel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Permit,
+    Deny,
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Action::Permit => "permit",
+            Action::Deny => "deny",
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AclError {
+    #[error("ACL entry must start with permit or deny, got '{0}'")]
+    InvalidAction(String),
+
+    #[error("ACL entry is missing the {0}")]
+    Missing(&'static str),
+
+    #[error("Invalid address in ACL entry: {0}")]
+    InvalidAddress(#[from] IpAddressError),
+
+    #[error("Invalid wildcard mask in ACL entry: {0}")]
+    InvalidWildcard(#[from] WildcardMaskError),
+
+    #[error("Unexpected '{0}' at the end of the ACL entry")]
+    TrailingInput(String),
+}
+
+// A standard ACL entry like `permit 10.4.16.0 0.0.15.255`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AclEntry {
+    pub action: Action,
+    pub address: IpAddress,
+    pub wildcard: WildcardMask,
+}
+
+impl AclEntry {
+    pub fn matches(&self, address: &IpAddress) -> bool {
+        self.wildcard.matches(&self.address, address)
+    }
+}
+
+impl FromStr for AclEntry {
+    type Err = AclError;
+
+    // Also accepts a leading `access-list <number>` and the `host` and `any` keywords
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split_whitespace().peekable();
+        if tokens.peek() == Some(&"access-list") {
+            tokens.next();
+            tokens.next();
+        }
+
+        let action = match tokens.next() {
+            Some(token) if token.eq_ignore_ascii_case("permit") => Action::Permit,
+            Some(token) if token.eq_ignore_ascii_case("deny") => Action::Deny,
+            Some(token) => return Err(AclError::InvalidAction(token.to_string())),
+            None => return Err(AclError::InvalidAction(String::new())),
+        };
+
+        let (address, wildcard) = match tokens.next() {
+            Some(token) if token.eq_ignore_ascii_case("any") => (
+                IpAddress::new("0.0.0.0".to_string()),
+                WildcardMask::from_u32(u32::MAX),
+            ),
+            Some(token) if token.eq_ignore_ascii_case("host") => {
+                let address = tokens.next().ok_or(AclError::Missing("host address"))?;
+                (address.parse()?, WildcardMask::from_u32(0))
+            }
+            Some(address) => {
+                let wildcard = tokens.next().ok_or(AclError::Missing("wildcard mask"))?;
+                (address.parse()?, wildcard.parse()?)
+            }
+            None => return Err(AclError::Missing("address")),
+        };
+
+        if let Some(token) = tokens.next() {
+            return Err(AclError::TrailingInput(token.to_string()));
+        }
+
+        Ok(Self {
+            action,
+            address,
+            wildcard,
+        })
+    }
+}
+
+impl std::fmt::Display for AclEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.action, self.address, self.wildcard)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AclExercise {
+    // Write the entry permitting exactly the network of the model
+    WriteEntry { model: IpModel },
+    // Decide whether the entry matches the address
+    Match { entry: AclEntry, address: IpAddress },
+}
+
+impl AclExercise {
+    pub fn text(&self) -> String {
+        match self {
+            AclExercise::WriteEntry { model } => format!(
+                "Write the ACL entry matching {}{}",
+                model.network_address, model.prefix
+            ),
+            AclExercise::Match { entry, address } => {
+                format!("Does `{}` match {}?", entry, address)
+            }
+        }
+    }
+
+    pub fn answer(&self) -> String {
+        match self {
+            AclExercise::WriteEntry { model } => {
+                format!("permit {} {}", model.network_address, model.wildcard_mask())
+            }
+            AclExercise::Match { entry, address } if entry.matches(address) => "yes".to_string(),
+            AclExercise::Match { .. } => "no".to_string(),
+        }
+    }
+
+    pub fn check(&self, answer: &str) -> bool {
+        match self {
+            AclExercise::WriteEntry { model } => match answer.parse::<AclEntry>() {
+                Ok(entry) => {
+                    entry.action == Action::Permit
+                        && entry.wildcard == model.wildcard_mask()
+                        && entry.address.to_u32() == model.network_address.to_u32()
+                }
+                Err(_) => false,
+            },
+            AclExercise::Match { entry, address } => {
+                let answer = answer.trim().to_lowercase();
+                let matches = match answer.as_str() {
+                    "yes" | "y" | "true" | "match" => true,
+                    "no" | "n" | "false" => false,
+                    _ => return false,
+                };
+                matches == entry.matches(address)
+            }
+        }
+    }
+}
+
+impl IpCalculator {
+    pub fn random_acl_exercise(&mut self) -> AclExercise {
+        let model = self.random_model();
+        if self.rng.gen_bool(0.5) {
+            return AclExercise::WriteEntry { model };
+        }
+
+        // Half of the entries care about one extra bit inside the host part,
+        // flipping that bit (or the lowest network bit) gives a near miss
+        let host_bits = 32 - model.prefix.value() as u32;
+        let mut wildcard = model.prefix.get_host_mask();
+        let mut near_miss = 1u32 << host_bits;
+        if self.rng.gen_bool(0.5) {
+            near_miss = 1u32 << self.rng.gen_range(0..host_bits);
+            wildcard &= !near_miss;
+        }
+
+        let base = model.network_address.to_u32();
+        let mut address = base | (self.rng.gen::<u32>() & wildcard);
+        if self.rng.gen_bool(0.5) {
+            address ^= near_miss;
+        }
+
+        AclExercise::Match {
+            entry: AclEntry {
+                action: Action::Permit,
+                address: IpAddress::new(Ipv4Addr::from(base).to_string()),
+                wildcard: WildcardMask::from_u32(wildcard),
+            },
+            address: IpAddress::new(Ipv4Addr::from(address).to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entry() {
+        let entry: AclEntry = "permit 172.16.0.0 0.0.255.254".parse().unwrap();
+        assert_eq!(entry.action, Action::Permit);
+        assert_eq!(entry.to_string(), "permit 172.16.0.0 0.0.255.254");
+        assert!(!entry.matches(&IpAddress::new("172.16.3.7".to_string())));
+        assert!(entry.matches(&IpAddress::new("172.16.3.8".to_string())));
+
+        let entry: AclEntry = "access-list 10 deny host 10.0.0.1".parse().unwrap();
+        assert_eq!(entry.action, Action::Deny);
+        assert_eq!(entry.wildcard.as_string(), "0.0.0.0");
+
+        let entry: AclEntry = "permit any".parse().unwrap();
+        assert!(entry.matches(&IpAddress::new("8.8.8.8".to_string())));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            "allow 10.0.0.0 0.0.0.255".parse::<AclEntry>(),
+            Err(AclError::InvalidAction(_))
+        ));
+        assert!(matches!(
+            "permit 10.0.0.0".parse::<AclEntry>(),
+            Err(AclError::Missing("wildcard mask"))
+        ));
+        assert!(matches!(
+            "permit 10.0.0.0 0.0.0.255 log".parse::<AclEntry>(),
+            Err(AclError::TrailingInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_entry_exercise() {
+        let exercise = AclExercise::WriteEntry {
+            model: IpModel::from_cidr("10.4.16.0/20").unwrap(),
+        };
+        assert_eq!(exercise.text(), "Write the ACL entry matching 10.4.16.0/20");
+        assert_eq!(exercise.answer(), "permit 10.4.16.0 0.0.15.255");
+        assert!(exercise.check("access-list 1 permit 10.4.16.0 0.0.15.255"));
+        assert!(!exercise.check("permit 10.4.16.0 255.255.240.0"));
+        assert!(!exercise.check("deny 10.4.16.0 0.0.15.255"));
+    }
+
+    #[test]
+    fn test_random_exercises_are_consistent() {
+        let mut calculator = IpCalculator::with_seed(5);
+        for _ in 0..200 {
+            let exercise = calculator.random_acl_exercise();
+            assert!(exercise.check(&exercise.answer()), "{}", exercise.text());
+            if let AclExercise::Match { entry, .. } = &exercise {
+                let opposite = if exercise.answer() == "yes" {
+                    "no"
+                } else {
+                    "yes"
+                };
+                assert!(!exercise.check(opposite));
+                assert_eq!(entry.action, Action::Permit);
+            }
+        }
+    }
+}
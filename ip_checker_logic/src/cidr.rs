@@ -0,0 +1,313 @@
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::ip_address::{IpAddress, IpAddressError};
+use crate::network_address::{NetworkAddress, NetworkAddressError};
+use crate::prefix::{Prefix, PrefixError};
+
+// A network written as `10.0.0.0/8`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cidr {
+    pub network: NetworkAddress,
+    pub prefix: Prefix,
+}
+
+#[derive(Debug, Error)]
+pub enum CidrError {
+    #[error("'{0}' is missing the /prefix")]
+    MissingPrefix(String),
+
+    #[error("Invalid prefix: {0}")]
+    InvalidPrefix(#[from] PrefixError),
+
+    #[error("Invalid network: {0}")]
+    InvalidNetwork(#[from] NetworkAddressError),
+
+    #[error("Invalid address: {0}")]
+    InvalidAddress(#[from] IpAddressError),
+
+    #[error("'{0}' is not a range like 10.0.0.0-10.0.0.255")]
+    InvalidRange(String),
+
+    #[error("Range start {start} is after its end {end}")]
+    ReversedRange { start: String, end: String },
+}
+
+impl Cidr {
+    pub fn new(network: NetworkAddress, prefix: Prefix) -> Self {
+        Self { network, prefix }
+    }
+
+    // The network containing `address`, host bits are cleared
+    pub fn from_u32(address: u32, prefix: u8) -> Result<Self, CidrError> {
+        let prefix = Prefix::new(prefix)?;
+        let network =
+            NetworkAddress::from_u32(address & prefix.get_network_mask(), prefix.value())?;
+        Ok(Self { network, prefix })
+    }
+
+    pub fn first(&self) -> u32 {
+        self.network.to_u32()
+    }
+
+    pub fn last(&self) -> u32 {
+        self.first() | self.prefix.get_host_mask()
+    }
+
+    // Number of addresses including network and broadcast
+    pub fn size(&self) -> u64 {
+        1u64 << (32 - self.prefix.value())
+    }
+
+    pub fn contains(&self, address: &IpAddress) -> bool {
+        (self.first()..=self.last()).contains(&address.to_u32())
+    }
+
+    pub fn contains_network(&self, other: &Cidr) -> bool {
+        self.first() <= other.first() && other.last() <= self.last()
+    }
+
+    pub fn overlaps(&self, other: &Cidr) -> bool {
+        self.first() <= other.last() && other.first() <= self.last()
+    }
+
+    // What is left of this network without `other`
+    pub fn subtract(&self, other: &Cidr) -> Vec<Cidr> {
+        exclude(std::slice::from_ref(self), std::slice::from_ref(other))
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = CidrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) = s
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| CidrError::MissingPrefix(s.to_string()))?;
+        let prefix = Prefix::new(
+            prefix
+                .parse()
+                .map_err(|_| PrefixError::ParseError(format!("'{}' is not a number", prefix)))?,
+        )?;
+        let network = NetworkAddress::try_new(address.to_string(), prefix.value())?;
+        Ok(Self { network, prefix })
+    }
+}
+
+impl std::fmt::Display for Cidr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.network, self.prefix)
+    }
+}
+
+// Written as `10.0.0.0/8` in plan files
+impl Serialize for Cidr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Cidr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+// Sorted, merged inclusive address ranges. u64 so the end of 255.255.255.255 can't overflow
+fn merged_ranges(networks: &[Cidr]) -> Vec<(u64, u64)> {
+    let mut ranges: Vec<(u64, u64)> = networks
+        .iter()
+        .map(|n| (n.first() as u64, n.last() as u64))
+        .collect();
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            // Overlapping or directly adjacent
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+// Largest aligned blocks first, which gives the fewest possible networks
+fn range_cidrs(mut start: u64, end: u64, out: &mut Vec<Cidr>) {
+    while start <= end {
+        let mut bits = if start == 0 {
+            32
+        } else {
+            start.trailing_zeros().min(32)
+        };
+        while start + (1u64 << bits) - 1 > end {
+            bits -= 1;
+        }
+        out.push(Cidr::from_u32(start as u32, 32 - bits as u8).unwrap());
+        start += 1u64 << bits;
+    }
+}
+
+// Minimal list of networks covering exactly the same addresses
+pub fn aggregate(networks: &[Cidr]) -> Vec<Cidr> {
+    let mut result = Vec::new();
+    for (start, end) in merged_ranges(networks) {
+        range_cidrs(start, end, &mut result);
+    }
+    result
+}
+
+// Addresses of `networks` that are in none of `removed`, as a minimal list
+pub fn exclude(networks: &[Cidr], removed: &[Cidr]) -> Vec<Cidr> {
+    let removed = merged_ranges(removed);
+    let mut result = Vec::new();
+    for (mut start, end) in merged_ranges(networks) {
+        for &(hole_start, hole_end) in &removed {
+            if hole_end < start || hole_start > end {
+                continue;
+            }
+            if hole_start > start {
+                range_cidrs(start, hole_start - 1, &mut result);
+            }
+            start = hole_end + 1;
+        }
+        if start <= end {
+            range_cidrs(start, end, &mut result);
+        }
+    }
+    result
+}
+
+// Minimal list of networks covering start..=end
+pub fn range_to_cidrs(start: &IpAddress, end: &IpAddress) -> Result<Vec<Cidr>, CidrError> {
+    let (first, last) = (start.to_u32(), end.to_u32());
+    if first > last {
+        return Err(CidrError::ReversedRange {
+            start: start.as_string(),
+            end: end.as_string(),
+        });
+    }
+    let mut result = Vec::new();
+    range_cidrs(first as u64, last as u64, &mut result);
+    Ok(result)
+}
+
+// Parses `10.0.0.0-10.0.0.255` or `10.0.0.0 - 10.0.0.255`
+pub fn parse_range(range: &str) -> Result<(IpAddress, IpAddress), CidrError> {
+    let (start, end) = range
+        .split_once('-')
+        .ok_or_else(|| CidrError::InvalidRange(range.to_string()))?;
+    Ok((start.trim().parse()?, end.trim().parse()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cidrs(list: &[&str]) -> Vec<Cidr> {
+        list.iter().map(|c| c.parse().unwrap()).collect()
+    }
+
+    fn strings(list: &[Cidr]) -> Vec<String> {
+        list.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let cidr: Cidr = "10.4.16.0/20".parse().unwrap();
+        assert_eq!(cidr.to_string(), "10.4.16.0/20");
+        assert_eq!(cidr.size(), 4096);
+        assert!(cidr.contains(&IpAddress::new("10.4.31.255".to_string())));
+        assert!(!cidr.contains(&IpAddress::new("10.4.32.0".to_string())));
+        assert!(matches!(
+            "10.4.16.1/20".parse::<Cidr>(),
+            Err(CidrError::InvalidNetwork(_))
+        ));
+        assert!(matches!(
+            "10.4.16.0".parse::<Cidr>(),
+            Err(CidrError::MissingPrefix(_))
+        ));
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let list = cidrs(&[
+            "10.0.1.0/24",
+            "10.0.0.0/24",
+            "10.0.2.0/23",
+            "10.0.3.128/25",
+            "192.168.0.0/24",
+        ]);
+        assert_eq!(
+            strings(&aggregate(&list)),
+            ["10.0.0.0/22", "192.168.0.0/24"]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_unaligned() {
+        // Adjacent but not mergeable into a single block
+        let list = cidrs(&["10.0.1.0/24", "10.0.2.0/24"]);
+        assert_eq!(strings(&aggregate(&list)), ["10.0.1.0/24", "10.0.2.0/24"]);
+    }
+
+    #[test]
+    fn test_subtract() {
+        let big: Cidr = "10.0.0.0/8".parse().unwrap();
+        let hole: Cidr = "10.1.2.0/24".parse().unwrap();
+        let rest = big.subtract(&hole);
+        assert_eq!(
+            strings(&rest),
+            [
+                "10.0.0.0/16",
+                "10.1.0.0/23",
+                "10.1.3.0/24",
+                "10.1.4.0/22",
+                "10.1.8.0/21",
+                "10.1.16.0/20",
+                "10.1.32.0/19",
+                "10.1.64.0/18",
+                "10.1.128.0/17",
+                "10.2.0.0/15",
+                "10.4.0.0/14",
+                "10.8.0.0/13",
+                "10.16.0.0/12",
+                "10.32.0.0/11",
+                "10.64.0.0/10",
+                "10.128.0.0/9"
+            ]
+        );
+        let total: u64 = rest.iter().map(Cidr::size).sum();
+        assert_eq!(total, big.size() - hole.size());
+        assert!(hole.subtract(&big).is_empty());
+    }
+
+    #[test]
+    fn test_range_to_cidrs() {
+        let (start, end) = parse_range("192.168.0.5 - 192.168.0.20").unwrap();
+        assert_eq!(
+            strings(&range_to_cidrs(&start, &end).unwrap()),
+            [
+                "192.168.0.5/32",
+                "192.168.0.6/31",
+                "192.168.0.8/29",
+                "192.168.0.16/30",
+                "192.168.0.20/32"
+            ]
+        );
+        let (start, end) = parse_range("0.0.0.0-255.255.255.255").unwrap();
+        assert_eq!(
+            strings(&range_to_cidrs(&start, &end).unwrap()),
+            ["0.0.0.0/0"]
+        );
+        assert!(matches!(
+            range_to_cidrs(&end, &start),
+            Err(CidrError::ReversedRange { .. })
+        ));
+    }
+}
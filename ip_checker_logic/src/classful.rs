@@ -0,0 +1,269 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::prefix::Prefix;
+use crate::subnet_mask::SubnetMask;
+use crate::{IpCalculator, IpModel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressClass {
+    A,
+    B,
+    C,
+    D,
+    E,
+}
+
+impl AddressClass {
+    pub fn from_first_octet(octet: u8) -> Self {
+        match octet {
+            0..=127 => AddressClass::A,
+            128..=191 => AddressClass::B,
+            192..=223 => AddressClass::C,
+            224..=239 => AddressClass::D,
+            _ => AddressClass::E,
+        }
+    }
+
+    // Multicast and experimental addresses have no default mask
+    pub fn default_prefix(&self) -> Option<u8> {
+        match self {
+            AddressClass::A => Some(8),
+            AddressClass::B => Some(16),
+            AddressClass::C => Some(24),
+            AddressClass::D | AddressClass::E => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AddressClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// How subnets are counted by older curricula
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassfulRules {
+    // Whether subnet zero and the all-ones subnet may be used
+    pub subnet_zero: bool,
+}
+
+impl Default for ClassfulRules {
+    fn default() -> Self {
+        Self { subnet_zero: true }
+    }
+}
+
+impl ClassfulRules {
+    // Without subnet zero one borrowed bit leaves no usable subnet
+    pub fn min_borrowed_bits(&self) -> u8 {
+        if self.subnet_zero {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn subnet_count(&self, borrowed_bits: u8) -> u64 {
+        let count = 1u64 << borrowed_bits;
+        if self.subnet_zero {
+            count
+        } else {
+            count.saturating_sub(2)
+        }
+    }
+}
+
+// The values a classful subnetting exercise asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClassfulField {
+    Class,
+    DefaultMask,
+    BorrowedBits,
+    Subnets,
+    HostsPerSubnet,
+}
+
+impl ClassfulField {
+    pub const ALL: [ClassfulField; 5] = [
+        ClassfulField::Class,
+        ClassfulField::DefaultMask,
+        ClassfulField::BorrowedBits,
+        ClassfulField::Subnets,
+        ClassfulField::HostsPerSubnet,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClassfulField::Class => "Address Class",
+            ClassfulField::DefaultMask => "Default Mask",
+            ClassfulField::BorrowedBits => "Borrowed Bits",
+            ClassfulField::Subnets => "Number of Subnets",
+            ClassfulField::HostsPerSubnet => "Hosts per Subnet",
+        }
+    }
+}
+
+impl IpModel {
+    pub fn address_class(&self) -> AddressClass {
+        AddressClass::from_first_octet(self.ip.to_u32().to_be_bytes()[0])
+    }
+
+    // None for class D and E, and for prefixes shorter than the default mask
+    pub fn borrowed_bits(&self) -> Option<u8> {
+        let default = self.address_class().default_prefix()?;
+        self.prefix.value().checked_sub(default)
+    }
+}
+
+// A subnetted classful network, the student names the class and counts the subnets
+#[derive(Debug, Clone)]
+pub struct ClassfulExercise {
+    pub model: IpModel,
+    pub rules: ClassfulRules,
+}
+
+impl ClassfulExercise {
+    pub fn text(&self) -> String {
+        let rule = if self.rules.subnet_zero {
+            "Subnet zero and the all-ones subnet are used."
+        } else {
+            "Subnet zero and the all-ones subnet are not used."
+        };
+        format!(
+            "The classful network of {} is subnetted with mask {}. {}",
+            self.model.ip, self.model.mask, rule
+        )
+    }
+
+    pub fn answer(&self, field: ClassfulField) -> String {
+        let class = self.model.address_class();
+        let borrowed = self.model.borrowed_bits().unwrap_or(0);
+        match field {
+            ClassfulField::Class => class.to_string(),
+            ClassfulField::DefaultMask => class.default_prefix().map_or(String::new(), |p| {
+                Prefix::new(p).unwrap().to_subnet_mask().as_string()
+            }),
+            ClassfulField::BorrowedBits => borrowed.to_string(),
+            ClassfulField::Subnets => self.rules.subnet_count(borrowed).to_string(),
+            ClassfulField::HostsPerSubnet => self.model.prefix.get_max_hosts().to_string(),
+        }
+    }
+
+    // The class may be written as "Class B", the default mask also as prefix
+    pub fn check(&self, field: ClassfulField, answer: &str) -> bool {
+        let answer = answer.trim();
+        let expected = self.answer(field);
+        match field {
+            ClassfulField::Class => {
+                let answer = answer.to_uppercase();
+                answer.trim_start_matches("CLASS").trim() == expected
+            }
+            ClassfulField::DefaultMask => {
+                answer == expected
+                    || answer.parse::<Prefix>().is_ok_and(|prefix| {
+                        prefix.to_subnet_mask() == SubnetMask::new(expected.clone())
+                    })
+            }
+            _ => answer == expected,
+        }
+    }
+}
+
+impl IpCalculator {
+    pub fn set_classful_rules(&mut self, rules: Option<ClassfulRules>) {
+        self.classful = rules;
+    }
+
+    pub fn classful_rules(&self) -> Option<&ClassfulRules> {
+        self.classful.as_ref()
+    }
+
+    // Class A to C address with enough borrowed bits for the rules
+    pub(crate) fn random_classful_model(&mut self, rules: ClassfulRules) -> IpModel {
+        let (min, max) = (self.settings.min_octet, self.settings.max_octet);
+        let mut ip: [u8; 4] = [0; 4].map(|_| self.rng.gen_range(min..=max));
+        // Settings that only allow class D and E fall back to the whole unicast range
+        let (low, high) = (min.max(1), max.min(223));
+        ip[0] = if low <= high {
+            self.rng.gen_range(low..=high)
+        } else {
+            self.rng.gen_range(1..=223)
+        };
+
+        let default = AddressClass::from_first_octet(ip[0])
+            .default_prefix()
+            .unwrap();
+        let lowest = default + rules.min_borrowed_bits();
+        let min_prefix = self.settings.min_prefix.clamp(lowest, 30);
+        let max_prefix = self.settings.max_prefix.clamp(min_prefix, 30);
+        let prefix = self.rng.gen_range(min_prefix..=max_prefix);
+        self.calculate_model(&ip, prefix)
+    }
+
+    pub fn random_classful_exercise(&mut self) -> ClassfulExercise {
+        let rules = self.classful.unwrap_or_default();
+        ClassfulExercise {
+            model: self.random_classful_model(rules),
+            rules,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(cidr: &str, subnet_zero: bool) -> ClassfulExercise {
+        ClassfulExercise {
+            model: IpModel::from_cidr(cidr).unwrap(),
+            rules: ClassfulRules { subnet_zero },
+        }
+    }
+
+    #[test]
+    fn test_address_class() {
+        assert_eq!(AddressClass::from_first_octet(10), AddressClass::A);
+        assert_eq!(AddressClass::from_first_octet(172), AddressClass::B);
+        assert_eq!(AddressClass::from_first_octet(223), AddressClass::C);
+        assert_eq!(AddressClass::from_first_octet(224).default_prefix(), None);
+    }
+
+    #[test]
+    fn test_classful_answers() {
+        let with_zero = exercise("172.16.5.9/20", true);
+        assert_eq!(with_zero.answer(ClassfulField::Class), "B");
+        assert_eq!(with_zero.answer(ClassfulField::DefaultMask), "255.255.0.0");
+        assert_eq!(with_zero.answer(ClassfulField::BorrowedBits), "4");
+        assert_eq!(with_zero.answer(ClassfulField::Subnets), "16");
+        assert_eq!(with_zero.answer(ClassfulField::HostsPerSubnet), "4094");
+
+        let without_zero = exercise("172.16.5.9/20", false);
+        assert_eq!(without_zero.answer(ClassfulField::Subnets), "14");
+        assert!(without_zero.text().ends_with("are not used."));
+    }
+
+    #[test]
+    fn test_check_notations() {
+        let exercise = exercise("192.168.1.77/27", true);
+        assert!(exercise.check(ClassfulField::Class, "class c"));
+        assert!(exercise.check(ClassfulField::DefaultMask, "/24"));
+        assert!(exercise.check(ClassfulField::DefaultMask, "255.255.255.0"));
+        assert!(!exercise.check(ClassfulField::DefaultMask, "/27"));
+        assert!(exercise.check(ClassfulField::Subnets, "8"));
+    }
+
+    #[test]
+    fn test_classful_mode_generates_valid_models() {
+        let mut calculator = IpCalculator::with_seed(4);
+        calculator.set_classful_rules(Some(ClassfulRules { subnet_zero: false }));
+        for _ in 0..300 {
+            let model = calculator.random_model();
+            assert!(model.borrowed_bits().unwrap() >= 2, "{}", model.cidr());
+            let exercise = calculator.random_classful_exercise();
+            assert_ne!(exercise.answer(ClassfulField::Subnets), "0");
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GeneratorSettings, IpCalculator, IpModel, Validator};
+
+// Messages are sent as one JSON object per line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Join { name: String },
+    Result { round: u32, result: Validator },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Welcome {
+        name: String,
+    },
+    Exercise {
+        round: u32,
+        seed: u64,
+        settings: GeneratorSettings,
+    },
+}
+
+// Every instance generates the same exercise from the pushed seed,
+// None if the peer sent settings no exercise can be drawn from
+pub fn exercise_model(seed: u64, settings: &GeneratorSettings) -> Option<IpModel> {
+    let mut calculator = IpCalculator::with_settings(settings.clone(), seed).ok()?;
+    Some(calculator.random_model())
+}
+
+pub(crate) fn send_line<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).map_err(io::Error::other)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+// Reads messages from a stream until it closes or sends something unreadable
+pub(crate) fn read_lines<T, F>(stream: TcpStream, mut handle: F)
+where
+    T: for<'de> Deserialize<'de>,
+    F: FnMut(T) -> bool,
+{
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        let Ok(message) = serde_json::from_str(&line) else {
+            break;
+        };
+        if !handle(message) {
+            break;
+        }
+    }
+}
+
+// Each student has its own writer lock, so slow connections never block the
+// shared state and a joiner's welcome is sent before any newer exercise
+type Writer = Arc<Mutex<TcpStream>>;
+
+struct Student {
+    name: String,
+    stream: Writer,
+    connected: bool,
+    results: HashMap<u32, Validator>,
+}
+
+#[derive(Default)]
+struct ServerState {
+    round: u32,
+    seed: Option<u64>,
+    students: Vec<Student>,
+}
+
+impl ServerState {
+    // A student joining again under the same name takes over their row
+    fn join(&mut self, name: String, stream: Writer) -> usize {
+        if let Some(index) = self.students.iter().position(|s| s.name == name) {
+            let student = &mut self.students[index];
+            student.stream = stream;
+            student.connected = true;
+            return index;
+        }
+        self.students.push(Student {
+            name,
+            stream,
+            connected: true,
+            results: HashMap::new(),
+        });
+        self.students.len() - 1
+    }
+
+    // Ignored if the row was taken over by a newer connection
+    fn disconnect(&mut self, index: usize, stream: &Writer) {
+        let student = &mut self.students[index];
+        if Arc::ptr_eq(&student.stream, stream) {
+            student.connected = false;
+        }
+    }
+
+    fn exercise(&self, settings: &GeneratorSettings) -> Option<ServerMessage> {
+        self.seed.map(|seed| ServerMessage::Exercise {
+            round: self.round,
+            seed,
+            settings: settings.clone(),
+        })
+    }
+}
+
+// One row of the teacher's dashboard for the current round
+#[derive(Debug, Clone, PartialEq)]
+pub struct DashboardRow {
+    pub name: String,
+    pub connected: bool,
+    pub result: Option<Validator>,
+}
+
+// Teacher side: pushes exercises to every student and collects their results.
+// Connections are served on background threads until the process exits.
+pub struct ClassroomServer {
+    addr: SocketAddr,
+    settings: GeneratorSettings,
+    state: Arc<Mutex<ServerState>>,
+}
+
+impl ClassroomServer {
+    pub fn bind(addr: impl ToSocketAddrs, settings: GeneratorSettings) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(ServerState::default()));
+
+        let accept_state = state.clone();
+        let accept_settings = settings.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = accept_state.clone();
+                let settings = accept_settings.clone();
+                thread::spawn(move || Self::serve(stream, state, settings));
+            }
+        });
+
+        Ok(Self {
+            addr,
+            settings,
+            state,
+        })
+    }
+
+    fn serve(stream: TcpStream, state: Arc<Mutex<ServerState>>, settings: GeneratorSettings) {
+        let Ok(writer) = stream.try_clone() else {
+            return;
+        };
+        let writer: Writer = Arc::new(Mutex::new(writer));
+        let mut index = None;
+        read_lines(stream, |message: ClientMessage| match (message, index) {
+            (ClientMessage::Join { name }, None) => {
+                // Held until the welcome is out, pushes to this student wait for it
+                let mut stream = writer.lock().unwrap();
+                let exercise = {
+                    let mut state = state.lock().unwrap();
+                    index = Some(state.join(name.clone(), writer.clone()));
+                    state.exercise(&settings)
+                };
+                let joined = send_line(&mut stream, &ServerMessage::Welcome { name })
+                    .and_then(|_| exercise.map_or(Ok(()), |e| send_line(&mut stream, &e)));
+                joined.is_ok()
+            }
+            (ClientMessage::Result { round, result }, Some(index)) => {
+                let mut state = state.lock().unwrap();
+                state.students[index].results.insert(round, result);
+                true
+            }
+            // Anything else breaks the protocol
+            _ => false,
+        });
+        if let Some(index) = index {
+            state.lock().unwrap().disconnect(index, &writer);
+        }
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn round(&self) -> u32 {
+        self.state.lock().unwrap().round
+    }
+
+    pub fn current_exercise(&self) -> Option<IpModel> {
+        let state = self.state.lock().unwrap();
+        state
+            .seed
+            .and_then(|seed| exercise_model(seed, &self.settings))
+    }
+
+    // Starts a new round with the exercise generated from `seed`
+    pub fn push_exercise(&self, seed: u64) {
+        let (message, writers) = {
+            let mut state = self.state.lock().unwrap();
+            state.round += 1;
+            state.seed = Some(seed);
+            let writers: Vec<(usize, Writer)> = state
+                .students
+                .iter()
+                .enumerate()
+                .filter(|(_, student)| student.connected)
+                .map(|(index, student)| (index, student.stream.clone()))
+                .collect();
+            (state.exercise(&self.settings).unwrap(), writers)
+        };
+        for (index, writer) in writers {
+            if send_line(&mut writer.lock().unwrap(), &message).is_err() {
+                self.state.lock().unwrap().disconnect(index, &writer);
+            }
+        }
+    }
+
+    pub fn dashboard(&self) -> Vec<DashboardRow> {
+        let state = self.state.lock().unwrap();
+        state
+            .students
+            .iter()
+            .map(|student| DashboardRow {
+                name: student.name.clone(),
+                connected: student.connected,
+                result: student.results.get(&state.round).cloned(),
+            })
+            .collect()
+    }
+}
+
+// Student side of the classroom connection
+pub struct ClassroomClient {
+    stream: TcpStream,
+    messages: Receiver<ServerMessage>,
+}
+
+impl ClassroomClient {
+    pub fn connect(addr: impl ToSocketAddrs, name: &str) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(addr)?;
+        send_line(
+            &mut stream,
+            &ClientMessage::Join {
+                name: name.to_string(),
+            },
+        )?;
+
+        let (sender, messages) = mpsc::channel();
+        let reader = stream.try_clone()?;
+        thread::spawn(move || read_lines(reader, |message| sender.send(message).is_ok()));
+
+        Ok(Self { stream, messages })
+    }
+
+    // Messages received since the last call, without blocking
+    pub fn poll(&self) -> Vec<ServerMessage> {
+        self.messages.try_iter().collect()
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<ServerMessage, RecvTimeoutError> {
+        self.messages.recv_timeout(timeout)
+    }
+
+    pub fn send_result(&mut self, round: u32, result: &Validator) -> io::Result<()> {
+        send_line(
+            &mut self.stream,
+            &ClientMessage::Result {
+                round,
+                result: result.clone(),
+            },
+        )
+    }
+}
+
+// The reader thread holds a clone of the socket, so close it explicitly
+impl Drop for ClassroomClient {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn wait_for<F: Fn() -> bool>(condition: F) {
+        let start = Instant::now();
+        while !condition() {
+            assert!(start.elapsed() < TIMEOUT, "timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn next_exercise(client: &ClassroomClient) -> (u32, IpModel) {
+        loop {
+            match client.recv_timeout(TIMEOUT).unwrap() {
+                ServerMessage::Exercise {
+                    round,
+                    seed,
+                    settings,
+                } => return (round, exercise_model(seed, &settings).unwrap()),
+                ServerMessage::Welcome { .. } => continue,
+            }
+        }
+    }
+
+    #[test]
+    fn test_message_format() {
+        let json = serde_json::to_string(&ClientMessage::Join {
+            name: "alice".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"type":"join","name":"alice"}"#);
+    }
+
+    #[test]
+    fn test_classroom_round() {
+        let server = ClassroomServer::bind("127.0.0.1:0", GeneratorSettings::default()).unwrap();
+        let mut alice = ClassroomClient::connect(server.local_addr(), "alice").unwrap();
+        let bob = ClassroomClient::connect(server.local_addr(), "bob").unwrap();
+        wait_for(|| server.dashboard().len() == 2);
+
+        server.push_exercise(99);
+        let (round, model) = next_exercise(&alice);
+        let (_, bob_model) = next_exercise(&bob);
+        assert_eq!(round, 1);
+        assert_eq!(model.ip, bob_model.ip);
+        assert_eq!(server.current_exercise().unwrap().ip, model.ip);
+
+        let result = Validator {
+            mask: true,
+            ..Default::default()
+        };
+        alice.send_result(round, &result).unwrap();
+        wait_for(|| server.dashboard()[0].result.is_some());
+        let dashboard = server.dashboard();
+        assert_eq!(dashboard[0].name, "alice");
+        assert_eq!(dashboard[0].result, Some(result));
+        assert_eq!(dashboard[1].result, None);
+    }
+
+    #[test]
+    fn test_late_joiner_gets_current_exercise() {
+        let server = ClassroomServer::bind("127.0.0.1:0", GeneratorSettings::default()).unwrap();
+        server.push_exercise(5);
+        let late = ClassroomClient::connect(server.local_addr(), "late").unwrap();
+        let (round, model) = next_exercise(&late);
+        assert_eq!(round, 1);
+        assert_eq!(model.ip, server.current_exercise().unwrap().ip);
+    }
+
+    #[test]
+    fn test_disconnect_is_shown() {
+        let server = ClassroomServer::bind("127.0.0.1:0", GeneratorSettings::default()).unwrap();
+        let client = ClassroomClient::connect(server.local_addr(), "carol").unwrap();
+        wait_for(|| server.dashboard().len() == 1);
+        drop(client);
+        wait_for(|| !server.dashboard()[0].connected);
+    }
+
+    #[test]
+    fn test_rejoin_reuses_row() {
+        let server = ClassroomServer::bind("127.0.0.1:0", GeneratorSettings::default()).unwrap();
+        let first = ClassroomClient::connect(server.local_addr(), "dave").unwrap();
+        wait_for(|| server.dashboard().len() == 1);
+        let second = ClassroomClient::connect(server.local_addr(), "dave").unwrap();
+        server.push_exercise(3);
+        next_exercise(&second);
+
+        // The old connection closing does not mark the new one as gone
+        drop(first);
+        thread::sleep(Duration::from_millis(100));
+        let dashboard = server.dashboard();
+        assert_eq!(dashboard.len(), 1);
+        assert!(dashboard[0].connected);
+    }
+}
@@ -0,0 +1,95 @@
+// Overlapping networks and addresses used twice, shared by plans, inventories,
+// DHCP and topology answers
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use crate::cidr::Cidr;
+use crate::ip_address::IpAddress;
+use crate::plan::Reservation;
+use crate::trie::PrefixTrie;
+
+// The gateway and reserved addresses of a subnet, named for issue messages
+pub fn fixed_addresses<'a>(
+    subnet: &'a str,
+    gateway: Option<&'a IpAddress>,
+    reservations: &'a [Reservation],
+) -> impl Iterator<Item = (&'a IpAddress, String)> {
+    let gateway = gateway.map(|gateway| (gateway, format!("gateway of {}", subnet)));
+    let hosts = reservations
+        .iter()
+        .map(|host| (&host.address, host.name.clone()));
+    gateway.into_iter().chain(hosts)
+}
+
+// Networks and addresses added so far, each with a value naming its owner
+pub struct Conflicts<N, A> {
+    networks: PrefixTrie<Vec<N>>,
+    addresses: BTreeMap<u32, A>,
+}
+
+impl<N, A> Default for Conflicts<N, A> {
+    fn default() -> Self {
+        Self {
+            networks: PrefixTrie::new(),
+            addresses: BTreeMap::new(),
+        }
+    }
+}
+
+impl<N: Clone, A: Clone> Conflicts<N, A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Owners of the earlier networks overlapping `network`, outermost first
+    pub fn add_network(&mut self, network: &Cidr, owner: N) -> Vec<N> {
+        let (address, prefix) = (&network.network, &network.prefix);
+        let overlapping = self
+            .networks
+            .overlapping(address, prefix)
+            .into_iter()
+            .flat_map(|(_, _, owners)| owners.iter().cloned())
+            .collect();
+        match self.networks.get_mut(address, prefix) {
+            Some(owners) => owners.push(owner),
+            None => {
+                self.networks.insert(address, prefix, vec![owner]);
+            }
+        }
+        overlapping
+    }
+
+    // Owner of the first earlier use of `address`
+    pub fn add_address(&mut self, address: &IpAddress, owner: A) -> Option<A> {
+        match self.addresses.entry(address.to_u32()) {
+            Entry::Occupied(first) => Some(first.get().clone()),
+            Entry::Vacant(entry) => {
+                entry.insert(owner);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflicts() {
+        let mut conflicts = Conflicts::new();
+        let mut add =
+            |cidr: &str, owner: &'static str| conflicts.add_network(&cidr.parse().unwrap(), owner);
+        assert!(add("10.0.1.0/24", "a").is_empty());
+        assert!(add("10.0.2.0/24", "b").is_empty());
+        assert_eq!(add("10.0.0.0/16", "c"), ["a", "b"]);
+        assert_eq!(add("10.0.1.0/24", "d"), ["c", "a"]);
+        assert_eq!(add("10.0.1.64/26", "e"), ["c", "a", "d"]);
+
+        let address = IpAddress::new("10.0.1.5".to_string());
+        assert_eq!(conflicts.add_address(&address, "x"), None);
+        assert_eq!(conflicts.add_address(&address, "y"), Some("x"));
+        assert_eq!(conflicts.add_address(&address, "z"), Some("x"));
+    }
+}
@@ -0,0 +1,109 @@
+// Minimal CSV reading and writing (RFC 4180 quoting, comma separated)
+
+pub fn parse(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => (),
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    // Drop blank lines
+    rows.retain(|row| !(row.len() == 1 && row[0].trim().is_empty()));
+    rows
+}
+
+// Column positions by header name, `IP Address`, `ip_address` and `ip-address` are the same
+pub struct Header(Vec<String>);
+
+impl Header {
+    pub fn new(row: &[String]) -> Self {
+        Self(
+            row.iter()
+                .map(|name| name.trim().to_lowercase().replace([' ', '-'], "_"))
+                .collect(),
+        )
+    }
+
+    pub fn find(&self, names: &[&str]) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|column| names.contains(&column.as_str()))
+    }
+}
+
+// Field of a row, empty if the row is too short
+pub fn field(row: &[String], column: Option<usize>) -> &str {
+    column
+        .and_then(|column| row.get(column))
+        .map(|value| value.trim())
+        .unwrap_or_default()
+}
+
+pub fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn write_row<S: AsRef<str>>(out: &mut String, fields: &[S]) {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| escape_field(field.as_ref()))
+        .collect();
+    out.push_str(&fields.join(","));
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple() {
+        let rows = parse("a,b,c\n1,2,3\n\n");
+        assert_eq!(rows, vec![vec!["a", "b", "c"], vec!["1", "2", "3"]]);
+    }
+
+    #[test]
+    fn test_parse_quoted() {
+        let rows = parse("\"x, y\",\"say \"\"hi\"\"\"\r\nlast,");
+        assert_eq!(rows, vec![vec!["x, y", "say \"hi\""], vec!["last", ""]]);
+    }
+
+    #[test]
+    fn test_write_row() {
+        let mut out = String::new();
+        write_row(&mut out, &["plain", "with,comma", "with \"quote\""]);
+        assert_eq!(out, "plain,\"with,comma\",\"with \"\"quote\"\"\"\n");
+    }
+}
@@ -0,0 +1,372 @@
+use std::fmt::{self, Write};
+use std::net::Ipv4Addr;
+
+use serde_json::json;
+use thiserror::Error;
+
+use crate::cidr::Cidr;
+use crate::conflicts;
+use crate::dns::dns_name;
+use crate::ip_address::IpAddress;
+use crate::plan::{is_valid_mac, AddressPlan, PlannedSubnet, Reservation};
+use crate::IpModel;
+
+// Problems that make a DHCP configuration hand out or reserve wrong addresses
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum DhcpIssue {
+    #[error("{address} ({host}) is not a usable address of {subnet} ({name})")]
+    OutsideSubnet {
+        name: String,
+        subnet: String,
+        host: String,
+        address: String,
+    },
+
+    #[error("{address} ({host}) is inside the pool {pool} of {name}")]
+    InsidePool {
+        name: String,
+        pool: String,
+        host: String,
+        address: String,
+    },
+
+    #[error("{host} has no MAC address to reserve {address} for")]
+    MissingMac { host: String, address: String },
+
+    #[error("{host} has an invalid MAC address '{mac}'")]
+    InvalidMac { host: String, mac: String },
+}
+
+impl DhcpIssue {
+    // A host without MAC is only left out of the configuration
+    pub fn is_warning(&self) -> bool {
+        matches!(self, DhcpIssue::MissingMac { .. })
+    }
+}
+
+// Addresses from `start` to `end` the server hands out dynamically
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pool {
+    pub start: IpAddress,
+    pub end: IpAddress,
+}
+
+impl Pool {
+    pub fn contains(&self, address: &IpAddress) -> bool {
+        (self.start.to_u32()..=self.end.to_u32()).contains(&address.to_u32())
+    }
+}
+
+impl fmt::Display for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.start, self.end)
+    }
+}
+
+fn address(value: u32) -> IpAddress {
+    IpAddress::new(Ipv4Addr::from(value).to_string())
+}
+
+// The host range split around the fixed addresses inside it
+fn pools(first_host: &IpAddress, last_host: &IpAddress, fixed: &[&IpAddress]) -> Vec<Pool> {
+    let (first, last) = (first_host.to_u32() as u64, last_host.to_u32() as u64);
+    let mut excluded: Vec<u64> = fixed
+        .iter()
+        .map(|a| a.to_u32() as u64)
+        .filter(|a| (first..=last).contains(a))
+        .collect();
+    excluded.sort_unstable();
+    excluded.dedup();
+
+    let mut pools = Vec::new();
+    let mut start = first;
+    for value in excluded.into_iter().chain([last + 1]) {
+        if start < value {
+            pools.push(Pool {
+                start: address(start as u32),
+                end: address((value - 1) as u32),
+            });
+        }
+        start = value + 1;
+    }
+    pools
+}
+
+// Kea and dnsmasq expect lower case MACs separated by `:`
+fn normalize_mac(mac: &str) -> String {
+    mac.replace('-', ":").to_lowercase()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DhcpSubnet {
+    pub name: String,
+    pub network: Cidr,
+    pub gateway: Option<IpAddress>,
+    pub reservations: Vec<Reservation>,
+    pub pools: Vec<Pool>,
+}
+
+impl DhcpSubnet {
+    // Pools cover every usable host except the gateway and the reservations
+    pub fn from_plan(subnet: &PlannedSubnet) -> Self {
+        let network = &subnet.network;
+        let hosts = network.hosts().len() as u64;
+        let mut dhcp = Self {
+            name: subnet.name.clone(),
+            network: network.clone(),
+            gateway: subnet.gateway.clone(),
+            reservations: subnet.reservations.clone(),
+            pools: Vec::new(),
+        };
+        dhcp.pools = pools(
+            &network.nth_host(0).unwrap(),
+            &network.nth_host(hosts - 1).unwrap(),
+            &dhcp.fixed_addresses().map(|(a, _)| a).collect::<Vec<_>>(),
+        );
+        dhcp
+    }
+
+    // The IP address of the model is used as gateway, e.g. the router interface
+    pub fn from_model(model: &IpModel) -> Self {
+        Self {
+            name: model.network().to_string(),
+            network: model.network(),
+            gateway: Some(model.ip.clone()),
+            reservations: Vec::new(),
+            pools: pools(&model.first_host, &model.last_host, &[&model.ip]),
+        }
+    }
+
+    fn fixed_addresses(&self) -> impl Iterator<Item = (&IpAddress, String)> {
+        conflicts::fixed_addresses(&self.name, self.gateway.as_ref(), &self.reservations)
+    }
+
+    pub fn check(&self) -> Vec<DhcpIssue> {
+        let mut issues = Vec::new();
+        for (address, host) in self.fixed_addresses() {
+            if !self.network.contains_host(address) {
+                issues.push(DhcpIssue::OutsideSubnet {
+                    name: self.name.clone(),
+                    subnet: self.network.to_string(),
+                    host: host.clone(),
+                    address: address.as_string(),
+                });
+            }
+            if let Some(pool) = self.pools.iter().find(|pool| pool.contains(address)) {
+                issues.push(DhcpIssue::InsidePool {
+                    name: self.name.clone(),
+                    pool: pool.to_string(),
+                    host,
+                    address: address.as_string(),
+                });
+            }
+        }
+        for host in &self.reservations {
+            match &host.mac {
+                None => issues.push(DhcpIssue::MissingMac {
+                    host: host.name.clone(),
+                    address: host.address.as_string(),
+                }),
+                Some(mac) if !is_valid_mac(mac) => issues.push(DhcpIssue::InvalidMac {
+                    host: host.name.clone(),
+                    mac: mac.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        issues
+    }
+
+    // Only reservations with a valid MAC can be written to a configuration
+    fn mac_reservations(&self) -> impl Iterator<Item = (&Reservation, String)> {
+        self.reservations.iter().filter_map(|host| {
+            host.mac
+                .as_deref()
+                .filter(|mac| is_valid_mac(mac))
+                .map(|mac| (host, normalize_mac(mac)))
+        })
+    }
+}
+
+pub fn plan_subnets(plan: &AddressPlan) -> Vec<DhcpSubnet> {
+    plan.subnets.iter().map(DhcpSubnet::from_plan).collect()
+}
+
+// Configuration for the ISC Kea DHCPv4 server
+pub fn render_kea(subnets: &[DhcpSubnet]) -> String {
+    let subnets: Vec<_> = subnets
+        .iter()
+        .enumerate()
+        .map(|(index, subnet)| {
+            let mut entry = json!({
+                "id": index + 1,
+                "subnet": subnet.network.to_string(),
+                "user-context": { "name": subnet.name },
+                "pools": subnet
+                    .pools
+                    .iter()
+                    .map(|pool| json!({ "pool": pool.to_string() }))
+                    .collect::<Vec<_>>(),
+                "reservations": subnet
+                    .mac_reservations()
+                    .map(|(host, mac)| json!({
+                        "hostname": dns_name(&host.name),
+                        "hw-address": mac,
+                        "ip-address": host.address.as_string(),
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            if let Some(gateway) = &subnet.gateway {
+                entry["option-data"] = json!([{ "name": "routers", "data": gateway.as_string() }]);
+            }
+            entry
+        })
+        .collect();
+    let config = json!({
+        "Dhcp4": {
+            "interfaces-config": { "interfaces": ["*"] },
+            "lease-database": { "type": "memfile" },
+            "subnet4": subnets,
+        }
+    });
+    serde_json::to_string_pretty(&config).unwrap() + "\n"
+}
+
+// dnsmasq.conf lines, each subnet tagged with its position and name
+pub fn render_dnsmasq(subnets: &[DhcpSubnet]) -> String {
+    let mut conf = String::new();
+    for (index, subnet) in subnets.iter().enumerate() {
+        // Names alone can be empty or the same for two subnets, e.g. "Lab A" and "Lab-A"
+        let tag = match dns_name(&subnet.name).replace('.', "-") {
+            name if name.is_empty() => format!("net{}", index),
+            name => format!("net{}-{}", index, name),
+        };
+        let mask = subnet.network.prefix.to_subnet_mask();
+        if subnet.name == subnet.network.to_string() {
+            writeln!(conf, "# {}", subnet.network).unwrap();
+        } else {
+            writeln!(conf, "# {} {}", subnet.name, subnet.network).unwrap();
+        }
+        for pool in &subnet.pools {
+            writeln!(
+                conf,
+                "dhcp-range=set:{},{},{},{}",
+                tag, pool.start, pool.end, mask
+            )
+            .unwrap();
+        }
+        if let Some(gateway) = &subnet.gateway {
+            writeln!(conf, "dhcp-option=tag:{},option:router,{}", tag, gateway).unwrap();
+        }
+        for (host, mac) in subnet.mac_reservations() {
+            writeln!(
+                conf,
+                "dhcp-host={},{},{}",
+                mac,
+                host.address,
+                dns_name(&host.name)
+            )
+            .unwrap();
+        }
+        conf.push('\n');
+    }
+    conf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn office() -> PlannedSubnet {
+        let mut office = PlannedSubnet::new("Office", "10.20.0.0/24".parse().unwrap());
+        office.gateway = Some(IpAddress::new("10.20.0.1".to_string()));
+        office.reservations.push(Reservation {
+            name: "Printer".to_string(),
+            address: IpAddress::new("10.20.0.10".to_string()),
+            mac: Some("00-11-22-AA-BB-CC".to_string()),
+            description: String::new(),
+        });
+        office
+    }
+
+    #[test]
+    fn test_pools() {
+        let subnet = DhcpSubnet::from_plan(&office());
+        let pools: Vec<String> = subnet.pools.iter().map(|p| p.to_string()).collect();
+        assert_eq!(pools, ["10.20.0.2 - 10.20.0.9", "10.20.0.11 - 10.20.0.254"]);
+        assert!(subnet.check().is_empty());
+
+        let model = IpModel::from_cidr("192.168.5.130/26").unwrap();
+        let subnet = DhcpSubnet::from_model(&model);
+        let pools: Vec<String> = subnet.pools.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            pools,
+            [
+                "192.168.5.129 - 192.168.5.129",
+                "192.168.5.131 - 192.168.5.190"
+            ]
+        );
+        assert_eq!(subnet.name, "192.168.5.128/26");
+    }
+
+    #[test]
+    fn test_check() {
+        let mut subnet = DhcpSubnet::from_plan(&office());
+        subnet.gateway = Some(IpAddress::new("10.20.1.1".to_string()));
+        subnet.pools = vec![Pool {
+            start: IpAddress::new("10.20.0.5".to_string()),
+            end: IpAddress::new("10.20.0.100".to_string()),
+        }];
+        subnet.reservations.push(Reservation {
+            name: "Camera".to_string(),
+            address: IpAddress::new("10.20.0.200".to_string()),
+            mac: None,
+            description: String::new(),
+        });
+
+        let issues: Vec<String> = subnet.check().iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            issues,
+            [
+                "10.20.1.1 (gateway of Office) is not a usable address of 10.20.0.0/24 (Office)",
+                "10.20.0.10 (Printer) is inside the pool 10.20.0.5 - 10.20.0.100 of Office",
+                "Camera has no MAC address to reserve 10.20.0.200 for",
+            ]
+        );
+        let errors = subnet.check().iter().filter(|i| !i.is_warning()).count();
+        assert_eq!(errors, 2);
+    }
+
+    #[test]
+    fn test_render() {
+        let subnets = [DhcpSubnet::from_plan(&office())];
+        let kea: serde_json::Value = serde_json::from_str(&render_kea(&subnets)).unwrap();
+        let subnet = &kea["Dhcp4"]["subnet4"][0];
+        assert_eq!(subnet["subnet"], "10.20.0.0/24");
+        assert_eq!(subnet["pools"][1]["pool"], "10.20.0.11 - 10.20.0.254");
+        assert_eq!(subnet["option-data"][0]["data"], "10.20.0.1");
+        assert_eq!(subnet["reservations"][0]["hw-address"], "00:11:22:aa:bb:cc");
+
+        let dnsmasq = render_dnsmasq(&subnets);
+        assert!(dnsmasq.contains("dhcp-range=set:net0-office,10.20.0.2,10.20.0.9,255.255.255.0\n"));
+        assert!(dnsmasq.contains("dhcp-option=tag:net0-office,option:router,10.20.0.1\n"));
+        assert!(dnsmasq.contains("dhcp-host=00:11:22:aa:bb:cc,10.20.0.10,printer\n"));
+    }
+
+    #[test]
+    fn test_dnsmasq_tags() {
+        let subnet = |name: &str, network: &str| {
+            let mut subnet = PlannedSubnet::new(name, network.parse().unwrap());
+            subnet.gateway = Some(subnet.network.nth_host(0).unwrap());
+            DhcpSubnet::from_plan(&subnet)
+        };
+        let dnsmasq = render_dnsmasq(&[
+            subnet("Lab A", "10.0.1.0/24"),
+            subnet("Lab-A", "10.0.2.0/24"),
+            subnet("???", "10.0.3.0/24"),
+        ]);
+        assert!(dnsmasq.contains("dhcp-option=tag:net0-lab-a,option:router,10.0.1.1\n"));
+        assert!(dnsmasq.contains("dhcp-option=tag:net1-lab-a,option:router,10.0.2.1\n"));
+        assert!(dnsmasq.contains("dhcp-range=set:net2,10.0.3.2,"));
+    }
+}
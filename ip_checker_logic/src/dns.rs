@@ -0,0 +1,392 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::cidr::Cidr;
+use crate::ip_address::IpAddress;
+use crate::plan::{AddressPlan, PlanIssue};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneOptions {
+    // Forward zone, e.g. example.com
+    pub domain: String,
+    // Relative to the domain, or a full name ending with a dot
+    pub nameserver: String,
+    pub hostmaster: String,
+    pub serial: u32,
+    pub ttl: u32,
+}
+
+impl Default for ZoneOptions {
+    fn default() -> Self {
+        Self {
+            domain: "example.com".to_string(),
+            nameserver: "ns1".to_string(),
+            hostmaster: "hostmaster".to_string(),
+            serial: 1,
+            ttl: 3600,
+        }
+    }
+}
+
+impl ZoneOptions {
+    fn domain(&self) -> &str {
+        self.domain.trim_end_matches('.')
+    }
+
+    // SOA mailbox, hostmaster@example.com is written as hostmaster.example.com.
+    fn mailbox(&self) -> String {
+        match self.hostmaster.split_once('@') {
+            Some((user, domain)) => format!("{}.{}.", user, domain.trim_end_matches('.')),
+            None => self.fqdn(&self.hostmaster),
+        }
+    }
+
+    fn fqdn(&self, name: &str) -> String {
+        if name.ends_with('.') {
+            name.to_string()
+        } else {
+            format!("{}.{}.", name, self.domain())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneFile {
+    // Name of the zone with the trailing dot
+    pub origin: String,
+    pub content: String,
+}
+
+impl ZoneFile {
+    // `/` of classless zones can't be used in file names
+    pub fn file_name(&self) -> String {
+        format!("db.{}", self.origin.trim_end_matches('.').replace('/', "-"))
+    }
+}
+
+// A host name usable as DNS label, other characters become `-`
+pub fn dns_name(name: &str) -> String {
+    name.split('.')
+        .map(|label| {
+            let label: String = label
+                .to_lowercase()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                .collect();
+            label.trim_matches('-').to_string()
+        })
+        .filter(|label| !label.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+// Host names of a plan with their DNS names, gateways are called gw-<subnet>
+fn plan_names(plan: &AddressPlan) -> Vec<(String, String, IpAddress)> {
+    let mut hosts = Vec::new();
+    for subnet in &plan.subnets {
+        if let Some(gateway) = &subnet.gateway {
+            let name = format!("gw-{}", subnet.name);
+            hosts.push((name.clone(), dns_name(&name), gateway.clone()));
+        }
+        for host in &subnet.reservations {
+            hosts.push((
+                host.name.clone(),
+                dns_name(&host.name),
+                host.address.clone(),
+            ));
+        }
+    }
+    hosts
+}
+
+// Named addresses of a plan
+pub fn plan_hosts(plan: &AddressPlan) -> Vec<(String, IpAddress)> {
+    plan_names(plan)
+        .into_iter()
+        .map(|(_, name, address)| (name, address))
+        .collect()
+}
+
+// Names that would give records without an owner, or two hosts the same name
+pub fn name_issues(plan: &AddressPlan) -> Vec<PlanIssue> {
+    let mut issues = Vec::new();
+    let mut seen: BTreeMap<String, String> = BTreeMap::new();
+    for (host, name, _) in plan_names(plan) {
+        if name.is_empty() {
+            issues.push(PlanIssue::EmptyDnsName { host });
+        } else if let Some(other_host) = seen.get(&name) {
+            issues.push(PlanIssue::DuplicateDnsName {
+                dns_name: name,
+                host,
+                other_host: other_host.clone(),
+            });
+        } else {
+            seen.insert(name, host);
+        }
+    }
+    issues
+}
+
+fn octets(address: u32) -> [u8; 4] {
+    address.to_be_bytes()
+}
+
+// SOA and NS records every zone starts with
+fn zone_header(origin: &str, options: &ZoneOptions) -> String {
+    let mut zone = String::new();
+    writeln!(zone, "$ORIGIN {}", origin).unwrap();
+    writeln!(zone, "$TTL {}", options.ttl).unwrap();
+    writeln!(
+        zone,
+        "@\tIN\tSOA\t{} {} (\n\t\t{}\t\t; serial\n\t\t3600\t\t; refresh\n\t\t900\t\t; retry\n\t\t1209600\t\t; expire\n\t\t300 )\t\t; negative caching TTL",
+        options.fqdn(&options.nameserver),
+        options.mailbox(),
+        options.serial
+    )
+    .unwrap();
+    writeln!(zone, "@\tIN\tNS\t{}", options.fqdn(&options.nameserver)).unwrap();
+    zone
+}
+
+pub fn forward_zone(hosts: &[(String, IpAddress)], options: &ZoneOptions) -> ZoneFile {
+    let origin = format!("{}.", options.domain());
+    let mut content = zone_header(&origin, options);
+    for (name, address) in hosts {
+        writeln!(content, "{}\tIN\tA\t{}", name, address).unwrap();
+    }
+    ZoneFile { origin, content }
+}
+
+// Reverse zone name of a network with an octet aligned prefix, e.g. 2.0.192.in-addr.arpa.
+fn octet_zone(network: &Cidr) -> String {
+    let bytes = octets(network.first());
+    let mut labels: Vec<String> = bytes[..network.prefix.value() as usize / 8]
+        .iter()
+        .map(|b| b.to_string())
+        .collect();
+    labels.reverse();
+    labels.push("in-addr.arpa.".to_string());
+    labels.join(".")
+}
+
+// RFC 2317 label of a network longer than /24 inside its /24 zone, e.g. 64/26
+fn classless_label(network: &Cidr) -> String {
+    format!("{}/{}", octets(network.first())[3], network.prefix.value())
+}
+
+// Owner name of `address` relative to an octet aligned zone
+fn relative_name(address: &IpAddress, zone_prefix: u8) -> String {
+    let bytes = octets(address.to_u32());
+    let mut labels: Vec<String> = bytes[zone_prefix as usize / 8..]
+        .iter()
+        .map(|b| b.to_string())
+        .collect();
+    labels.reverse();
+    labels.join(".")
+}
+
+// Reverse zones for all subnets of the plan. Subnets up to /24 get one zone per
+// octet boundary, longer ones a delegated RFC 2317 zone plus CNAMEs in the /24 zone
+pub fn reverse_zones(
+    subnets: &[Cidr],
+    hosts: &[(String, IpAddress)],
+    options: &ZoneOptions,
+) -> Vec<ZoneFile> {
+    // Zones in address order, each with its records
+    let mut zones: BTreeMap<(u32, u8), (String, Vec<String>)> = BTreeMap::new();
+    for subnet in subnets {
+        let prefix = subnet.prefix.value();
+        if prefix <= 24 {
+            let zone_prefix = prefix.div_ceil(8) * 8;
+            for zone in subnet.split(zone_prefix.max(8)).unwrap() {
+                zones
+                    .entry((zone.first(), zone.prefix.value()))
+                    .or_insert_with(|| (octet_zone(&zone), Vec::new()));
+            }
+            continue;
+        }
+
+        let parent = Cidr::from_u32(subnet.first(), 24).unwrap();
+        let delegation = classless_label(subnet);
+        let name = format!("{}.{}", delegation, octet_zone(&parent));
+        zones.insert((subnet.first(), prefix), (name, Vec::new()));
+        let (_, records) = zones
+            .entry((parent.first(), 24))
+            .or_insert_with(|| (octet_zone(&parent), Vec::new()));
+        records.push(format!(
+            "{}\tIN\tNS\t{}",
+            delegation,
+            options.fqdn(&options.nameserver)
+        ));
+        for host in subnet.hosts() {
+            let last = octets(host.to_u32())[3];
+            records.push(format!("{}\tIN\tCNAME\t{}.{}", last, last, delegation));
+        }
+    }
+
+    // PTR records go into the most specific zone containing the address
+    for (name, address) in hosts {
+        let value = address.to_u32();
+        let zone = zones
+            .iter_mut()
+            .filter(|((first, prefix), _)| {
+                Cidr::from_u32(*first, *prefix).unwrap().contains(address)
+            })
+            .max_by_key(|((_, prefix), _)| *prefix);
+        if let Some(((_, prefix), (_, records))) = zone {
+            let owner = if *prefix > 24 {
+                octets(value)[3].to_string()
+            } else {
+                relative_name(address, *prefix)
+            };
+            records.push(format!("{}\tIN\tPTR\t{}", owner, options.fqdn(name)));
+        }
+    }
+
+    zones
+        .into_values()
+        .map(|(origin, records)| {
+            let mut content = zone_header(&origin, options);
+            for record in records {
+                writeln!(content, "{}", record).unwrap();
+            }
+            ZoneFile { origin, content }
+        })
+        .collect()
+}
+
+// Forward and reverse zones of a plan
+pub fn plan_zones(plan: &AddressPlan, options: &ZoneOptions) -> Vec<ZoneFile> {
+    let hosts = plan_hosts(plan);
+    let subnets: Vec<Cidr> = plan.subnets.iter().map(|s| s.network.clone()).collect();
+    let mut zones = vec![forward_zone(&hosts, options)];
+    zones.extend(reverse_zones(&subnets, &hosts, options));
+    zones
+}
+
+// named.conf entries loading the zone files from `directory`
+pub fn named_conf(zones: &[ZoneFile], directory: &str) -> String {
+    let mut conf = String::new();
+    for zone in zones {
+        writeln!(
+            conf,
+            "zone \"{}\" {{\n\ttype master;\n\tfile \"{}/{}\";\n}};",
+            zone.origin.trim_end_matches('.'),
+            directory.trim_end_matches('/'),
+            zone.file_name()
+        )
+        .unwrap();
+    }
+    conf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(name: &str, address: &str) -> (String, IpAddress) {
+        (name.to_string(), IpAddress::new(address.to_string()))
+    }
+
+    fn cidrs(list: &[&str]) -> Vec<Cidr> {
+        list.iter().map(|c| c.parse().unwrap()).collect()
+    }
+
+    fn records(zone: &ZoneFile) -> Vec<&str> {
+        zone.content
+            .lines()
+            // Directives, SOA and NS of the zone and the SOA continuation lines
+            .filter(|line| !line.starts_with(['$', '@']) && !line.starts_with("\t\t"))
+            .collect()
+    }
+
+    #[test]
+    fn test_dns_name() {
+        assert_eq!(dns_name("Print Server #2"), "print-server--2");
+        assert_eq!(dns_name("web.DMZ"), "web.dmz");
+    }
+
+    #[test]
+    fn test_forward_zone() {
+        let options = ZoneOptions::default();
+        let zone = forward_zone(&[host("printer", "10.20.0.10")], &options);
+        assert_eq!(zone.origin, "example.com.");
+        assert_eq!(zone.file_name(), "db.example.com");
+        assert!(zone
+            .content
+            .contains("@\tIN\tSOA\tns1.example.com. hostmaster.example.com. ("));
+        assert!(zone.content.contains("@\tIN\tNS\tns1.example.com."));
+        let options = ZoneOptions {
+            hostmaster: "dns@example.org".to_string(),
+            ..options
+        };
+        assert!(forward_zone(&[], &options)
+            .content
+            .contains(" dns.example.org. ("));
+        assert_eq!(records(&zone), ["printer\tIN\tA\t10.20.0.10"]);
+    }
+
+    #[test]
+    fn test_octet_reverse_zones() {
+        let zones = reverse_zones(
+            &cidrs(&["10.20.0.0/23", "172.16.0.0/16"]),
+            &[host("printer", "10.20.1.10"), host("nas", "172.16.5.9")],
+            &ZoneOptions::default(),
+        );
+        let origins: Vec<&str> = zones.iter().map(|z| z.origin.as_str()).collect();
+        assert_eq!(
+            origins,
+            [
+                "0.20.10.in-addr.arpa.",
+                "1.20.10.in-addr.arpa.",
+                "16.172.in-addr.arpa."
+            ]
+        );
+        assert_eq!(records(&zones[1]), ["10\tIN\tPTR\tprinter.example.com."]);
+        assert_eq!(records(&zones[2]), ["9.5\tIN\tPTR\tnas.example.com."]);
+    }
+
+    #[test]
+    fn test_classless_delegation() {
+        let zones = reverse_zones(
+            &cidrs(&["192.0.2.64/29", "192.0.2.0/24"]),
+            &[host("router", "192.0.2.65"), host("web", "192.0.2.10")],
+            &ZoneOptions::default(),
+        );
+        let origins: Vec<&str> = zones.iter().map(|z| z.origin.as_str()).collect();
+        assert_eq!(
+            origins,
+            ["2.0.192.in-addr.arpa.", "64/29.2.0.192.in-addr.arpa."]
+        );
+        assert_eq!(zones[1].file_name(), "db.64-29.2.0.192.in-addr.arpa");
+
+        let parent = records(&zones[0]);
+        assert_eq!(parent[0], "64/29\tIN\tNS\tns1.example.com.");
+        assert_eq!(parent[1], "65\tIN\tCNAME\t65.64/29");
+        assert_eq!(parent.len(), 1 + 6 + 1);
+        assert_eq!(parent[7], "10\tIN\tPTR\tweb.example.com.");
+        assert_eq!(records(&zones[1]), ["65\tIN\tPTR\trouter.example.com."]);
+    }
+
+    #[test]
+    fn test_plan_zones() {
+        let plan = AddressPlan::parse(
+            r#"
+block = "10.0.0.0/16"
+[[subnets]]
+name = "Office"
+network = "10.0.1.0/25"
+gateway = "10.0.1.1"
+"#,
+            crate::plan::PlanFormat::Toml,
+        )
+        .unwrap();
+        let zones = plan_zones(&plan, &ZoneOptions::default());
+        assert_eq!(records(&zones[0]), ["gw-office\tIN\tA\t10.0.1.1"]);
+        assert_eq!(zones.len(), 3);
+        let conf = named_conf(&zones, "/etc/bind/zones/");
+        assert!(conf.contains(
+            "zone \"0/25.1.0.10.in-addr.arpa\" {\n\ttype master;\n\tfile \"/etc/bind/zones/db.0-25.1.0.10.in-addr.arpa\";\n};"
+        ));
+    }
+}
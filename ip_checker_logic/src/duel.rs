@@ -0,0 +1,487 @@
+use std::io;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::classroom::{exercise_model, read_lines, send_line};
+use crate::{GeneratorSettings, IpModel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Player {
+    Host,
+    Guest,
+}
+
+// Messages between the two instances, one JSON object per line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum DuelMessage {
+    Hello {
+        name: String,
+    },
+    Start {
+        host_name: String,
+        best_of: u32,
+    },
+    Round {
+        round: u32,
+        seed: u64,
+        settings: GeneratorSettings,
+    },
+    Solved {
+        round: u32,
+    },
+    RoundWon {
+        round: u32,
+        winner: Player,
+        host_score: u32,
+        guest_score: u32,
+    },
+    MatchOver {
+        winner: Player,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scoreboard {
+    pub host_name: String,
+    pub guest_name: String,
+    pub host_score: u32,
+    pub guest_score: u32,
+    pub best_of: u32,
+}
+
+impl Scoreboard {
+    pub fn wins_needed(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        if self.host_score >= self.wins_needed() {
+            Some(Player::Host)
+        } else if self.guest_score >= self.wins_needed() {
+            Some(Player::Guest)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self, player: Player) -> &str {
+        match player {
+            Player::Host => &self.host_name,
+            Player::Guest => &self.guest_name,
+        }
+    }
+}
+
+// What happened in the duel since the last poll
+#[derive(Debug, Clone)]
+pub enum DuelEvent {
+    Connected { opponent: String },
+    Round { round: u32, model: IpModel },
+    RoundWon { round: u32, winner: Player },
+    MatchOver { winner: Player },
+    Disconnected,
+}
+
+enum Incoming {
+    Connected(TcpStream),
+    Message(DuelMessage),
+    Closed,
+}
+
+// One side of a duel. The host picks the exercises and decides who
+// solved a round first, the guest follows the host's messages.
+pub struct Duel {
+    me: Player,
+    stream: Option<TcpStream>,
+    incoming: Receiver<Incoming>,
+    local_addr: Option<SocketAddr>,
+    settings: GeneratorSettings,
+    match_seed: u64,
+    scoreboard: Scoreboard,
+    round: u32,
+    round_over: bool,
+    pending: Vec<DuelEvent>,
+}
+
+impl Duel {
+    // Waits for one opponent in the background
+    pub fn host(
+        addr: impl ToSocketAddrs,
+        name: &str,
+        best_of: u32,
+        settings: GeneratorSettings,
+        match_seed: u64,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            if let Some(Ok(stream)) = listener.incoming().next() {
+                if let Ok(writer) = stream.try_clone() {
+                    let _ = sender.send(Incoming::Connected(writer));
+                    forward(stream, sender);
+                }
+            }
+        });
+
+        Ok(Self::new(
+            Player::Host,
+            None,
+            incoming,
+            Some(local_addr),
+            Scoreboard {
+                host_name: name.to_string(),
+                guest_name: String::new(),
+                host_score: 0,
+                guest_score: 0,
+                best_of: best_of.max(1),
+            },
+            settings,
+            match_seed,
+        ))
+    }
+
+    pub fn join(addr: impl ToSocketAddrs, name: &str) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(addr)?;
+        send_line(
+            &mut stream,
+            &DuelMessage::Hello {
+                name: name.to_string(),
+            },
+        )?;
+        let (sender, incoming) = mpsc::channel();
+        let reader = stream.try_clone()?;
+        thread::spawn(move || forward(reader, sender));
+
+        Ok(Self::new(
+            Player::Guest,
+            Some(stream),
+            incoming,
+            None,
+            Scoreboard {
+                host_name: String::new(),
+                guest_name: name.to_string(),
+                host_score: 0,
+                guest_score: 0,
+                best_of: 1,
+            },
+            GeneratorSettings::default(),
+            0,
+        ))
+    }
+
+    fn new(
+        me: Player,
+        stream: Option<TcpStream>,
+        incoming: Receiver<Incoming>,
+        local_addr: Option<SocketAddr>,
+        scoreboard: Scoreboard,
+        settings: GeneratorSettings,
+        match_seed: u64,
+    ) -> Self {
+        Self {
+            me,
+            stream,
+            incoming,
+            local_addr,
+            settings,
+            match_seed,
+            scoreboard,
+            round: 0,
+            round_over: true,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn me(&self) -> Player {
+        self.me
+    }
+
+    // Address the host listens on
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    pub fn scoreboard(&self) -> &Scoreboard {
+        &self.scoreboard
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn poll(&mut self) -> Vec<DuelEvent> {
+        while let Ok(incoming) = self.incoming.try_recv() {
+            match incoming {
+                Incoming::Connected(stream) => self.stream = Some(stream),
+                Incoming::Message(message) => self.handle(message),
+                Incoming::Closed => self.pending.push(DuelEvent::Disconnected),
+            }
+        }
+        std::mem::take(&mut self.pending)
+    }
+
+    // Reports that this player got every field of the round right
+    pub fn solve(&mut self, round: u32) {
+        match self.me {
+            Player::Host => self.award(Player::Host, round),
+            Player::Guest => self.send(&DuelMessage::Solved { round }),
+        }
+    }
+
+    fn handle(&mut self, message: DuelMessage) {
+        match (self.me, message) {
+            (Player::Host, DuelMessage::Hello { name }) => {
+                self.scoreboard.guest_name = name.clone();
+                self.send(&DuelMessage::Start {
+                    host_name: self.scoreboard.host_name.clone(),
+                    best_of: self.scoreboard.best_of,
+                });
+                self.pending.push(DuelEvent::Connected { opponent: name });
+                self.start_round(1);
+            }
+            (Player::Host, DuelMessage::Solved { round }) => self.award(Player::Guest, round),
+            (Player::Guest, DuelMessage::Start { host_name, best_of }) => {
+                self.scoreboard.host_name = host_name.clone();
+                self.scoreboard.best_of = best_of;
+                self.pending.push(DuelEvent::Connected {
+                    opponent: host_name,
+                });
+            }
+            (
+                Player::Guest,
+                DuelMessage::Round {
+                    round,
+                    seed,
+                    settings,
+                },
+            ) => {
+                // A round with settings nothing can be drawn from is skipped
+                if let Some(model) = exercise_model(seed, &settings) {
+                    self.round = round;
+                    self.round_over = false;
+                    self.pending.push(DuelEvent::Round { round, model });
+                }
+            }
+            (
+                Player::Guest,
+                DuelMessage::RoundWon {
+                    round,
+                    winner,
+                    host_score,
+                    guest_score,
+                },
+            ) => {
+                self.round_over = true;
+                self.scoreboard.host_score = host_score;
+                self.scoreboard.guest_score = guest_score;
+                self.pending.push(DuelEvent::RoundWon { round, winner });
+            }
+            (Player::Guest, DuelMessage::MatchOver { winner }) => {
+                self.pending.push(DuelEvent::MatchOver { winner })
+            }
+            // Messages meant for the other side are ignored
+            _ => (),
+        }
+    }
+
+    // The host only counts the first solution of the current round
+    fn award(&mut self, winner: Player, round: u32) {
+        if round != self.round || self.round_over {
+            return;
+        }
+        self.round_over = true;
+        match winner {
+            Player::Host => self.scoreboard.host_score += 1,
+            Player::Guest => self.scoreboard.guest_score += 1,
+        }
+        self.send(&DuelMessage::RoundWon {
+            round,
+            winner,
+            host_score: self.scoreboard.host_score,
+            guest_score: self.scoreboard.guest_score,
+        });
+        self.pending.push(DuelEvent::RoundWon { round, winner });
+
+        match self.scoreboard.winner() {
+            Some(winner) => {
+                self.send(&DuelMessage::MatchOver { winner });
+                self.pending.push(DuelEvent::MatchOver { winner });
+            }
+            None => self.start_round(round + 1),
+        }
+    }
+
+    fn start_round(&mut self, round: u32) {
+        let seed = self.match_seed.wrapping_add(round as u64);
+        self.round = round;
+        self.round_over = false;
+        self.send(&DuelMessage::Round {
+            round,
+            seed,
+            settings: self.settings.clone(),
+        });
+        self.pending.extend(
+            exercise_model(seed, &self.settings).map(|model| DuelEvent::Round { round, model }),
+        );
+    }
+
+    fn send(&mut self, message: &DuelMessage) {
+        let sent = match &mut self.stream {
+            Some(stream) => send_line(stream, message).is_ok(),
+            None => false,
+        };
+        if !sent {
+            self.pending.push(DuelEvent::Disconnected);
+        }
+    }
+}
+
+// The reader thread holds a clone of the socket, so close it explicitly
+impl Drop for Duel {
+    fn drop(&mut self) {
+        if let Some(stream) = &self.stream {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn forward(stream: TcpStream, sender: Sender<Incoming>) {
+    read_lines(stream, |message| {
+        sender.send(Incoming::Message(message)).is_ok()
+    });
+    let _ = sender.send(Incoming::Closed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    // Polls both sides until `side` reports an event matching `matches`
+    fn wait_for<F: Fn(&DuelEvent) -> bool>(
+        host: &mut Duel,
+        guest: &mut Duel,
+        guest_side: bool,
+        matches: F,
+    ) -> DuelEvent {
+        let start = Instant::now();
+        loop {
+            let host_events = host.poll();
+            let guest_events = guest.poll();
+            let events = if guest_side {
+                guest_events
+            } else {
+                host_events
+            };
+            if let Some(event) = events.into_iter().find(|e| matches(e)) {
+                return event;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn start_duel(best_of: u32) -> (Duel, Duel) {
+        let host = Duel::host(
+            "127.0.0.1:0",
+            "hannah",
+            best_of,
+            GeneratorSettings::default(),
+            7,
+        )
+        .unwrap();
+        let guest = Duel::join(host.local_addr().unwrap(), "gus").unwrap();
+        (host, guest)
+    }
+
+    #[test]
+    fn test_same_exercise_on_both_sides() {
+        let (mut host, mut guest) = start_duel(3);
+        let DuelEvent::Round {
+            model: guest_model, ..
+        } = wait_for(&mut host, &mut guest, true, |e| {
+            matches!(e, DuelEvent::Round { .. })
+        })
+        else {
+            unreachable!()
+        };
+        assert_eq!(host.round(), 1);
+        assert_eq!(
+            exercise_model(8, &GeneratorSettings::default()).unwrap().ip,
+            guest_model.ip
+        );
+        assert_eq!(guest.scoreboard().host_name, "hannah");
+        assert_eq!(host.scoreboard().guest_name, "gus");
+    }
+
+    #[test]
+    fn test_first_solution_wins_round() {
+        let (mut host, mut guest) = start_duel(3);
+        wait_for(&mut host, &mut guest, true, |e| {
+            matches!(e, DuelEvent::Round { round: 1, .. })
+        });
+
+        guest.solve(1);
+        wait_for(&mut host, &mut guest, false, |e| {
+            matches!(
+                e,
+                DuelEvent::RoundWon {
+                    round: 1,
+                    winner: Player::Guest
+                }
+            )
+        });
+        // Too late, the round is already over
+        host.solve(1);
+        assert_eq!(host.scoreboard().guest_score, 1);
+        assert_eq!(host.scoreboard().host_score, 0);
+        wait_for(&mut host, &mut guest, true, |e| {
+            matches!(e, DuelEvent::Round { round: 2, .. })
+        });
+        assert_eq!(guest.scoreboard().guest_score, 1);
+    }
+
+    #[test]
+    fn test_best_of_three() {
+        let (mut host, mut guest) = start_duel(3);
+        wait_for(&mut host, &mut guest, true, |e| {
+            matches!(e, DuelEvent::Round { round: 1, .. })
+        });
+        host.solve(1);
+        host.solve(2);
+        wait_for(&mut host, &mut guest, true, |e| {
+            matches!(
+                e,
+                DuelEvent::MatchOver {
+                    winner: Player::Host
+                }
+            )
+        });
+        assert_eq!(guest.scoreboard().host_score, 2);
+        assert_eq!(guest.scoreboard().winner(), Some(Player::Host));
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let (mut host, mut guest) = start_duel(3);
+        wait_for(&mut host, &mut guest, true, |e| {
+            matches!(e, DuelEvent::Round { .. })
+        });
+        drop(guest);
+        let start = Instant::now();
+        while !host
+            .poll()
+            .iter()
+            .any(|e| matches!(e, DuelEvent::Disconnected))
+        {
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
@@ -0,0 +1,666 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Field, GeneratorSettings, InputIpModel, IpCalculator, IpModel, SettingsError};
+
+#[derive(Debug, Error)]
+pub enum ExamError {
+    #[error("Invalid exam file: {0}")]
+    InvalidFile(#[from] toml::de::Error),
+
+    #[error("Failed to write exam file: {0}")]
+    Serialize(#[from] toml::ser::Error),
+
+    #[error("Invalid exercise '{0}': expected an address with prefix like 192.168.1.1/24")]
+    InvalidExercise(String),
+
+    #[error("Invalid exam settings: {0}")]
+    InvalidSettings(#[from] SettingsError),
+
+    #[error("Exam allows no attempts, expected at least 1")]
+    NoAttempts,
+
+    #[error("Exam has no exercises")]
+    NoExercises,
+
+    #[error("Invalid signing key")]
+    InvalidSigningKey,
+
+    #[error("Invalid verifying key in exam file")]
+    InvalidVerifyingKey,
+
+    #[error("Signing key does not belong to this exam")]
+    KeyMismatch,
+
+    #[error("Exam has no verifying key to pair a signing key with")]
+    MissingVerifyingKey,
+
+    #[error("Not an encrypted exam package")]
+    InvalidPackage,
+
+    #[error("Wrong password")]
+    WrongPassword,
+
+    #[error("Failed to derive key from password: {0}")]
+    KeyDerivation(String),
+}
+
+// Layout of an encrypted exam package: magic, salt, nonce, ciphertext
+const PACKAGE_MAGIC: &[u8; 8] = b"IPCEXAM1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum VerificationError {
+    #[error("Exam has no verifying key, results can not be verified")]
+    UnsignedExam,
+
+    #[error("Result file is not signed")]
+    MissingSignature,
+
+    #[error("Signature does not match the result")]
+    InvalidSignature,
+
+    #[error("Result belongs to exam '{found}', expected '{expected}'")]
+    WrongExam { expected: String, found: String },
+
+    #[error("Exercise seed {found} does not match the exam seed {expected}")]
+    WrongSeed { expected: u64, found: u64 },
+
+    #[error("Exercises in the result do not match the exam")]
+    WrongExercises,
+
+    #[error("Recorded score {recorded} does not match the recomputed score {recomputed}")]
+    WrongScore { recorded: usize, recomputed: usize },
+}
+
+// Exam definition written by the teacher, either as a fixed exercise
+// list or as a seed the exercises are generated from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Exam {
+    pub id: String,
+    pub title: String,
+    pub seed: u64,
+    pub count: usize,
+    pub settings: GeneratorSettings,
+    pub exercises: Vec<String>,
+    pub time_limit_minutes: u32,
+    pub attempts: u32,
+    pub hints: bool,
+    // Hex encoded Ed25519 public key the teacher verifies results with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verifying_key: Option<String>,
+    // Private half the app signs results with. It only travels inside an
+    // encrypted package and is never written by `to_toml`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+}
+
+impl Default for Exam {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            title: "Exam".to_string(),
+            seed: 0,
+            count: 5,
+            settings: GeneratorSettings::default(),
+            exercises: Vec::new(),
+            time_limit_minutes: 30,
+            attempts: 1,
+            hints: false,
+            verifying_key: None,
+            signing_key: None,
+        }
+    }
+}
+
+impl Exam {
+    pub fn from_toml(text: &str) -> Result<Self, ExamError> {
+        let exam: Self = toml::from_str(text)?;
+        exam.validate()?;
+        Ok(exam)
+    }
+
+    // A bad exam fails when it is loaded, not halfway through an exercise
+    pub fn validate(&self) -> Result<(), ExamError> {
+        self.settings.validate()?;
+        if self.attempts == 0 {
+            return Err(ExamError::NoAttempts);
+        }
+        Ok(())
+    }
+
+    // The exam without its signing key, safe to hand out or keep for verification
+    pub fn to_toml(&self) -> Result<String, ExamError> {
+        let public = Self {
+            signing_key: None,
+            ..self.clone()
+        };
+        Ok(toml::to_string(&public)?)
+    }
+
+    // Creates a fresh key pair for this exam, keeps the public half and
+    // returns the private half for the teacher to store separately
+    pub fn generate_key_pair(&mut self) -> String {
+        let key = SigningKey::from_bytes(&rand::thread_rng().gen());
+        self.verifying_key = Some(to_hex(key.verifying_key().as_bytes()));
+        to_hex(key.as_bytes())
+    }
+
+    // Adds the private key from `generate_key_pair`, e.g. before packaging
+    pub fn set_signing_key(&mut self, hex: &str) -> Result<(), ExamError> {
+        let key = parse_signing_key(hex.trim())?;
+        let expected = self
+            .verifying_key()?
+            .ok_or(ExamError::MissingVerifyingKey)?;
+        if key.verifying_key() != expected {
+            return Err(ExamError::KeyMismatch);
+        }
+        self.signing_key = Some(hex.trim().to_string());
+        Ok(())
+    }
+
+    pub fn signing_key(&self) -> Result<Option<SigningKey>, ExamError> {
+        self.signing_key
+            .as_deref()
+            .map(parse_signing_key)
+            .transpose()
+    }
+
+    pub fn verifying_key(&self) -> Result<Option<VerifyingKey>, ExamError> {
+        let Some(hex) = &self.verifying_key else {
+            return Ok(None);
+        };
+        let bytes: [u8; 32] = from_hex(hex)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ExamError::InvalidVerifyingKey)?;
+        VerifyingKey::from_bytes(&bytes)
+            .map(Some)
+            .map_err(|_| ExamError::InvalidVerifyingKey)
+    }
+
+    pub fn is_package(bytes: &[u8]) -> bool {
+        bytes.starts_with(PACKAGE_MAGIC)
+    }
+
+    // Encrypts the whole exam so its exercises can not be inspected without the password
+    pub fn to_package(&self, password: &str) -> Result<Vec<u8>, ExamError> {
+        let mut rng = rand::thread_rng();
+        let salt: [u8; SALT_LEN] = rng.gen();
+        let nonce: [u8; NONCE_LEN] = rng.gen();
+        let cipher = package_cipher(password, &salt)?;
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), toml::to_string(self)?.as_bytes())
+            .expect("encrypting an in-memory buffer can not fail");
+
+        let mut package =
+            Vec::with_capacity(PACKAGE_MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+        package.extend_from_slice(PACKAGE_MAGIC);
+        package.extend_from_slice(&salt);
+        package.extend_from_slice(&nonce);
+        package.extend_from_slice(&ciphertext);
+        Ok(package)
+    }
+
+    pub fn from_package(package: &[u8], password: &str) -> Result<Self, ExamError> {
+        let header_len = PACKAGE_MAGIC.len() + SALT_LEN + NONCE_LEN;
+        if !Self::is_package(package) || package.len() < header_len {
+            return Err(ExamError::InvalidPackage);
+        }
+        let salt = &package[PACKAGE_MAGIC.len()..PACKAGE_MAGIC.len() + SALT_LEN];
+        let nonce = &package[PACKAGE_MAGIC.len() + SALT_LEN..header_len];
+        let cipher = package_cipher(password, salt)?;
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), &package[header_len..])
+            .map_err(|_| ExamError::WrongPassword)?;
+        let text = String::from_utf8(plaintext).map_err(|_| ExamError::InvalidPackage)?;
+        Self::from_toml(&text)
+    }
+
+    pub fn exercises(&self) -> Result<Vec<IpModel>, ExamError> {
+        let exercises = if self.exercises.is_empty() {
+            IpCalculator::with_settings(self.settings.clone(), self.seed)?.random_models(self.count)
+        } else {
+            self.exercises
+                .iter()
+                .map(|cidr| {
+                    IpModel::from_cidr(cidr).ok_or_else(|| ExamError::InvalidExercise(cidr.clone()))
+                })
+                .collect::<Result<_, _>>()?
+        };
+        if exercises.is_empty() {
+            return Err(ExamError::NoExercises);
+        }
+        Ok(exercises)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    Submitted,
+    TimeUp,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExerciseResult {
+    pub exercise: String,
+    pub attempts: u32,
+    pub points: usize,
+    pub answers: InputIpModel,
+}
+
+// What the app writes when an exam ends
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExamResult {
+    pub exam_id: String,
+    pub seed: u64,
+    pub student: String,
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub reason: FinishReason,
+    pub points: usize,
+    pub max_points: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    pub exercises: Vec<ExerciseResult>,
+}
+
+impl ExamResult {
+    pub fn from_toml(text: &str) -> Result<Self, ExamError> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String, ExamError> {
+        Ok(toml::to_string(self)?)
+    }
+
+    // The signature covers the serialized result without the signature itself.
+    // TOML has no integers above i64::MAX, so such seeds fail here.
+    fn signed_payload(&self) -> Result<Vec<u8>, ExamError> {
+        let unsigned = Self {
+            signature: None,
+            ..self.clone()
+        };
+        Ok(toml::to_string(&unsigned)?.into_bytes())
+    }
+
+    pub fn sign(&mut self, key: &SigningKey) -> Result<(), ExamError> {
+        let signature = key.sign(&self.signed_payload()?);
+        self.signature = Some(to_hex(&signature.to_bytes()));
+        Ok(())
+    }
+
+    // Checks the signature and that the result matches the exam, returns the recomputed points
+    pub fn verify(&self, exam: &Exam) -> Result<usize, VerificationError> {
+        let key = exam
+            .verifying_key()
+            .ok()
+            .flatten()
+            .ok_or(VerificationError::UnsignedExam)?;
+        let signature = self
+            .signature
+            .as_deref()
+            .ok_or(VerificationError::MissingSignature)?;
+        let signature: [u8; 64] = from_hex(signature)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(VerificationError::InvalidSignature)?;
+        // A result that does not serialize can not have been signed either
+        let payload = self
+            .signed_payload()
+            .map_err(|_| VerificationError::InvalidSignature)?;
+        key.verify(&payload, &Signature::from_bytes(&signature))
+            .map_err(|_| VerificationError::InvalidSignature)?;
+
+        if self.exam_id != exam.id {
+            return Err(VerificationError::WrongExam {
+                expected: exam.id.clone(),
+                found: self.exam_id.clone(),
+            });
+        }
+        if self.seed != exam.seed {
+            return Err(VerificationError::WrongSeed {
+                expected: exam.seed,
+                found: self.seed,
+            });
+        }
+
+        let models = exam
+            .exercises()
+            .map_err(|_| VerificationError::WrongExercises)?;
+        let same_exercises = models.len() == self.exercises.len()
+            && models
+                .iter()
+                .zip(&self.exercises)
+                .all(|(model, result)| model.cidr() == result.exercise);
+        if !same_exercises {
+            return Err(VerificationError::WrongExercises);
+        }
+
+        // Never trust the recorded points, recompute them from the answers
+        let recomputed: usize = models
+            .iter()
+            .zip(&self.exercises)
+            .map(|(model, result)| model.validate(&result.answers).score())
+            .sum();
+        let recorded: usize = self.exercises.iter().map(|e| e.points).sum();
+        if recorded != recomputed || self.points != recomputed {
+            return Err(VerificationError::WrongScore {
+                recorded: self.points,
+                recomputed,
+            });
+        }
+        Ok(recomputed)
+    }
+}
+
+// Progress of one student through an exam
+#[derive(Debug, Clone)]
+pub struct ExamAttempt {
+    pub exam: Exam,
+    pub student: String,
+    pub exercises: Vec<IpModel>,
+    pub answers: Vec<InputIpModel>,
+    pub attempts: Vec<u32>,
+    pub started_at: u64,
+}
+
+impl ExamAttempt {
+    pub fn start(exam: Exam, student: String) -> Result<Self, ExamError> {
+        let exercises = exam.exercises()?;
+        Ok(Self {
+            answers: vec![InputIpModel::default(); exercises.len()],
+            attempts: vec![0; exercises.len()],
+            exam,
+            student,
+            exercises,
+            started_at: unix_time(),
+        })
+    }
+
+    pub fn can_submit(&self, index: usize) -> bool {
+        self.attempts[index] < self.exam.attempts
+    }
+
+    // Records an answer; returns false once the allowed attempts are used up
+    pub fn submit(&mut self, index: usize, answers: InputIpModel) -> bool {
+        if !self.can_submit(index) {
+            return false;
+        }
+        self.attempts[index] += 1;
+        self.answers[index] = answers;
+        true
+    }
+
+    pub fn finish(&self, reason: FinishReason) -> ExamResult {
+        let exercises: Vec<ExerciseResult> = self
+            .exercises
+            .iter()
+            .zip(&self.answers)
+            .zip(&self.attempts)
+            .map(|((model, answers), attempts)| ExerciseResult {
+                exercise: model.cidr(),
+                attempts: *attempts,
+                points: model.validate(answers).score(),
+                answers: answers.clone(),
+            })
+            .collect();
+        let mut result = ExamResult {
+            exam_id: self.exam.id.clone(),
+            seed: self.exam.seed,
+            student: self.student.clone(),
+            started_at: self.started_at,
+            finished_at: unix_time(),
+            reason,
+            points: exercises.iter().map(|e| e.points).sum(),
+            max_points: exercises.len() * Field::ALL.len(),
+            signature: None,
+            exercises,
+        };
+        if let Ok(Some(key)) = self.exam.signing_key() {
+            // Fails only for results `to_toml` can not write either, which
+            // reports the error when the result is saved
+            let _ = result.sign(&key);
+        }
+        result
+    }
+}
+
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn parse_signing_key(hex: &str) -> Result<SigningKey, ExamError> {
+    let bytes: [u8; 32] = from_hex(hex)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ExamError::InvalidSigningKey)?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+fn package_cipher(password: &str, salt: &[u8]) -> Result<ChaCha20Poly1305, ExamError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| ExamError::KeyDerivation(e.to_string()))?;
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAM: &str = r#"
+id = "midterm"
+title = "Subnetting Midterm"
+seed = 42
+count = 3
+time_limit_minutes = 20
+attempts = 2
+"#;
+
+    #[test]
+    fn test_parse_exam() {
+        let exam = Exam::from_toml(EXAM).unwrap();
+        assert_eq!(exam.id, "midterm");
+        assert_eq!(exam.attempts, 2);
+        assert!(!exam.hints);
+        assert_eq!(exam.settings, GeneratorSettings::default());
+        assert_eq!(exam.exercises().unwrap().len(), 3);
+        assert_eq!(Exam::from_toml(&exam.to_toml().unwrap()).unwrap(), exam);
+    }
+
+    #[test]
+    fn test_invalid_exam() {
+        assert!(matches!(
+            Exam::from_toml("[settings]\nmin_prefix = 29"),
+            Err(ExamError::InvalidSettings(_))
+        ));
+        assert!(matches!(
+            Exam::from_toml("[settings]\nmax_prefix = 33"),
+            Err(ExamError::InvalidSettings(_))
+        ));
+        assert!(matches!(
+            Exam::from_toml("attempts = 0"),
+            Err(ExamError::NoAttempts)
+        ));
+    }
+
+    #[test]
+    fn test_fixed_exercises() {
+        let exam = Exam::from_toml("exercises = [\"192.168.1.1/24\", \"bogus\"]").unwrap();
+        assert!(matches!(
+            exam.exercises(),
+            Err(ExamError::InvalidExercise(cidr)) if cidr == "bogus"
+        ));
+    }
+
+    #[test]
+    fn test_attempt_limits_and_result() {
+        let exam = Exam::from_toml("exercises = [\"192.168.1.1/24\"]").unwrap();
+        let mut attempt = ExamAttempt::start(exam, "alice".to_string()).unwrap();
+        let mut answers = InputIpModel::default();
+        answers.set(Field::Mask, "255.255.255.0".to_string());
+        assert!(attempt.submit(0, answers.clone()));
+        assert!(!attempt.submit(0, answers));
+
+        let result = attempt.finish(FinishReason::TimeUp);
+        assert_eq!(result.points, 1);
+        assert_eq!(result.max_points, 6);
+        assert_eq!(result.exercises[0].exercise, "192.168.1.1/24");
+        let text = result.to_toml().unwrap();
+        assert_eq!(ExamResult::from_toml(&text).unwrap(), result);
+    }
+
+    // The exam the student's app runs and the teacher's copy without the signing key
+    fn signed_result() -> (Exam, ExamResult) {
+        let mut exam = Exam::from_toml(EXAM).unwrap();
+        let signing_key = exam.generate_key_pair();
+        let teacher = Exam::from_toml(&exam.to_toml().unwrap()).unwrap();
+        assert_eq!(teacher.signing_key, None);
+        exam.set_signing_key(&signing_key).unwrap();
+        let mut attempt = ExamAttempt::start(exam, "bob".to_string()).unwrap();
+        let correct = attempt.exercises[0].clone();
+        let mut answers = InputIpModel::default();
+        answers.set(Field::Mask, correct.mask.as_string());
+        answers.set(Field::LastHost, correct.last_host.as_string());
+        attempt.submit(0, answers);
+        (teacher, attempt.finish(FinishReason::Submitted))
+    }
+
+    #[test]
+    fn test_signing_key_must_match() {
+        let mut exam = Exam::from_toml(EXAM).unwrap();
+        let other_key = exam.clone().generate_key_pair();
+        assert!(matches!(
+            exam.set_signing_key(&other_key),
+            Err(ExamError::MissingVerifyingKey)
+        ));
+        exam.generate_key_pair();
+        assert!(matches!(
+            exam.set_signing_key(&other_key),
+            Err(ExamError::KeyMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_verify_signed_result() {
+        let (exam, result) = signed_result();
+        assert!(result.signature.is_some());
+        let text = result.to_toml().unwrap();
+        assert_eq!(ExamResult::from_toml(&text).unwrap().verify(&exam), Ok(2));
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let (exam, result) = signed_result();
+
+        let mut edited = result.clone();
+        edited.points = 18;
+        assert_eq!(
+            edited.verify(&exam),
+            Err(VerificationError::InvalidSignature)
+        );
+
+        let mut other_exam = exam.clone();
+        other_exam.generate_key_pair();
+        assert_eq!(
+            result.verify(&other_exam),
+            Err(VerificationError::InvalidSignature)
+        );
+
+        let mut unsigned = result.clone();
+        unsigned.signature = None;
+        assert_eq!(
+            unsigned.verify(&exam),
+            Err(VerificationError::MissingSignature)
+        );
+    }
+
+    #[test]
+    fn test_verify_recomputes_score() {
+        let mut exam = Exam::from_toml(EXAM).unwrap();
+        let signing_key = exam.generate_key_pair();
+        exam.set_signing_key(&signing_key).unwrap();
+        let mut result = ExamAttempt::start(exam.clone(), "eve".to_string())
+            .unwrap()
+            .finish(FinishReason::Submitted);
+        // A result signed with the right key but an inflated score
+        result.points = 10;
+        result.exercises[0].points = 10;
+        result.sign(&exam.signing_key().unwrap().unwrap()).unwrap();
+        assert_eq!(
+            result.verify(&exam),
+            Err(VerificationError::WrongScore {
+                recorded: 10,
+                recomputed: 0
+            })
+        );
+
+        // TOML can not hold such a seed, so there is nothing to sign
+        result.seed = u64::MAX;
+        assert!(result.sign(&exam.signing_key().unwrap().unwrap()).is_err());
+        assert_eq!(
+            result.verify(&exam),
+            Err(VerificationError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_hex_roundtrip() {
+        assert_eq!(from_hex(&to_hex(&[0, 15, 255])), Some(vec![0, 15, 255]));
+        assert_eq!(from_hex("abc"), None);
+    }
+
+    #[test]
+    fn test_package_roundtrip() {
+        let mut exam = Exam::from_toml(EXAM).unwrap();
+        let signing_key = exam.generate_key_pair();
+        assert!(!exam.to_toml().unwrap().contains(&signing_key));
+        exam.set_signing_key(&signing_key).unwrap();
+        let package = exam.to_package("open sesame").unwrap();
+        assert!(Exam::is_package(&package));
+        // Neither the seed nor the key are readable in the package
+        let raw = String::from_utf8_lossy(&package);
+        assert!(!raw.contains("seed"));
+        assert!(!raw.contains(exam.signing_key.as_deref().unwrap()));
+        assert_eq!(Exam::from_package(&package, "open sesame").unwrap(), exam);
+    }
+
+    #[test]
+    fn test_package_wrong_password() {
+        let package = Exam::from_toml(EXAM).unwrap().to_package("right").unwrap();
+        assert!(matches!(
+            Exam::from_package(&package, "wrong"),
+            Err(ExamError::WrongPassword)
+        ));
+        assert!(matches!(
+            Exam::from_package(EXAM.as_bytes(), "right"),
+            Err(ExamError::InvalidPackage)
+        ));
+    }
+}
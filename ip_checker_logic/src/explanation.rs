@@ -0,0 +1,99 @@
+use crate::{Field, IpModel};
+
+// Dotted binary notation, e.g. 11000000.10101000.00000001.00000001
+pub fn to_binary(value: u32) -> String {
+    value
+        .to_be_bytes()
+        .iter()
+        .map(|octet| format!("{:08b}", octet))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+impl IpModel {
+    // Step by step working that leads to the answer of a field
+    pub fn explain(&self, field: Field) -> Vec<String> {
+        let bits = self.prefix.value();
+        let ip = self.ip.to_u32();
+        let mask = self.prefix.get_network_mask();
+        let network = self.network_address.to_u32();
+        let broadcast = self.broadcast_address.to_u32();
+
+        match field {
+            Field::Mask => vec![
+                format!(
+                    "{} means the first {} bits are network bits and the remaining {} are host bits.",
+                    self.prefix,
+                    bits,
+                    32 - bits
+                ),
+                format!("Mask in binary: {}", to_binary(mask)),
+                format!("Each octet converted to decimal gives {}.", self.mask),
+            ],
+            Field::NetworkAddress => vec![
+                "The network address is the IP address AND the subnet mask.".to_string(),
+                format!("IP address:  {} ({})", to_binary(ip), self.ip),
+                format!("Subnet mask: {} ({})", to_binary(mask), self.mask),
+                format!("AND result:  {} ({})", to_binary(network), self.network_address),
+            ],
+            Field::BroadcastAddress => vec![
+                "The broadcast address is the network address with all host bits set to 1."
+                    .to_string(),
+                format!("Network:     {} ({})", to_binary(network), self.network_address),
+                format!("Host bits:   {}", to_binary(self.prefix.get_host_mask())),
+                format!(
+                    "OR result:   {} ({})",
+                    to_binary(broadcast),
+                    self.broadcast_address
+                ),
+            ],
+            Field::FirstHost => vec![
+                "The first host is the address right after the network address.".to_string(),
+                format!("{} + 1 = {}", self.network_address, self.first_host),
+            ],
+            Field::LastHost => vec![
+                "The last host is the address right before the broadcast address.".to_string(),
+                format!("{} - 1 = {}", self.broadcast_address, self.last_host),
+            ],
+            Field::PossibleHosts if bits >= 31 => vec![format!(
+                "A {} network has no room for a network and a broadcast address plus hosts, so 0 hosts are counted.",
+                self.prefix
+            )],
+            Field::PossibleHosts => vec![
+                format!("There are 32 - {} = {} host bits.", bits, 32 - bits),
+                "The network and broadcast addresses can not be used by hosts.".to_string(),
+                format!(
+                    "2^{} - 2 = {}",
+                    32 - bits,
+                    self.possible_hosts.unwrap_or_default()
+                ),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IpCalculator;
+
+    #[test]
+    fn test_to_binary() {
+        assert_eq!(to_binary(0xC0A80101), "11000000.10101000.00000001.00000001");
+    }
+
+    #[test]
+    fn test_explain_network_address() {
+        let model = IpCalculator::new().calculate_model(&[192, 168, 1, 1], 24);
+        let steps = model.explain(Field::NetworkAddress);
+        assert_eq!(steps.len(), 4);
+        assert!(steps[3].contains("11000000.10101000.00000001.00000000 (192.168.1.0)"));
+    }
+
+    #[test]
+    fn test_explain_possible_hosts() {
+        let model = IpCalculator::new().calculate_model(&[192, 168, 1, 1], 24);
+        let steps = model.explain(Field::PossibleHosts);
+        assert_eq!(steps.last().unwrap(), "2^8 - 2 = 254");
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt::Write;
+
+use crate::export::{escape_xml, question_subject};
+use crate::{Field, IpModel};
+
+pub const NOTE_TYPE: &str = "IP Checker";
+pub const NOTE_FIELDS: [&str; 5] = ["IP", "Prefix", "Asked", "Answer", "Explanation"];
+
+pub const FRONT_TEMPLATE: &str = "<div class=\"ip\">{{IP}}{{Prefix}}</div>\n\
+<div>What is the {{Asked}}?</div>";
+
+pub const BACK_TEMPLATE: &str = "{{FrontSide}}\n\
+<hr id=\"answer\">\n\
+<div class=\"answer\">{{Answer}}</div>\n\
+<div class=\"explanation\">{{Explanation}}</div>";
+
+pub const STYLING: &str =
+    ".card { font-family: sans-serif; font-size: 20px; text-align: center; }\n\
+.ip { font-size: 28px; font-weight: bold; }\n\
+.answer { font-size: 26px; color: #2a7; }\n\
+.explanation { font-family: monospace; font-size: 14px; text-align: left; margin-top: 1em; }";
+
+// Anki's tab separated import format, one note per field of every exercise
+pub fn to_anki_tsv(models: &[IpModel], deck: &str) -> String {
+    let mut tsv = String::new();
+    tsv.push_str("#separator:tab\n#html:true\n");
+    writeln!(tsv, "#notetype:{}", NOTE_TYPE).unwrap();
+    writeln!(tsv, "#deck:{}", deck).unwrap();
+    tsv.push_str("#guid column:1\n#tags column:7\n");
+    writeln!(tsv, "#columns:GUID\t{}\tTags", NOTE_FIELDS.join("\t")).unwrap();
+
+    for model in models {
+        for field in Field::ALL {
+            let explanation = model
+                .explain(field)
+                .iter()
+                .map(|line| escape_xml(line))
+                .collect::<Vec<_>>()
+                .join("<br>");
+            let columns = [
+                guid(model, field),
+                model.ip.to_string(),
+                model.prefix.to_string(),
+                question_subject(field),
+                escape_xml(&model.answer(field)),
+                explanation,
+                format!(
+                    "subnetting {}",
+                    field.label().replace(' ', "_").to_lowercase()
+                ),
+            ];
+            let columns: Vec<String> = columns.iter().map(|c| sanitize(c)).collect();
+            writeln!(tsv, "{}", columns.join("\t")).unwrap();
+        }
+    }
+    tsv
+}
+
+// Instructions for creating the note type the deck is imported into
+pub fn note_type_definition() -> String {
+    format!(
+        "Note type: {}\nFields: {}\n\nFront template:\n{}\n\nBack template:\n{}\n\nStyling:\n{}\n",
+        NOTE_TYPE,
+        NOTE_FIELDS.join(", "),
+        FRONT_TEMPLATE,
+        BACK_TEMPLATE,
+        STYLING
+    )
+}
+
+// Stable id so importing the same exercise again updates the existing note
+fn guid(model: &IpModel, field: Field) -> String {
+    format!("ipc-{}-{}-{:?}", model.ip, model.prefix.value(), field)
+}
+
+// Tabs and newlines would break the row structure
+fn sanitize(column: &str) -> String {
+    column.replace('\t', " ").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IpCalculator;
+
+    #[test]
+    fn test_anki_tsv() {
+        let models = vec![IpCalculator::new().calculate_model(&[192, 168, 1, 1], 24)];
+        let tsv = to_anki_tsv(&models, "Subnetting");
+        assert!(tsv.contains("#notetype:IP Checker\n"));
+        assert!(tsv.contains("#deck:Subnetting\n"));
+
+        let notes: Vec<&str> = tsv.lines().filter(|line| !line.starts_with('#')).collect();
+        assert_eq!(notes.len(), 6);
+        let columns: Vec<&str> = notes[1].split('\t').collect();
+        assert_eq!(columns.len(), 7);
+        assert_eq!(columns[0], "ipc-192.168.1.1-24-NetworkAddress");
+        assert_eq!(columns[3], "network address");
+        assert_eq!(columns[4], "192.168.1.0");
+        assert!(columns[5].contains("AND"));
+        assert_eq!(
+            notes[5].split('\t').nth(3),
+            Some("number of possible hosts")
+        );
+    }
+
+    #[test]
+    fn test_note_type_definition() {
+        let definition = note_type_definition();
+        assert!(definition.contains("Fields: IP, Prefix, Asked, Answer, Explanation"));
+        assert!(definition.contains("{{FrontSide}}"));
+    }
+}
@@ -0,0 +1,64 @@
+pub mod anki;
+pub mod moodle;
+pub mod topology;
+pub mod worksheet;
+
+use crate::{Field, IpModel};
+
+// Question wording shared by all export formats
+pub fn question_text(model: &IpModel, field: Field) -> String {
+    format!(
+        "What is the {} of {}{}?",
+        question_subject(field),
+        model.ip,
+        model.prefix
+    )
+}
+
+// What is asked for, e.g. "broadcast address"
+pub fn question_subject(field: Field) -> String {
+    match field {
+        Field::PossibleHosts => "number of possible hosts".to_string(),
+        _ => field.label().to_lowercase(),
+    }
+}
+
+// Escape text for use inside XML and HTML documents
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IpCalculator;
+
+    #[test]
+    fn test_question_text() {
+        let model = IpCalculator::new().calculate_model(&[192, 168, 1, 1], 24);
+        assert_eq!(
+            question_text(&model, Field::BroadcastAddress),
+            "What is the broadcast address of 192.168.1.1/24?"
+        );
+        assert_eq!(
+            question_text(&model, Field::PossibleHosts),
+            "What is the number of possible hosts of 192.168.1.1/24?"
+        );
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml("a < b & \"c\""), "a &lt; b &amp; &quot;c&quot;");
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt::Write;
+
+use crate::export::{escape_xml, question_text};
+use crate::{Field, IpModel};
+
+// Moodle XML quiz with one question per field of every exercise
+pub fn to_moodle_xml(models: &[IpModel], category: &str) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<quiz>\n");
+    writeln!(
+        xml,
+        "  <question type=\"category\">\n    <category><text>$course$/{}</text></category>\n  </question>",
+        escape_xml(category)
+    )
+    .unwrap();
+
+    for (index, model) in models.iter().enumerate() {
+        for field in Field::ALL {
+            let question_type = if field.is_numeric() {
+                "numerical"
+            } else {
+                "shortanswer"
+            };
+            writeln!(xml, "  <question type=\"{}\">", question_type).unwrap();
+            writeln!(
+                xml,
+                "    <name><text>Exercise {}: {}</text></name>",
+                index + 1,
+                field.label()
+            )
+            .unwrap();
+            writeln!(
+                xml,
+                "    <questiontext format=\"html\"><text>{}</text></questiontext>",
+                escape_xml(&format!("<p>{}</p>", question_text(model, field)))
+            )
+            .unwrap();
+            xml.push_str("    <defaultgrade>1</defaultgrade>\n");
+
+            if field.is_numeric() {
+                writeln!(
+                    xml,
+                    "    <answer fraction=\"100\"><text>{}</text><tolerance>0</tolerance></answer>",
+                    model.answer(field)
+                )
+                .unwrap();
+            } else {
+                xml.push_str("    <usecase>0</usecase>\n");
+                for answer in model.accepted_answers(field) {
+                    writeln!(
+                        xml,
+                        "    <answer fraction=\"100\" format=\"moodle_auto_format\"><text>{}</text></answer>",
+                        escape_xml(&answer)
+                    )
+                    .unwrap();
+                }
+            }
+            xml.push_str("  </question>\n");
+        }
+    }
+
+    xml.push_str("</quiz>\n");
+    xml
+}
+
+// GIFT quiz with one question per field of every exercise
+pub fn to_gift(models: &[IpModel], category: &str) -> String {
+    let mut gift = format!("$CATEGORY: $course$/{}\n\n", category);
+
+    for (index, model) in models.iter().enumerate() {
+        for field in Field::ALL {
+            let answers = if field.is_numeric() {
+                format!("#{}", model.answer(field))
+            } else {
+                model
+                    .accepted_answers(field)
+                    .iter()
+                    .map(|answer| format!("={}", escape_gift(answer)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            writeln!(
+                gift,
+                "::Exercise {} - {}::{} {{{}}}\n",
+                index + 1,
+                field.label(),
+                escape_gift(&question_text(model, field)),
+                answers
+            )
+            .unwrap();
+        }
+    }
+
+    gift
+}
+
+fn escape_gift(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '~' | '=' | '#' | '{' | '}' | ':') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IpCalculator;
+
+    fn sample() -> Vec<IpModel> {
+        vec![IpCalculator::new().calculate_model(&[192, 168, 1, 1], 24)]
+    }
+
+    #[test]
+    fn test_moodle_xml_questions() {
+        let xml = to_moodle_xml(&sample(), "Subnetting");
+        assert!(xml.starts_with("<?xml"));
+        assert_eq!(xml.matches("<question type=\"shortanswer\">").count(), 5);
+        assert_eq!(xml.matches("<question type=\"numerical\">").count(), 1);
+        assert!(xml.contains("<text>$course$/Subnetting</text>"));
+        assert!(xml.contains("<text>192.168.1.0/24</text>"));
+        assert!(xml.contains("<text>254</text><tolerance>0</tolerance>"));
+    }
+
+    #[test]
+    fn test_gift_questions() {
+        let gift = to_gift(&sample(), "Subnetting");
+        assert!(gift.starts_with("$CATEGORY: $course$/Subnetting"));
+        assert!(gift.contains(
+            "::Exercise 1 - Subnet Mask::What is the subnet mask of 192.168.1.1/24? {=255.255.255.0 =/24 =0xFFFFFF00}"
+        ));
+        assert!(gift.contains("{#254}"));
+    }
+
+    #[test]
+    fn test_escape_gift() {
+        assert_eq!(escape_gift("a=b{c}"), "a\\=b\\{c\\}");
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt::Write;
+
+use crate::export::escape_xml;
+use crate::export::worksheet::Sheet;
+use crate::topology::{NodeKind, Topology};
+
+// Pixels per layout unit in the SVG
+const SCALE: f32 = 140.0;
+const MARGIN: f32 = 40.0;
+
+// Escape text for a quoted Graphviz string, line breaks become centered `\n`
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// Labels of a segment and its interfaces, with addresses on the answer key
+struct Labels {
+    segment: String,
+    interfaces: Vec<String>,
+}
+
+fn labels(topology: &Topology, sheet: Sheet) -> Vec<Labels> {
+    let solution = match sheet {
+        Sheet::Student => None,
+        Sheet::AnswerKey => Some(topology.solution()),
+    };
+    topology
+        .segments
+        .iter()
+        .enumerate()
+        .map(|(index, segment)| {
+            let answer = solution.as_ref().map(|s| &s[index]);
+            let line = |label: &str, value: Option<&String>| match value {
+                Some(value) => format!("{}\n{}", label, value),
+                None => label.to_string(),
+            };
+            Labels {
+                segment: line(&segment.name, answer.map(|a| &a.subnet)),
+                interfaces: segment
+                    .interfaces
+                    .iter()
+                    .enumerate()
+                    .map(|(i, interface)| {
+                        line(&interface.name, answer.and_then(|a| a.interfaces.get(i)))
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+// Graphviz source, render with `dot -Tsvg` or `dot -Tpdf` for a different layout
+pub fn render_dot(topology: &Topology, sheet: Sheet) -> String {
+    let layout = topology.layout();
+    let labels = labels(topology, sheet);
+    let mut dot = String::from("graph topology {\n");
+    writeln!(dot, "  label=\"{}\";", escape_dot(&topology.text())).unwrap();
+    dot.push_str("  fontname=\"sans-serif\";\n  node [fontname=\"sans-serif\"];\n  edge [fontname=\"sans-serif\", fontsize=10];\n");
+
+    for (index, node) in layout.nodes.iter().enumerate() {
+        let shape = match node.kind {
+            NodeKind::Router => "circle",
+            NodeKind::Switch => "box",
+        };
+        writeln!(
+            dot,
+            "  n{} [label=\"{}\", shape={}];",
+            index,
+            escape_dot(&node.label),
+            shape
+        )
+        .unwrap();
+    }
+    for edge in &layout.edges {
+        let labels = &labels[edge.segment];
+        let mut attributes = vec![
+            format!("label=\"{}\"", escape_dot(&labels.segment)),
+            format!("taillabel=\"{}\"", escape_dot(&labels.interfaces[0])),
+        ];
+        if let Some(head) = labels.interfaces.get(1) {
+            attributes.push(format!("headlabel=\"{}\"", escape_dot(head)));
+        }
+        writeln!(
+            dot,
+            "  n{} -- n{} [{}];",
+            edge.from,
+            edge.to,
+            attributes.join(", ")
+        )
+        .unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+// Multi line text centered on a point
+fn svg_text(svg: &mut String, x: f32, y: f32, size: u32, text: &str) {
+    for (line, content) in text.lines().enumerate() {
+        writeln!(
+            svg,
+            "<text x=\"{:.0}\" y=\"{:.0}\" font-size=\"{}\" text-anchor=\"middle\">{}</text>",
+            x,
+            y + (line as f32 * (size as f32 + 2.0)),
+            size,
+            escape_xml(content)
+        )
+        .unwrap();
+    }
+}
+
+// A ready to print diagram, laid out like in the application
+pub fn render_svg(topology: &Topology, sheet: Sheet) -> String {
+    let layout = topology.layout();
+    let labels = labels(topology, sheet);
+    let point = |x: f32, y: f32| (MARGIN + x * SCALE, MARGIN * 2.0 + y * SCALE);
+    let (width, height) = point(layout.width, layout.height);
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" font-family=\"sans-serif\">",
+        width + MARGIN,
+        height
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<text x=\"{:.0}\" y=\"{:.0}\" font-size=\"12\">{}</text>",
+        MARGIN,
+        MARGIN,
+        escape_xml(&topology.text())
+    )
+    .unwrap();
+
+    // Edges first so the nodes are drawn on top of them
+    for edge in &layout.edges {
+        let (from, to) = (&layout.nodes[edge.from], &layout.nodes[edge.to]);
+        let (x1, y1) = point(from.x, from.y);
+        let (x2, y2) = point(to.x, to.y);
+        writeln!(
+            svg,
+            "<line x1=\"{:.0}\" y1=\"{:.0}\" x2=\"{:.0}\" y2=\"{:.0}\" stroke=\"#444\" stroke-width=\"2\"/>",
+            x1, y1, x2, y2
+        )
+        .unwrap();
+        let labels = &labels[edge.segment];
+        svg_text(
+            &mut svg,
+            (x1 + x2) / 2.0 + 8.0,
+            (y1 + y2) / 2.0 - 6.0,
+            12,
+            &labels.segment,
+        );
+        // Interface labels close to the router they belong to
+        let ends = [(x1, y1, x2, y2), (x2, y2, x1, y1)];
+        for (label, (ax, ay, bx, by)) in labels.interfaces.iter().zip(ends) {
+            let (x, y) = (ax + (bx - ax) * 0.25, ay + (by - ay) * 0.25);
+            svg_text(&mut svg, x + 10.0, y + 12.0, 10, label);
+        }
+    }
+
+    for node in &layout.nodes {
+        let (x, y) = point(node.x, node.y);
+        match node.kind {
+            NodeKind::Router => writeln!(
+                svg,
+                "<circle cx=\"{:.0}\" cy=\"{:.0}\" r=\"22\" fill=\"#dde8f5\" stroke=\"#000\"/>",
+                x, y
+            ),
+            NodeKind::Switch => writeln!(
+                svg,
+                "<rect x=\"{:.0}\" y=\"{:.0}\" width=\"60\" height=\"30\" fill=\"#e8f5dd\" stroke=\"#000\"/>",
+                x - 30.0,
+                y - 15.0
+            ),
+        }
+        .unwrap();
+        // Centered vertically on the node
+        let lines = node.label.lines().count() as f32;
+        svg_text(&mut svg, x, y + 4.0 - (lines - 1.0) * 6.5, 11, &node.label);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IpCalculator;
+
+    #[test]
+    fn test_dot() {
+        let topology = IpCalculator::with_seed(3).random_topology();
+        let student = render_dot(&topology, Sheet::Student);
+        assert!(student.starts_with("graph topology {"));
+        assert!(student.contains("n0 [label=\"R1\", shape=circle];"));
+        assert_eq!(student.matches(" -- ").count(), topology.segments.len());
+
+        let solution = topology.solution();
+        let key = render_dot(&topology, Sheet::AnswerKey);
+        assert!(!student.contains(&solution[0].subnet));
+        assert!(key.contains(&format!("\\n{}", solution[0].subnet)));
+    }
+
+    #[test]
+    fn test_svg() {
+        let topology = IpCalculator::with_seed(3).random_topology();
+        let svg = render_svg(&topology, Sheet::AnswerKey);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<circle").count(), topology.routers.len());
+        assert!(svg.contains(&topology.solution()[0].interfaces[0]));
+    }
+}
@@ -0,0 +1,335 @@
+use std::fmt::Write;
+
+use crate::export::escape_xml;
+use crate::{Field, IpModel};
+
+// Which of the two printouts to render
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sheet {
+    Student,
+    AnswerKey,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorksheetOptions {
+    pub title: String,
+    pub exercises_per_page: usize,
+    pub binary_grids: bool,
+}
+
+impl Default for WorksheetOptions {
+    fn default() -> Self {
+        Self {
+            title: "Subnetting Worksheet".to_string(),
+            exercises_per_page: 2,
+            binary_grids: false,
+        }
+    }
+}
+
+impl WorksheetOptions {
+    // Layouts only exist for one to four exercises per page
+    fn per_page(&self) -> usize {
+        self.exercises_per_page.clamp(1, 4)
+    }
+
+    fn heading(&self, sheet: Sheet) -> String {
+        match sheet {
+            Sheet::Student => self.title.clone(),
+            Sheet::AnswerKey => format!("{} - Answer Key", self.title),
+        }
+    }
+}
+
+// Rows of the binary working grid, with the value filled in on the answer key
+fn grid_rows(model: &IpModel) -> [(&'static str, u32); 4] {
+    [
+        ("IP Address", model.ip.to_u32()),
+        ("Subnet Mask", model.prefix.get_network_mask()),
+        ("Network", model.network_address.to_u32()),
+        ("Broadcast", model.broadcast_address.to_u32()),
+    ]
+}
+
+pub fn render_html(models: &[IpModel], options: &WorksheetOptions, sheet: Sheet) -> String {
+    let mut html = String::new();
+    let heading = escape_xml(&options.heading(sheet));
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>",
+        heading
+    )
+    .unwrap();
+    html.push_str(
+        "<style>\n\
+         body { font-family: sans-serif; }\n\
+         .page { page-break-after: always; }\n\
+         .page:last-child { page-break-after: auto; }\n\
+         .exercise { margin-bottom: 1.5em; }\n\
+         table { border-collapse: collapse; }\n\
+         td, th { border: 1px solid #444; padding: 0.3em 0.6em; }\n\
+         td.answer { min-width: 12em; }\n\
+         table.grid td { width: 0.9em; height: 1.2em; padding: 0; text-align: center; font-family: monospace; }\n\
+         table.grid td.octet-end { border-right: 3px solid #000; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+
+    for (page, chunk) in models.chunks(options.per_page()).enumerate() {
+        html.push_str("<section class=\"page\">\n");
+        writeln!(html, "<h1>{}</h1>", heading).unwrap();
+        if sheet == Sheet::Student && page == 0 {
+            html.push_str("<p>Name: ______________________ Date: ____________</p>\n");
+        }
+        for (offset, model) in chunk.iter().enumerate() {
+            let number = page * options.per_page() + offset + 1;
+            html.push_str("<div class=\"exercise\">\n");
+            writeln!(
+                html,
+                "<h2>Exercise {}: {}{}</h2>",
+                number, model.ip, model.prefix
+            )
+            .unwrap();
+            html.push_str("<table>\n");
+            for field in Field::ALL {
+                let answer = match sheet {
+                    Sheet::Student => String::new(),
+                    Sheet::AnswerKey => escape_xml(&model.answer(field)),
+                };
+                writeln!(
+                    html,
+                    "<tr><th>{}</th><td class=\"answer\">{}</td></tr>",
+                    field.label(),
+                    answer
+                )
+                .unwrap();
+            }
+            html.push_str("</table>\n");
+
+            if options.binary_grids {
+                html.push_str("<table class=\"grid\">\n");
+                for (label, value) in grid_rows(model) {
+                    write!(html, "<tr><th>{}</th>", label).unwrap();
+                    for bit in 0..32 {
+                        let class = if bit % 8 == 7 && bit != 31 {
+                            " class=\"octet-end\""
+                        } else {
+                            ""
+                        };
+                        let content = match sheet {
+                            Sheet::Student => "",
+                            Sheet::AnswerKey if value & (1 << (31 - bit)) != 0 => "1",
+                            Sheet::AnswerKey => "0",
+                        };
+                        write!(html, "<td{}>{}</td>", class, content).unwrap();
+                    }
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("</table>\n");
+            }
+            html.push_str("</div>\n");
+        }
+        html.push_str("</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+// A4 pages in millimetres, one SVG document per page
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const LINE_HEIGHT: f32 = 6.0;
+const CELL_SIZE: f32 = 4.0;
+
+pub fn render_svg_pages(
+    models: &[IpModel],
+    options: &WorksheetOptions,
+    sheet: Sheet,
+) -> Vec<String> {
+    let slot_height = (PAGE_HEIGHT - 2.0 * MARGIN - 10.0) / options.per_page() as f32;
+
+    models
+        .chunks(options.per_page())
+        .enumerate()
+        .map(|(page, chunk)| {
+            let mut svg = String::new();
+            writeln!(
+                svg,
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\">",
+                w = PAGE_WIDTH,
+                h = PAGE_HEIGHT
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-size=\"7\" font-weight=\"bold\">{}</text>",
+                MARGIN,
+                MARGIN,
+                escape_xml(&options.heading(sheet))
+            )
+            .unwrap();
+
+            for (offset, model) in chunk.iter().enumerate() {
+                let number = page * options.per_page() + offset + 1;
+                let top = MARGIN + 10.0 + offset as f32 * slot_height;
+                render_svg_exercise(&mut svg, model, number, top, options, sheet);
+            }
+
+            svg.push_str("</svg>\n");
+            svg
+        })
+        .collect()
+}
+
+fn render_svg_exercise(
+    svg: &mut String,
+    model: &IpModel,
+    number: usize,
+    top: f32,
+    options: &WorksheetOptions,
+    sheet: Sheet,
+) {
+    writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" font-size=\"5\" font-weight=\"bold\">Exercise {}: {}{}</text>",
+        MARGIN,
+        top + 5.0,
+        number,
+        model.ip,
+        model.prefix
+    )
+    .unwrap();
+
+    for (row, field) in Field::ALL.iter().enumerate() {
+        let y = top + 12.0 + row as f32 * LINE_HEIGHT;
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"4\">{}:</text>",
+            MARGIN,
+            y,
+            field.label()
+        )
+        .unwrap();
+        match sheet {
+            Sheet::Student => writeln!(
+                svg,
+                "<line x1=\"{x}\" y1=\"{y}\" x2=\"{x2}\" y2=\"{y}\" stroke=\"#444\" stroke-width=\"0.2\"/>",
+                x = MARGIN + 40.0,
+                x2 = MARGIN + 110.0,
+                y = y + 0.5
+            )
+            .unwrap(),
+            Sheet::AnswerKey => writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-size=\"4\" font-family=\"monospace\">{}</text>",
+                MARGIN + 40.0,
+                y,
+                escape_xml(&model.answer(*field))
+            )
+            .unwrap(),
+        }
+    }
+
+    if !options.binary_grids {
+        return;
+    }
+
+    let grid_top = top + 12.0 + Field::ALL.len() as f32 * LINE_HEIGHT;
+    let grid_left = MARGIN + 25.0;
+    for (row, (label, value)) in grid_rows(model).iter().enumerate() {
+        let y = grid_top + row as f32 * CELL_SIZE;
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"3\">{}</text>",
+            MARGIN,
+            y + 3.0,
+            label
+        )
+        .unwrap();
+        for bit in 0..32 {
+            // Leave a small gap between octets
+            let x = grid_left + bit as f32 * CELL_SIZE + (bit / 8) as f32 * 1.5;
+            writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"none\" stroke=\"#444\" stroke-width=\"0.2\"/>",
+                x,
+                y,
+                s = CELL_SIZE
+            )
+            .unwrap();
+            if sheet == Sheet::AnswerKey {
+                let digit = (value >> (31 - bit)) & 1;
+                writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-size=\"3\" font-family=\"monospace\">{}</text>",
+                    x + 1.1,
+                    y + 3.0,
+                    digit
+                )
+                .unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IpCalculator;
+
+    fn sample(count: usize) -> Vec<IpModel> {
+        IpCalculator::with_seed(1).random_models(count)
+    }
+
+    #[test]
+    fn test_html_pages() {
+        let options = WorksheetOptions {
+            exercises_per_page: 3,
+            ..Default::default()
+        };
+        let html = render_html(&sample(7), &options, Sheet::Student);
+        assert_eq!(html.matches("<section class=\"page\">").count(), 3);
+        assert_eq!(html.matches("<div class=\"exercise\">").count(), 7);
+        assert!(html.contains("<h2>Exercise 7:"));
+    }
+
+    #[test]
+    fn test_answer_key_contains_answers() {
+        let models = vec![IpCalculator::new().calculate_model(&[192, 168, 1, 1], 24)];
+        let options = WorksheetOptions::default();
+        let student = render_html(&models, &options, Sheet::Student);
+        let key = render_html(&models, &options, Sheet::AnswerKey);
+        assert!(!student.contains("192.168.1.255"));
+        assert!(key.contains("192.168.1.255"));
+        assert!(key.contains("Answer Key"));
+    }
+
+    #[test]
+    fn test_binary_grid() {
+        let models = vec![IpCalculator::new().calculate_model(&[192, 168, 1, 1], 24)];
+        let options = WorksheetOptions {
+            binary_grids: true,
+            ..Default::default()
+        };
+        let key = render_html(&models, &options, Sheet::AnswerKey);
+        assert_eq!(key.matches("<table class=\"grid\">").count(), 1);
+        // 7 set bits in the IP, 24 in the mask, 6 in the network and 14 in the broadcast
+        assert_eq!(key.matches(">1</td>").count(), 51);
+        let student = render_html(&models, &options, Sheet::Student);
+        assert!(!student.contains(">1</td>"));
+    }
+
+    #[test]
+    fn test_svg_pages() {
+        let options = WorksheetOptions {
+            exercises_per_page: 4,
+            binary_grids: true,
+            ..Default::default()
+        };
+        let pages = render_svg_pages(&sample(9), &options, Sheet::AnswerKey);
+        assert_eq!(pages.len(), 3);
+        assert!(pages.iter().all(|page| page.starts_with("<svg")));
+        assert_eq!(pages[0].matches("<rect").count(), 4 * 4 * 32);
+    }
+}
@@ -0,0 +1,525 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::Ipv4Addr;
+
+use thiserror::Error;
+
+use crate::export::escape_xml;
+use crate::{csv, Field, InputIpModel, IpCalculator, IpModel, Validator};
+
+#[derive(Debug, Error)]
+pub enum GradingError {
+    #[error("Line {line}: invalid exercise number '{value}'")]
+    InvalidExerciseNumber { line: usize, value: String },
+
+    #[error("Line {line}: unknown field '{name}'")]
+    UnknownField { line: usize, name: String },
+
+    #[error("Line {line}: answer given before the first exercise heading")]
+    AnswerOutsideExercise { line: usize },
+
+    #[error("CSV header has no 'exercise' column")]
+    MissingExerciseColumn,
+}
+
+// The answers one student handed in, indexed by exercise number starting at 1
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub student: String,
+    pub answers: BTreeMap<usize, InputIpModel>,
+}
+
+impl Submission {
+    // Reads either the CSV format (header `exercise,mask,network_address,...`)
+    // or the text format (`Exercise 1` headings followed by `Field: value` lines)
+    pub fn parse(student: &str, text: &str) -> Result<Self, GradingError> {
+        let first_line = text.lines().find(|line| !line.trim().is_empty());
+        let is_csv = first_line
+            .map(|line| line.trim().to_lowercase().starts_with("exercise,"))
+            .unwrap_or(false);
+        let answers = if is_csv {
+            Self::parse_csv(text)?
+        } else {
+            Self::parse_text(text)?
+        };
+        Ok(Self {
+            student: student.to_string(),
+            answers,
+        })
+    }
+
+    fn parse_csv(text: &str) -> Result<BTreeMap<usize, InputIpModel>, GradingError> {
+        let rows = csv::parse(text);
+        let Some((header, rows)) = rows.split_first() else {
+            return Ok(BTreeMap::new());
+        };
+
+        let mut exercise_column = None;
+        let mut columns = Vec::new();
+        for (index, name) in header.iter().enumerate() {
+            if name.trim().eq_ignore_ascii_case("exercise") {
+                exercise_column = Some(index);
+            } else {
+                let field = Field::from_name(name).ok_or_else(|| GradingError::UnknownField {
+                    line: 1,
+                    name: name.clone(),
+                })?;
+                columns.push((index, field));
+            }
+        }
+        let exercise_column = exercise_column.ok_or(GradingError::MissingExerciseColumn)?;
+
+        let mut answers = BTreeMap::new();
+        for (row_index, row) in rows.iter().enumerate() {
+            let line = row_index + 2;
+            let value = row.get(exercise_column).cloned().unwrap_or_default();
+            let exercise = parse_exercise_number(&value, line)?;
+            let input: &mut InputIpModel = answers.entry(exercise).or_default();
+            for (index, field) in &columns {
+                if let Some(value) = row.get(*index) {
+                    input.set(*field, value.trim().to_string());
+                }
+            }
+        }
+        Ok(answers)
+    }
+
+    fn parse_text(text: &str) -> Result<BTreeMap<usize, InputIpModel>, GradingError> {
+        let mut answers = BTreeMap::new();
+        let mut current = None;
+
+        for (index, raw_line) in text.lines().enumerate() {
+            let line = index + 1;
+            let trimmed = raw_line.trim().trim_start_matches('#').trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            let lower = trimmed.to_lowercase();
+            if let Some(number) = lower.strip_prefix("exercise") {
+                let number = number.trim().trim_end_matches(':');
+                let exercise = parse_exercise_number(number, line)?;
+                answers
+                    .entry(exercise)
+                    .or_insert_with(InputIpModel::default);
+                current = Some(exercise);
+                continue;
+            }
+
+            let Some((name, value)) = trimmed.split_once([':', '=']) else {
+                return Err(GradingError::UnknownField {
+                    line,
+                    name: trimmed.to_string(),
+                });
+            };
+            let field = Field::from_name(name).ok_or_else(|| GradingError::UnknownField {
+                line,
+                name: name.trim().to_string(),
+            })?;
+            let exercise = current.ok_or(GradingError::AnswerOutsideExercise { line })?;
+            answers
+                .get_mut(&exercise)
+                .unwrap()
+                .set(field, value.trim().to_string());
+        }
+        Ok(answers)
+    }
+}
+
+fn parse_exercise_number(value: &str, line: usize) -> Result<usize, GradingError> {
+    match value.trim().parse::<usize>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(GradingError::InvalidExerciseNumber {
+            line,
+            value: value.to_string(),
+        }),
+    }
+}
+
+// Typical errors recognised in wrong answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Mistake {
+    Blank,
+    CopiedIpAddress,
+    PrefixOffByOne,
+    OffByOne,
+    ForgotReservedAddresses,
+    Other,
+}
+
+impl Mistake {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Mistake::Blank => "No answer",
+            Mistake::CopiedIpAddress => "Copied the IP address",
+            Mistake::PrefixOffByOne => "Calculated with a prefix off by one",
+            Mistake::OffByOne => "Address off by one",
+            Mistake::ForgotReservedAddresses => "Did not subtract network and broadcast",
+            Mistake::Other => "Other",
+        }
+    }
+
+    // Best guess of what went wrong in a wrong answer
+    pub fn classify(model: &IpModel, field: Field, answer: &str) -> Mistake {
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Mistake::Blank;
+        }
+
+        if field == Field::PossibleHosts {
+            let host_bits = 32 - model.prefix.value() as u32;
+            let total = 2u64.pow(host_bits);
+            return match answer.parse::<u64>() {
+                Ok(n) if n == total || n + 1 == total => Mistake::ForgotReservedAddresses,
+                _ if Self::matches_neighbour_prefix(model, field, answer) => {
+                    Mistake::PrefixOffByOne
+                }
+                _ => Mistake::Other,
+            };
+        }
+
+        if field != Field::Mask && answer == model.ip.as_string() {
+            return Mistake::CopiedIpAddress;
+        }
+        if Self::matches_neighbour_prefix(model, field, answer) {
+            return Mistake::PrefixOffByOne;
+        }
+        if field != Field::Mask {
+            let expected = model.answer(field).parse::<Ipv4Addr>().map(u32::from);
+            let given = answer.parse::<Ipv4Addr>().map(u32::from);
+            if let (Ok(expected), Ok(given)) = (expected, given) {
+                if expected.abs_diff(given) == 1 {
+                    return Mistake::OffByOne;
+                }
+            }
+        }
+        Mistake::Other
+    }
+
+    fn matches_neighbour_prefix(model: &IpModel, field: Field, answer: &str) -> bool {
+        let octets = model.ip.to_u32().to_be_bytes();
+        let prefix = model.prefix.value();
+        [prefix.wrapping_sub(1), prefix + 1]
+            .into_iter()
+            .filter(|p| (1..=30).contains(p))
+            .any(|p| {
+                IpCalculator::new()
+                    .calculate_model(&octets, p)
+                    .answer(field)
+                    == answer
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StudentResult {
+    pub student: String,
+    pub results: Vec<Validator>,
+}
+
+impl StudentResult {
+    pub fn points(&self) -> usize {
+        self.results.iter().map(Validator::score).sum()
+    }
+
+    // Partial credit: every correct field is worth the same
+    pub fn percent(&self) -> f64 {
+        let max = self.results.len() * Field::ALL.len();
+        if max == 0 {
+            0.0
+        } else {
+            self.points() as f64 * 100.0 / max as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuestionStats {
+    pub exercise: usize,
+    pub field: Field,
+    pub correct: usize,
+    pub total: usize,
+    pub mistakes: BTreeMap<Mistake, usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GradeReport {
+    pub exercises: Vec<IpModel>,
+    pub students: Vec<StudentResult>,
+    pub questions: Vec<QuestionStats>,
+}
+
+impl GradeReport {
+    pub fn new(exercises: Vec<IpModel>, submissions: &[Submission]) -> Self {
+        let mut questions: Vec<QuestionStats> = exercises
+            .iter()
+            .enumerate()
+            .flat_map(|(index, _)| {
+                Field::ALL.into_iter().map(move |field| QuestionStats {
+                    exercise: index + 1,
+                    field,
+                    correct: 0,
+                    total: 0,
+                    mistakes: BTreeMap::new(),
+                })
+            })
+            .collect();
+
+        let mut students = Vec::new();
+        for submission in submissions {
+            let mut results = Vec::new();
+            for (index, model) in exercises.iter().enumerate() {
+                let input = submission
+                    .answers
+                    .get(&(index + 1))
+                    .cloned()
+                    .unwrap_or_default();
+                let validator = model.validate(&input);
+                for (offset, field) in Field::ALL.into_iter().enumerate() {
+                    let stats = &mut questions[index * Field::ALL.len() + offset];
+                    stats.total += 1;
+                    if validator.is_correct(field) {
+                        stats.correct += 1;
+                    } else {
+                        let mistake = Mistake::classify(model, field, &input.get(field));
+                        *stats.mistakes.entry(mistake).or_default() += 1;
+                    }
+                }
+                results.push(validator);
+            }
+            students.push(StudentResult {
+                student: submission.student.clone(),
+                results,
+            });
+        }
+
+        Self {
+            exercises,
+            students,
+            questions,
+        }
+    }
+
+    // Mistakes summed over all questions
+    pub fn mistake_totals(&self) -> BTreeMap<Mistake, usize> {
+        let mut totals = BTreeMap::new();
+        for question in &self.questions {
+            for (mistake, count) in &question.mistakes {
+                *totals.entry(*mistake).or_default() += count;
+            }
+        }
+        totals
+    }
+
+    pub fn students_csv(&self) -> String {
+        let mut out = String::new();
+        let mut header = vec!["student".to_string()];
+        header.extend((1..=self.exercises.len()).map(|n| format!("exercise_{}", n)));
+        header.extend([
+            "points".to_string(),
+            "max".to_string(),
+            "percent".to_string(),
+        ]);
+        csv::write_row(&mut out, &header);
+
+        for student in &self.students {
+            let mut row = vec![student.student.clone()];
+            row.extend(student.results.iter().map(|v| v.score().to_string()));
+            row.push(student.points().to_string());
+            row.push((self.exercises.len() * Field::ALL.len()).to_string());
+            row.push(format!("{:.1}", student.percent()));
+            csv::write_row(&mut out, &row);
+        }
+        out
+    }
+
+    pub fn questions_csv(&self) -> String {
+        let mut out = String::new();
+        csv::write_row(
+            &mut out,
+            &["exercise", "question", "correct", "total", "mistakes"],
+        );
+        for question in &self.questions {
+            let mistakes = question
+                .mistakes
+                .iter()
+                .map(|(mistake, count)| format!("{}: {}", mistake.description(), count))
+                .collect::<Vec<_>>()
+                .join("; ");
+            csv::write_row(
+                &mut out,
+                &[
+                    question.exercise.to_string(),
+                    question.field.key().to_string(),
+                    question.correct.to_string(),
+                    question.total.to_string(),
+                    mistakes,
+                ],
+            );
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Grading Report</title>\n\
+             <style>\nbody { font-family: sans-serif; }\ntable { border-collapse: collapse; margin-bottom: 2em; }\n\
+             td, th { border: 1px solid #444; padding: 0.3em 0.6em; }\n</style>\n</head>\n<body>\n",
+        );
+
+        html.push_str("<h1>Students</h1>\n<table>\n<tr><th>Student</th>");
+        for n in 1..=self.exercises.len() {
+            write!(html, "<th>{}</th>", n).unwrap();
+        }
+        html.push_str("<th>Points</th><th>Percent</th></tr>\n");
+        for student in &self.students {
+            write!(html, "<tr><td>{}</td>", escape_xml(&student.student)).unwrap();
+            for validator in &student.results {
+                write!(html, "<td>{}/{}</td>", validator.score(), Field::ALL.len()).unwrap();
+            }
+            writeln!(
+                html,
+                "<td>{}</td><td>{:.1}%</td></tr>",
+                student.points(),
+                student.percent()
+            )
+            .unwrap();
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h1>Questions</h1>\n<table>\n<tr><th>Exercise</th><th>Question</th><th>Correct</th><th>Mistakes</th></tr>\n");
+        for question in &self.questions {
+            let model = &self.exercises[question.exercise - 1];
+            let mistakes = question
+                .mistakes
+                .iter()
+                .map(|(mistake, count)| format!("{} ({})", mistake.description(), count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                html,
+                "<tr><td>{} ({}{})</td><td>{}</td><td>{}/{}</td><td>{}</td></tr>",
+                question.exercise,
+                model.ip,
+                model.prefix,
+                question.field.label(),
+                question.correct,
+                question.total,
+                escape_xml(&mistakes)
+            )
+            .unwrap();
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h1>Common Mistakes</h1>\n<table>\n");
+        for (mistake, count) in self.mistake_totals() {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                mistake.description(),
+                count
+            )
+            .unwrap();
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise() -> IpModel {
+        IpCalculator::new().calculate_model(&[192, 168, 1, 1], 24)
+    }
+
+    #[test]
+    fn test_parse_csv_submission() {
+        let text =
+            "exercise,mask,network_address,possible_hosts\n1,255.255.255.0,192.168.1.0,254\n";
+        let submission = Submission::parse("alice", text).unwrap();
+        let answers = &submission.answers[&1];
+        assert_eq!(answers.mask, "255.255.255.0");
+        assert_eq!(answers.possible_hosts, "254");
+
+        let text = "exercise,possible_hosts\n1,abc\n";
+        let answers = &Submission::parse("bob", text).unwrap().answers[&1];
+        let answer = answers.get(Field::PossibleHosts);
+        assert!(!exercise()
+            .validate(answers)
+            .is_correct(Field::PossibleHosts));
+        assert_eq!(
+            Mistake::classify(&exercise(), Field::PossibleHosts, &answer),
+            Mistake::Other
+        );
+    }
+
+    #[test]
+    fn test_parse_text_submission() {
+        let text = "Exercise 2\nSubnet Mask: 255.255.255.0\nlast_host = 192.168.1.254\n";
+        let submission = Submission::parse("bob", text).unwrap();
+        assert_eq!(submission.answers[&2].last_host, "192.168.1.254");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            Submission::parse("x", "Mask: 255.0.0.0"),
+            Err(GradingError::AnswerOutsideExercise { line: 1 })
+        ));
+        assert!(matches!(
+            Submission::parse("x", "Exercise 1\nGateway: 1.1.1.1"),
+            Err(GradingError::UnknownField { line: 2, .. })
+        ));
+        assert!(matches!(
+            Submission::parse("x", "Exercise one"),
+            Err(GradingError::InvalidExerciseNumber { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_classify_mistakes() {
+        let model = exercise();
+        assert_eq!(
+            Mistake::classify(&model, Field::NetworkAddress, "192.168.1.1"),
+            Mistake::CopiedIpAddress
+        );
+        assert_eq!(
+            Mistake::classify(&model, Field::BroadcastAddress, "192.168.1.254"),
+            Mistake::OffByOne
+        );
+        assert_eq!(
+            Mistake::classify(&model, Field::PossibleHosts, "256"),
+            Mistake::ForgotReservedAddresses
+        );
+        assert_eq!(
+            Mistake::classify(&model, Field::Mask, "255.255.254.0"),
+            Mistake::PrefixOffByOne
+        );
+        assert_eq!(
+            Mistake::classify(&model, Field::FirstHost, ""),
+            Mistake::Blank
+        );
+    }
+
+    #[test]
+    fn test_grade_report() {
+        let good = Submission::parse(
+            "good",
+            "exercise,mask,network_address,broadcast_address,first_host,last_host,possible_hosts\n\
+             1,255.255.255.0,192.168.1.0,192.168.1.255,192.168.1.1,192.168.1.254,254\n",
+        )
+        .unwrap();
+        let partial = Submission::parse("partial", "Exercise 1\nmask: 255.255.255.0\n").unwrap();
+
+        let report = GradeReport::new(vec![exercise()], &[good, partial]);
+        assert_eq!(report.students[0].points(), 6);
+        assert_eq!(report.students[1].points(), 1);
+        assert_eq!(report.questions[0].correct, 2);
+        assert_eq!(report.mistake_totals()[&Mistake::Blank], 5);
+        assert!(report.students_csv().contains("partial,1,1,6,16.7"));
+        assert!(report.to_html().contains("<td>good</td>"));
+    }
+}
@@ -0,0 +1,219 @@
+// IPv4 addresses of the local machine, read from `ip -j addr` or /proc/net
+
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::process::Command;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::ip_address::IpAddress;
+use crate::IpModel;
+
+#[derive(Debug, Error)]
+pub enum InterfaceError {
+    #[error("Invalid `ip -j addr` output: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    #[error("Failed to read the interfaces: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalAddress {
+    pub interface: String,
+    pub ip: IpAddress,
+    pub prefix: u8,
+}
+
+impl LocalAddress {
+    pub fn cidr(&self) -> String {
+        format!("{}/{}", self.ip, self.prefix)
+    }
+
+    // None for /31 and /32, they have no network and broadcast address to compute
+    pub fn model(&self) -> Option<IpModel> {
+        IpModel::from_cidr(&self.cidr())
+    }
+
+    pub fn is_loopback(&self) -> bool {
+        self.ip.to_u32() >> 24 == 127
+    }
+}
+
+impl fmt::Display for LocalAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.interface, self.cidr())
+    }
+}
+
+#[derive(Deserialize)]
+struct IpLink {
+    #[serde(default)]
+    ifname: String,
+    #[serde(default)]
+    addr_info: Vec<IpAddrInfo>,
+}
+
+#[derive(Deserialize)]
+struct IpAddrInfo {
+    family: String,
+    local: String,
+    prefixlen: u8,
+}
+
+// Output of `ip -j addr`, also from a saved file. IPv6 addresses are skipped.
+pub fn parse_ip_json(text: &str) -> Result<Vec<LocalAddress>, InterfaceError> {
+    let links: Vec<IpLink> = serde_json::from_str(text)?;
+    Ok(links
+        .into_iter()
+        .flat_map(|link| {
+            link.addr_info
+                .into_iter()
+                .filter(|info| info.family == "inet")
+                .filter_map(move |info| {
+                    Some(LocalAddress {
+                        interface: link.ifname.clone(),
+                        ip: IpAddress::try_new(info.local).ok()?,
+                        prefix: info.prefixlen,
+                    })
+                })
+        })
+        .collect())
+}
+
+// /proc/net/route prints addresses as hex of the in-memory value, so the
+// native byte order gives the octets back
+fn parse_route_hex(value: &str) -> Option<u32> {
+    let value = u32::from_str_radix(value, 16).ok()?;
+    Some(u32::from(Ipv4Addr::from(value.to_ne_bytes())))
+}
+
+// Local addresses are the `/32 host LOCAL` entries of /proc/net/fib_trie, their
+// interface and prefix come from the most specific route in /proc/net/route.
+// Addresses without a route, like 127.0.0.1, are left out.
+pub fn parse_proc(route: &str, fib_trie: &str) -> Vec<LocalAddress> {
+    let routes: Vec<(String, u32, u32)> = route
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            let destination = parse_route_hex(columns.get(1)?)?;
+            let mask = parse_route_hex(columns.get(7)?)?;
+            (mask != 0).then(|| (columns[0].to_string(), destination, mask))
+        })
+        .collect();
+
+    let mut locals: Vec<u32> = Vec::new();
+    let mut last = None;
+    for line in fib_trie.lines().map(str::trim) {
+        if let Some(address) = line.strip_prefix("|-- ") {
+            last = address.parse::<Ipv4Addr>().ok().map(u32::from);
+        } else if line == "/32 host LOCAL" {
+            // Both the main and the local table list the address
+            locals.extend(last.filter(|address| !locals.contains(address)));
+        }
+    }
+
+    locals
+        .into_iter()
+        .filter_map(|address| {
+            let (interface, _, mask) = routes
+                .iter()
+                .filter(|(_, destination, mask)| address & mask == *destination)
+                .max_by_key(|(_, _, mask)| mask.count_ones())?;
+            Some(LocalAddress {
+                interface: interface.clone(),
+                ip: IpAddress::new(Ipv4Addr::from(address).to_string()),
+                prefix: mask.count_ones() as u8,
+            })
+        })
+        .collect()
+}
+
+// Addresses of this machine, from `ip -j addr` or /proc/net if `ip` is missing
+pub fn read_local() -> Result<Vec<LocalAddress>, InterfaceError> {
+    match Command::new("ip").args(["-j", "addr"]).output() {
+        Ok(output) if output.status.success() => {
+            parse_ip_json(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => Ok(parse_proc(
+            &std::fs::read_to_string("/proc/net/route")?,
+            &std::fs::read_to_string("/proc/net/fib_trie")?,
+        )),
+    }
+}
+
+// Addresses that make a subnet exercise, without loopback, /31 and /32
+pub fn practice_addresses(addresses: Vec<LocalAddress>) -> Vec<LocalAddress> {
+    addresses
+        .into_iter()
+        .filter(|address| !address.is_loopback() && address.model().is_some())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Saved from `ip -j addr`, shortened
+    const IP_JSON: &str = r#"[{"ifindex":1,"ifname":"lo","flags":["LOOPBACK","UP","LOWER_UP"],"mtu":65536,"addr_info":[{"family":"inet","local":"127.0.0.1","prefixlen":8,"scope":"host","label":"lo"},{"family":"inet6","local":"::1","prefixlen":128,"scope":"host"}]},{"ifindex":2,"ifname":"eth0","flags":["BROADCAST","MULTICAST","UP","LOWER_UP"],"mtu":1500,"addr_info":[{"family":"inet","local":"192.168.178.23","prefixlen":24,"broadcast":"192.168.178.255","scope":"global","dynamic":true,"label":"eth0"},{"family":"inet6","local":"fe80::a00:27ff:fe4e:66a1","prefixlen":64,"scope":"link"}]},{"ifindex":3,"ifname":"wg0","mtu":1420,"addr_info":[{"family":"inet","local":"10.8.0.2","prefixlen":32,"scope":"global","label":"wg0"}]},{"ifindex":4,"ifname":"docker0","addr_info":[]}]"#;
+
+    const ROUTE: &str = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t00000000\t01B2A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+eth0\t00B2A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+";
+
+    const FIB_TRIE: &str = "\
+Main:
+  +-- 0.0.0.0/0 3 0 5
+     |-- 0.0.0.0
+        /0 universe UNICAST
+     +-- 127.0.0.0/8 2 0 2
+        |-- 127.0.0.1
+           /32 host LOCAL
+     +-- 192.168.178.0/24 2 0 2
+        |-- 192.168.178.0
+           /24 link UNICAST
+        |-- 192.168.178.23
+           /32 host LOCAL
+        |-- 192.168.178.255
+           /32 link BROADCAST
+Local:
+  +-- 0.0.0.0/0 3 0 5
+     +-- 192.168.178.0/24 2 0 2
+        |-- 192.168.178.23
+           /32 host LOCAL
+";
+
+    #[test]
+    fn test_parse_ip_json() {
+        let addresses = parse_ip_json(IP_JSON).unwrap();
+        let names: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+        assert_eq!(
+            names,
+            [
+                "lo: 127.0.0.1/8",
+                "eth0: 192.168.178.23/24",
+                "wg0: 10.8.0.2/32"
+            ]
+        );
+
+        let practice = practice_addresses(addresses);
+        assert_eq!(practice.len(), 1);
+        let model = practice[0].model().unwrap();
+        assert_eq!(model.network_address.as_string(), "192.168.178.0");
+        assert_eq!(model.broadcast_address.as_string(), "192.168.178.255");
+        assert!(parse_ip_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_proc() {
+        let addresses = parse_proc(ROUTE, FIB_TRIE);
+        if cfg!(target_endian = "little") {
+            assert_eq!(addresses.len(), 1);
+            assert_eq!(addresses[0].to_string(), "eth0: 192.168.178.23/24");
+        }
+    }
+}
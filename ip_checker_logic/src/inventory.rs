@@ -0,0 +1,307 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::cidr::Cidr;
+use crate::conflicts::Conflicts;
+use crate::csv::{self, field, Header};
+use crate::ip_address::IpAddress;
+use crate::plan::Utilisation;
+use crate::trie::PrefixTrie;
+
+#[derive(Debug, Error)]
+pub enum InventoryError {
+    #[error("Invalid inventory file: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    #[error("Unknown inventory format of '{0}', expected a .csv or .json file")]
+    UnknownFormat(String),
+
+    #[error("CSV header has no 'address' column")]
+    MissingAddressColumn,
+
+    #[error("Line {line}: invalid network '{value}'")]
+    InvalidNetwork { line: usize, value: String },
+
+    #[error("Line {line}: invalid IP address '{value}'")]
+    InvalidAddress { line: usize, value: String },
+}
+
+// Problems of the assigned networks and addresses
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum InventoryIssue {
+    #[error("{network} ({name}) overlaps {other} ({other_name})")]
+    Overlap {
+        name: String,
+        network: String,
+        other_name: String,
+        other: String,
+    },
+
+    #[error("{address} is used by {host} and {other_host}")]
+    DuplicateAddress {
+        address: String,
+        host: String,
+        other_host: String,
+    },
+
+    #[error("{address} ({host}) is not inside any network")]
+    OutsideNetworks { host: String, address: String },
+
+    #[error("{address} ({host}) is the network address of {network}")]
+    NetworkAddress {
+        host: String,
+        address: String,
+        network: String,
+    },
+
+    #[error("{address} ({host}) is the broadcast address of {network}")]
+    BroadcastAddress {
+        host: String,
+        address: String,
+        network: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryFormat {
+    Csv,
+    Json,
+}
+
+impl InventoryFormat {
+    // Picked by the file extension
+    pub fn from_path(path: &Path) -> Result<Self, InventoryError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => Ok(InventoryFormat::Csv),
+            Some("json") => Ok(InventoryFormat::Json),
+            _ => Err(InventoryError::UnknownFormat(path.display().to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InventoryNetwork {
+    #[serde(default)]
+    pub name: String,
+    pub network: Cidr,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InventoryHost {
+    #[serde(default)]
+    pub name: String,
+    pub address: IpAddress,
+}
+
+// Networks and host addresses in use, e.g. collected from several teams
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    #[serde(default)]
+    pub networks: Vec<InventoryNetwork>,
+    #[serde(default)]
+    pub hosts: Vec<InventoryHost>,
+}
+
+impl Inventory {
+    // JSON with `networks` and `hosts` lists, or CSV with `name,address` rows
+    // where addresses with a prefix length are networks
+    pub fn parse(text: &str, format: InventoryFormat) -> Result<Self, InventoryError> {
+        match format {
+            InventoryFormat::Json => Ok(serde_json::from_str(text)?),
+            InventoryFormat::Csv => Self::parse_csv(text),
+        }
+    }
+
+    fn parse_csv(text: &str) -> Result<Self, InventoryError> {
+        let rows = csv::parse(text);
+        let Some((header, rows)) = rows.split_first() else {
+            return Ok(Self::default());
+        };
+        let header = Header::new(header);
+        let address = header
+            .find(&["address"])
+            .ok_or(InventoryError::MissingAddressColumn)?;
+        let name = header.find(&["name"]);
+
+        let mut inventory = Self::default();
+        for (index, row) in rows.iter().enumerate() {
+            let line = index + 2;
+            let value = field(row, Some(address)).to_string();
+            let name = field(row, name).to_string();
+            if value.contains('/') {
+                let network = value
+                    .parse()
+                    .map_err(|_| InventoryError::InvalidNetwork { line, value })?;
+                inventory.networks.push(InventoryNetwork { name, network });
+            } else {
+                let address = IpAddress::try_new(value.clone())
+                    .map_err(|_| InventoryError::InvalidAddress { line, value })?;
+                inventory.hosts.push(InventoryHost { name, address });
+            }
+        }
+        Ok(inventory)
+    }
+
+    // Adds the networks and hosts of another file
+    pub fn merge(&mut self, other: Inventory) {
+        self.networks.extend(other.networks);
+        self.hosts.extend(other.hosts);
+    }
+
+    pub fn check(&self) -> Vec<InventoryIssue> {
+        let mut issues = Vec::new();
+        let mut conflicts = Conflicts::new();
+        for (index, network) in self.networks.iter().enumerate() {
+            let mut overlapping = conflicts.add_network(&network.network, index);
+            overlapping.sort_unstable();
+            for other in overlapping.iter().map(|&other| &self.networks[other]) {
+                issues.push(InventoryIssue::Overlap {
+                    name: network.name.clone(),
+                    network: network.network.to_string(),
+                    other_name: other.name.clone(),
+                    other: other.network.to_string(),
+                });
+            }
+        }
+
+        let networks: PrefixTrie<&Cidr> = self
+            .networks
+            .iter()
+            .map(|network| {
+                let network = &network.network;
+                (network.network.clone(), network.prefix.clone(), network)
+            })
+            .collect();
+        for host in &self.hosts {
+            let address = host.address.as_string();
+            if let Some(other_host) = conflicts.add_address(&host.address, host.name.clone()) {
+                issues.push(InventoryIssue::DuplicateAddress {
+                    address: address.clone(),
+                    host: host.name.clone(),
+                    other_host,
+                });
+            }
+
+            // The smallest network containing the address
+            let Some(network) = networks.longest_match_value(&host.address) else {
+                issues.push(InventoryIssue::OutsideNetworks {
+                    host: host.name.clone(),
+                    address,
+                });
+                continue;
+            };
+            // Nested networks are checked against the most specific one
+            if !network.contains_host(&host.address) {
+                let is_first = host.address.to_u32() == network.first();
+                let (host, network) = (host.name.clone(), network.to_string());
+                issues.push(if is_first {
+                    InventoryIssue::NetworkAddress {
+                        host,
+                        address,
+                        network,
+                    }
+                } else {
+                    InventoryIssue::BroadcastAddress {
+                        host,
+                        address,
+                        network,
+                    }
+                });
+            }
+        }
+        issues
+    }
+
+    // Usable hosts of each network that are in use, counting nested networks too
+    pub fn utilisation(&self) -> Vec<Utilisation> {
+        self.networks
+            .iter()
+            .map(|network| {
+                let mut used: Vec<u32> = self
+                    .hosts
+                    .iter()
+                    .filter(|host| network.network.contains_host(&host.address))
+                    .map(|host| host.address.to_u32())
+                    .collect();
+                used.sort_unstable();
+                used.dedup();
+                Utilisation {
+                    name: network.name.clone(),
+                    network: network.network.clone(),
+                    used: used.len() as u64,
+                    total: network.network.hosts().len() as u64,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "\
+name,address
+Office,10.1.0.0/24
+Lab,10.1.0.128/25
+Servers,10.1.1.0/29
+printer,10.1.0.10
+camera,10.1.0.10
+nas,10.1.1.7
+router,10.1.1.0
+laptop,192.168.0.5
+";
+
+    #[test]
+    fn test_parse() {
+        let inventory = Inventory::parse(CSV, InventoryFormat::Csv).unwrap();
+        assert_eq!(inventory.networks.len(), 3);
+        assert_eq!(inventory.hosts.len(), 5);
+        assert!(matches!(
+            Inventory::parse("name,address\nx,10.1.0.1/24\n", InventoryFormat::Csv),
+            Err(InventoryError::InvalidNetwork { line: 2, .. })
+        ));
+
+        let json = r#"{"networks": [{"name": "Office", "network": "10.1.0.0/24"}],
+                       "hosts": [{"address": "10.1.0.10"}]}"#;
+        let inventory = Inventory::parse(json, InventoryFormat::Json).unwrap();
+        assert_eq!(inventory.networks[0].network.to_string(), "10.1.0.0/24");
+        assert_eq!(inventory.hosts[0].name, "");
+    }
+
+    #[test]
+    fn test_check() {
+        let inventory = Inventory::parse(CSV, InventoryFormat::Csv).unwrap();
+        let issues: Vec<String> = inventory.check().iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            issues,
+            [
+                "10.1.0.128/25 (Lab) overlaps 10.1.0.0/24 (Office)",
+                "10.1.0.10 is used by camera and printer",
+                "10.1.1.7 (nas) is the broadcast address of 10.1.1.0/29",
+                "10.1.1.0 (router) is the network address of 10.1.1.0/29",
+                "192.168.0.5 (laptop) is not inside any network",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_utilisation() {
+        let inventory = Inventory::parse(CSV, InventoryFormat::Csv).unwrap();
+        let utilisation: Vec<String> = inventory
+            .utilisation()
+            .iter()
+            .map(|u| u.to_string())
+            .collect();
+        assert_eq!(
+            utilisation,
+            [
+                "10.1.0.0/24 (Office): 1/254 used (0.4%)",
+                "10.1.0.128/25 (Lab): 0/126 used (0.0%)",
+                "10.1.1.0/29 (Servers): 0/6 used (0.0%)",
+            ]
+        );
+    }
+}
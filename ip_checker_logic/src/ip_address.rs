@@ -1,5 +1,6 @@
 use std::str::FromStr;
 
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
 #[derive(Debug, Clone)]
@@ -13,7 +14,7 @@ pub enum IpAddressError {
     #[error("Invalid octet at position {position}: {reason}")]
     InvalidOctet { position: usize, reason: String },
 
-    #[error("Octet out of range at position {position}: value must be between 1 and 255")]
+    #[error("Octet out of range at position {position}: value must be between 0 and 255")]
     OctetOutOfRange { position: usize },
 
     #[error("Empty IP address")]
@@ -47,9 +48,6 @@ impl IpAddress {
                 Ok(num) if num > 255 => {
                     return Err(IpAddressError::OctetOutOfRange { position: i + 1 });
                 }
-                Ok(num) if num < 1 => {
-                    return Err(IpAddressError::OctetOutOfRange { position: i + 1 });
-                }
                 Ok(_) => (), // Valid range, continue
                 Err(_) => {
                     return Err(IpAddressError::InvalidOctet {
@@ -66,6 +64,13 @@ impl IpAddress {
     pub fn as_string(&self) -> String {
         self.0.clone()
     }
+
+    pub fn to_u32(&self) -> u32 {
+        self.0
+            .split('.')
+            .map(|x| x.parse::<u8>().unwrap())
+            .fold(0, |acc, octet| (acc << 8) | octet as u32)
+    }
 }
 
 impl FromStr for IpAddress {
@@ -88,6 +93,21 @@ impl std::fmt::Display for IpAddress {
     }
 }
 
+// Written as the dotted string in plan files
+impl Serialize for IpAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for IpAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -124,12 +144,31 @@ mod test {
         ));
     }
 
+    #[test]
+    fn test_zero_octets() {
+        // Network addresses like 10.0.0.0 are valid addresses too
+        for address in ["10.0.0.1", "10.0.0.0", "0.0.0.0"] {
+            assert!(IpAddress::try_new(address.to_string()).is_ok());
+        }
+        let error = IpAddress::try_new("10.0.0.256".to_string()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Octet out of range at position 4: value must be between 0 and 255"
+        );
+    }
+
     #[test]
     fn test_empty_address() {
         let ip = IpAddress::try_new("".to_string());
         assert!(matches!(ip.unwrap_err(), IpAddressError::EmptyAddress));
     }
 
+    #[test]
+    fn test_to_u32() {
+        let ip = IpAddress::new("192.168.1.1".to_string());
+        assert_eq!(ip.to_u32(), 0xC0A80101);
+    }
+
     #[test]
     fn test_from_str() {
         let ip: Result<IpAddress, _> = "192.168.1.1".parse();
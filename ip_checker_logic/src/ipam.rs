@@ -0,0 +1,416 @@
+// Prefixes and IP addresses exchanged with NetBox or phpIPAM as CSV files
+
+use thiserror::Error;
+
+use crate::cidr::Cidr;
+use crate::csv::{self, field, Header};
+use crate::dns::dns_name;
+use crate::ip_address::IpAddress;
+use crate::plan::{AddressPlan, PlannedSubnet, Reservation};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum IpamError {
+    #[error("CSV header has no '{0}' column")]
+    MissingColumn(String),
+
+    #[error("Line {line}: invalid prefix '{value}'")]
+    InvalidPrefix { line: usize, value: String },
+
+    #[error("Line {line}: invalid IP address '{value}'")]
+    InvalidAddress { line: usize, value: String },
+}
+
+// Rows of an export, IPv6 rows are only counted since plans are IPv4
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpamRows<T> {
+    pub rows: Vec<T>,
+    pub skipped_ipv6: usize,
+}
+
+impl<T> Default for IpamRows<T> {
+    fn default() -> Self {
+        Self {
+            rows: Vec::new(),
+            skipped_ipv6: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpamPrefix {
+    pub network: Cidr,
+    // e.g. active, reserved or container
+    pub status: String,
+    pub vlan: Option<u16>,
+    pub description: String,
+}
+
+impl IpamPrefix {
+    fn is_container(&self) -> bool {
+        self.status.eq_ignore_ascii_case("container")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpamAddress {
+    pub address: IpAddress,
+    pub name: String,
+    pub mac: Option<String>,
+    pub gateway: bool,
+    pub description: String,
+}
+
+// A VID, or a VLAN shown as `Office (10)` by the NetBox export
+fn parse_vlan(value: &str) -> Option<u16> {
+    value.parse().ok().or_else(|| {
+        let (_, vid) = value.strip_suffix(')')?.rsplit_once('(')?;
+        vid.trim().parse().ok()
+    })
+}
+
+// NetBox `prefix` column, or the `subnet` and `mask` columns of phpIPAM
+pub fn parse_prefixes(text: &str) -> Result<IpamRows<IpamPrefix>, IpamError> {
+    let rows = csv::parse(text);
+    let Some((header, rows)) = rows.split_first() else {
+        return Ok(IpamRows::default());
+    };
+    let header = Header::new(header);
+    let prefix = header.find(&["prefix"]);
+    let (subnet, mask) = (header.find(&["subnet"]), header.find(&["mask"]));
+    if prefix.is_none() && (subnet.is_none() || mask.is_none()) {
+        return Err(IpamError::MissingColumn("prefix".to_string()));
+    }
+    let status = header.find(&["status"]);
+    let vlan = header.find(&["vlan", "vid", "vlan_id"]);
+    let description = header.find(&["description"]);
+
+    let mut prefixes = IpamRows::default();
+    for (index, row) in rows.iter().enumerate() {
+        let value = match prefix {
+            Some(_) => field(row, prefix).to_string(),
+            None => format!("{}/{}", field(row, subnet), field(row, mask)),
+        };
+        if value.contains(':') {
+            prefixes.skipped_ipv6 += 1;
+            continue;
+        }
+        let network = value.parse().map_err(|_| IpamError::InvalidPrefix {
+            line: index + 2,
+            value: value.clone(),
+        })?;
+        prefixes.rows.push(IpamPrefix {
+            network,
+            status: field(row, status).to_lowercase(),
+            vlan: parse_vlan(field(row, vlan)),
+            description: field(row, description).to_string(),
+        });
+    }
+    Ok(prefixes)
+}
+
+// NetBox addresses are written with their prefix length, e.g. 10.0.0.5/24
+pub fn parse_addresses(text: &str) -> Result<IpamRows<IpamAddress>, IpamError> {
+    let rows = csv::parse(text);
+    let Some((header, rows)) = rows.split_first() else {
+        return Ok(IpamRows::default());
+    };
+    let header = Header::new(header);
+    let address = header
+        .find(&["address", "ip_address", "ip_addr", "ip"])
+        .ok_or_else(|| IpamError::MissingColumn("address".to_string()))?;
+    let name = header.find(&["dns_name", "hostname", "name"]);
+    let mac = header.find(&["mac_address", "mac"]);
+    let gateway = header.find(&["is_gateway", "gateway"]);
+    let description = header.find(&["description"]);
+
+    let mut addresses = IpamRows::default();
+    for (index, row) in rows.iter().enumerate() {
+        let value = field(row, Some(address));
+        if value.contains(':') {
+            addresses.skipped_ipv6 += 1;
+            continue;
+        }
+        let ip = value.split('/').next().unwrap_or_default();
+        let ip = IpAddress::try_new(ip.to_string()).map_err(|_| IpamError::InvalidAddress {
+            line: index + 2,
+            value: value.to_string(),
+        })?;
+        let description = field(row, description).to_string();
+        // phpIPAM marks gateways, NetBox exports of our plans describe them as such
+        let is_gateway = matches!(
+            field(row, gateway).to_lowercase().as_str(),
+            "1" | "true" | "yes"
+        ) || description.eq_ignore_ascii_case("gateway");
+        addresses.rows.push(IpamAddress {
+            address: ip,
+            name: field(row, name).to_string(),
+            mac: Some(field(row, mac).to_string()).filter(|mac| !mac.is_empty()),
+            gateway: is_gateway,
+            description,
+        });
+    }
+    Ok(addresses)
+}
+
+// The smallest network containing all prefixes
+fn covering(prefixes: &[IpamPrefix]) -> Option<Cidr> {
+    let first = prefixes.iter().map(|p| p.network.first()).min()?;
+    let last = prefixes.iter().map(|p| p.network.last()).max()?;
+    Cidr::from_u32(first, (first ^ last).leading_zeros() as u8).ok()
+}
+
+// Prefixes without smaller prefixes inside become the subnets of the plan, the
+// network covering all prefixes its block. Addresses go into the subnet
+// containing them, addresses outside every subnet are left out.
+pub fn to_plan(prefixes: &[IpamPrefix], addresses: &[IpamAddress]) -> Option<AddressPlan> {
+    let block = covering(prefixes)?;
+    let name = prefixes
+        .iter()
+        .find(|prefix| prefix.network == block)
+        .map(|prefix| prefix.description.clone())
+        .unwrap_or_default();
+    let mut plan = AddressPlan::new(&name, block);
+
+    let mut leaves: Vec<&IpamPrefix> = prefixes
+        .iter()
+        .filter(|prefix| {
+            !prefix.is_container()
+                && !prefixes.iter().any(|other| {
+                    other.network.prefix.value() > prefix.network.prefix.value()
+                        && prefix.network.contains_network(&other.network)
+                })
+        })
+        .collect();
+    leaves.sort_by_key(|prefix| (prefix.network.first(), prefix.network.prefix.value()));
+    for prefix in leaves {
+        let name = match prefix.description.as_str() {
+            "" => prefix.network.to_string(),
+            description => description.to_string(),
+        };
+        let mut subnet = PlannedSubnet::new(&name, prefix.network.clone());
+        subnet.vlan = prefix.vlan;
+        plan.subnets.push(subnet);
+    }
+
+    for address in addresses {
+        let Some(subnet) = plan
+            .subnets
+            .iter_mut()
+            .find(|subnet| subnet.network.contains(&address.address))
+        else {
+            continue;
+        };
+        if address.gateway && subnet.gateway.is_none() {
+            subnet.gateway = Some(address.address.clone());
+            continue;
+        }
+        let name = match address.name.as_str() {
+            "" => address.address.as_string(),
+            name => name.to_string(),
+        };
+        subnet.reservations.push(Reservation {
+            name,
+            address: address.address.clone(),
+            mac: address.mac.clone(),
+            description: address.description.clone(),
+        });
+    }
+    Some(plan)
+}
+
+// Addresses `to_plan` leaves out because no imported subnet contains them
+pub fn unassigned<'a>(plan: &AddressPlan, addresses: &'a [IpamAddress]) -> Vec<&'a IpamAddress> {
+    addresses
+        .iter()
+        .filter(|address| {
+            !plan
+                .subnets
+                .iter()
+                .any(|subnet| subnet.network.contains(&address.address))
+        })
+        .collect()
+}
+
+// NetBox bulk import of the block as container and the subnets as active prefixes
+pub fn render_netbox_prefixes(plan: &AddressPlan) -> String {
+    let mut out = String::new();
+    csv::write_row(&mut out, &["prefix", "status", "vlan", "description"]);
+    csv::write_row(
+        &mut out,
+        &[
+            plan.block.to_string(),
+            "container".to_string(),
+            String::new(),
+            plan.name.clone(),
+        ],
+    );
+    for subnet in &plan.subnets {
+        let vlan = subnet.vlan.map(|vlan| vlan.to_string()).unwrap_or_default();
+        csv::write_row(
+            &mut out,
+            &[
+                subnet.network.to_string(),
+                "active".to_string(),
+                vlan,
+                subnet.name.clone(),
+            ],
+        );
+    }
+    out
+}
+
+// NetBox bulk import of gateways and reservations, named by their DNS names
+pub fn render_netbox_addresses(plan: &AddressPlan) -> String {
+    let mut out = String::new();
+    csv::write_row(&mut out, &["address", "status", "dns_name", "description"]);
+    for subnet in &plan.subnets {
+        let address = |ip: &IpAddress| format!("{}{}", ip, subnet.network.prefix);
+        if let Some(gateway) = &subnet.gateway {
+            csv::write_row(
+                &mut out,
+                &[
+                    address(gateway),
+                    "active".to_string(),
+                    dns_name(&format!("gw-{}", subnet.name)),
+                    "Gateway".to_string(),
+                ],
+            );
+        }
+        for host in &subnet.reservations {
+            csv::write_row(
+                &mut out,
+                &[
+                    address(&host.address),
+                    "active".to_string(),
+                    dns_name(&host.name),
+                    host.description.clone(),
+                ],
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETBOX_PREFIXES: &str = "\
+Prefix,Status,VRF,Tenant,Site,VLAN,Role,Description
+10.20.0.0/22,Container,,,,,,Campus
+10.20.0.0/24,Active,,,,Office (10),,Office
+10.20.2.0/26,Active,,,,20,,
+10.20.2.0/26,Reserved,,,,,,Old servers
+";
+
+    const NETBOX_ADDRESSES: &str = "\
+IP Address,Status,Role,Tenant,Assigned,DNS Name,Description
+10.20.0.1/24,Active,,,,gw-office,Gateway
+10.20.0.10/24,Active,,,,printer,\"Printer, 2nd floor\"
+10.20.3.5/24,Active,,,,lost,
+";
+
+    #[test]
+    fn test_parse_netbox() {
+        let prefixes = parse_prefixes(NETBOX_PREFIXES).unwrap().rows;
+        assert_eq!(prefixes.len(), 4);
+        assert_eq!(prefixes[0].status, "container");
+        assert_eq!(prefixes[1].vlan, Some(10));
+        assert_eq!(prefixes[2].vlan, Some(20));
+
+        let addresses = parse_addresses(NETBOX_ADDRESSES).unwrap().rows;
+        assert!(addresses[0].gateway);
+        assert_eq!(addresses[1].address.as_string(), "10.20.0.10");
+        assert_eq!(addresses[1].description, "Printer, 2nd floor");
+
+        assert_eq!(
+            parse_prefixes("Prefix\n10.20.0.1/24\n").unwrap_err(),
+            IpamError::InvalidPrefix {
+                line: 2,
+                value: "10.20.0.1/24".to_string()
+            }
+        );
+        assert_eq!(
+            parse_addresses("Status\nActive\n").unwrap_err(),
+            IpamError::MissingColumn("address".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_mixed_families() {
+        let prefixes = parse_prefixes(
+            "Prefix,Status,Description\n\
+             10.20.0.0/24,Active,Office\n\
+             2001:db8::/32,Container,Campus v6\n\
+             2001:db8:1::/64,Active,Office v6\n",
+        )
+        .unwrap();
+        assert_eq!(prefixes.rows.len(), 1);
+        assert_eq!(prefixes.rows[0].description, "Office");
+        assert_eq!(prefixes.skipped_ipv6, 2);
+
+        let addresses = parse_addresses(
+            "IP Address,DNS Name\n2001:db8:1::10/64,printer\n10.20.0.10/24,printer\n",
+        )
+        .unwrap();
+        assert_eq!(addresses.rows[0].address.as_string(), "10.20.0.10");
+        assert_eq!(addresses.skipped_ipv6, 1);
+    }
+
+    #[test]
+    fn test_parse_phpipam() {
+        let prefixes =
+            parse_prefixes("Section,Subnet,Mask,Description,VLAN\nLab,192.168.7.0,25,Lab,7\n")
+                .unwrap()
+                .rows;
+        assert_eq!(prefixes[0].network.to_string(), "192.168.7.0/25");
+        assert_eq!(prefixes[0].vlan, Some(7));
+
+        let addresses = parse_addresses(
+            "ip_addr,hostname,mac,is_gateway\n192.168.7.1,router,,1\n192.168.7.20,nas,00:11:22:33:44:55,0\n",
+        )
+        .unwrap()
+        .rows;
+        assert!(addresses[0].gateway);
+        assert!(!addresses[1].gateway);
+        assert_eq!(addresses[1].mac.as_deref(), Some("00:11:22:33:44:55"));
+    }
+
+    #[test]
+    fn test_plan_round_trip() {
+        let prefixes = parse_prefixes(NETBOX_PREFIXES).unwrap().rows;
+        let addresses = parse_addresses(NETBOX_ADDRESSES).unwrap().rows;
+        let plan = to_plan(&prefixes, &addresses).unwrap();
+        assert_eq!(plan.name, "Campus");
+        assert_eq!(plan.block.to_string(), "10.20.0.0/22");
+        let names: Vec<&str> = plan.subnets.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Office", "10.20.2.0/26", "Old servers"]);
+        assert_eq!(
+            plan.subnets[0].gateway.as_ref().unwrap().as_string(),
+            "10.20.0.1"
+        );
+        assert_eq!(plan.subnets[0].reservations[0].name, "printer");
+        // The duplicate prefix shows up as overlap
+        assert_eq!(plan.check().len(), 1);
+        let lost: Vec<&str> = unassigned(&plan, &addresses)
+            .iter()
+            .map(|address| address.name.as_str())
+            .collect();
+        assert_eq!(lost, ["lost"]);
+
+        let exported = render_netbox_prefixes(&plan);
+        assert!(exported
+            .starts_with("prefix,status,vlan,description\n10.20.0.0/22,container,,Campus\n"));
+        let addresses = render_netbox_addresses(&plan);
+        assert!(addresses.contains("10.20.0.1/24,active,gw-office,Gateway\n"));
+        assert!(addresses.contains("10.20.0.10/24,active,printer,\"Printer, 2nd floor\"\n"));
+
+        let imported = to_plan(
+            &parse_prefixes(&exported).unwrap().rows,
+            &parse_addresses(&addresses).unwrap().rows,
+        )
+        .unwrap();
+        assert_eq!(imported.subnets[0].gateway, plan.subnets[0].gateway);
+        assert_eq!(imported.subnets.len(), plan.subnets.len());
+    }
+}
@@ -1,13 +1,44 @@
 use std::str::FromStr;
+pub mod acl;
+pub mod cidr;
+pub mod classful;
+pub mod classroom;
+mod conflicts;
+pub mod csv;
+pub mod dhcp;
+pub mod dns;
+pub mod duel;
+pub mod exam;
+mod explanation;
+pub mod export;
+pub mod grading;
+pub mod interfaces;
+pub mod inventory;
 mod ip_address;
+pub mod ipam;
+pub mod multiple_choice;
 mod network_address;
+pub mod nth;
+pub mod plan;
 mod prefix;
+pub mod routing;
 mod subnet_mask;
-use ip_address::IpAddress;
-use network_address::NetworkAddress;
-use prefix::Prefix;
-use rand::Rng;
-use subnet_mask::SubnetMask;
+pub mod subnetting;
+pub mod topology;
+pub mod trie;
+pub mod troubleshooting;
+mod wildcard_mask;
+use classful::ClassfulRules;
+pub use explanation::to_binary;
+pub use ip_address::{IpAddress, IpAddressError};
+pub use network_address::{NetworkAddress, NetworkAddressError};
+pub use prefix::{Prefix, PrefixError};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+pub use subnet_mask::{SubnetMask, SubnetMaskError};
+use thiserror::Error;
+pub use wildcard_mask::{WildcardMask, WildcardMaskError};
 
 #[derive(Debug, Clone)]
 pub struct IpModel {
@@ -20,7 +51,8 @@ pub struct IpModel {
     pub last_host: IpAddress,
     pub possible_hosts: Option<u32>,
 }
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct InputIpModel {
     pub ip: String,
     pub prefix: String,
@@ -29,10 +61,138 @@ pub struct InputIpModel {
     pub broadcast_address: String,
     pub first_host: String,
     pub last_host: String,
-    pub possible_hosts: Option<u32>,
+    // Kept as typed, so a non-numeric answer is wrong rather than blank
+    pub possible_hosts: String,
+}
+
+impl InputIpModel {
+    pub fn get(&self, field: Field) -> String {
+        match field {
+            Field::Mask => self.mask.clone(),
+            Field::NetworkAddress => self.network_address.clone(),
+            Field::BroadcastAddress => self.broadcast_address.clone(),
+            Field::FirstHost => self.first_host.clone(),
+            Field::LastHost => self.last_host.clone(),
+            Field::PossibleHosts => self.possible_hosts.clone(),
+        }
+    }
+
+    pub fn set(&mut self, field: Field, value: String) {
+        match field {
+            Field::Mask => self.mask = value,
+            Field::NetworkAddress => self.network_address = value,
+            Field::BroadcastAddress => self.broadcast_address = value,
+            Field::FirstHost => self.first_host = value,
+            Field::LastHost => self.last_host = value,
+            Field::PossibleHosts => self.possible_hosts = value,
+        }
+    }
+}
+
+// The fields of an IpModel the user has to work out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Field {
+    Mask,
+    NetworkAddress,
+    BroadcastAddress,
+    FirstHost,
+    LastHost,
+    PossibleHosts,
+}
+
+impl Field {
+    pub const ALL: [Field; 6] = [
+        Field::Mask,
+        Field::NetworkAddress,
+        Field::BroadcastAddress,
+        Field::FirstHost,
+        Field::LastHost,
+        Field::PossibleHosts,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Field::Mask => "Subnet Mask",
+            Field::NetworkAddress => "Network Address",
+            Field::BroadcastAddress => "Broadcast Address",
+            Field::FirstHost => "First Host",
+            Field::LastHost => "Last Host",
+            Field::PossibleHosts => "Possible Hosts",
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Field::PossibleHosts)
+    }
+
+    // Identifier used in files, e.g. `network_address`
+    pub fn key(&self) -> &'static str {
+        match self {
+            Field::Mask => "mask",
+            Field::NetworkAddress => "network_address",
+            Field::BroadcastAddress => "broadcast_address",
+            Field::FirstHost => "first_host",
+            Field::LastHost => "last_host",
+            Field::PossibleHosts => "possible_hosts",
+        }
+    }
+
+    // Accepts both the key and the label, ignoring case
+    pub fn from_name(name: &str) -> Option<Field> {
+        let name = name.trim();
+        Field::ALL.into_iter().find(|field| {
+            field.key().eq_ignore_ascii_case(name) || field.label().eq_ignore_ascii_case(name)
+        })
+    }
 }
 
 impl IpModel {
+    // Exercise written as `192.168.1.1/24`
+    pub fn from_cidr(cidr: &str) -> Option<IpModel> {
+        let (ip, prefix) = cidr.trim().split_once('/')?;
+        let ip: std::net::Ipv4Addr = ip.parse().ok()?;
+        let prefix: u8 = prefix.parse().ok()?;
+        if !(1..=30).contains(&prefix) {
+            return None;
+        }
+        Some(IpCalculator::new().calculate_model(&ip.octets(), prefix))
+    }
+
+    pub fn cidr(&self) -> String {
+        format!("{}{}", self.ip, self.prefix)
+    }
+
+    pub fn wildcard_mask(&self) -> WildcardMask {
+        self.prefix.to_wildcard_mask()
+    }
+
+    pub fn answer(&self, field: Field) -> String {
+        match field {
+            Field::Mask => self.mask.as_string(),
+            Field::NetworkAddress => self.network_address.as_string(),
+            Field::BroadcastAddress => self.broadcast_address.as_string(),
+            Field::FirstHost => self.first_host.as_string(),
+            Field::LastHost => self.last_host.as_string(),
+            Field::PossibleHosts => self.possible_hosts.unwrap_or(0).to_string(),
+        }
+    }
+
+    // The canonical answer first, followed by other notations a teacher would accept
+    pub fn accepted_answers(&self, field: Field) -> Vec<String> {
+        let mut answers = vec![self.answer(field)];
+        match field {
+            Field::Mask => {
+                answers.push(self.prefix.to_string());
+                answers.push(format!("0x{:08X}", self.prefix.get_network_mask()));
+            }
+            Field::NetworkAddress => {
+                answers.push(format!("{}{}", self.network_address, self.prefix));
+            }
+            _ => (),
+        }
+        answers
+    }
+
     pub fn validate(&self, other: &InputIpModel) -> Validator {
         Validator {
             mask: self.mask.as_string() == other.mask,
@@ -40,12 +200,12 @@ impl IpModel {
             broadcast_address: self.broadcast_address.as_string() == other.broadcast_address,
             first_host: self.first_host.as_string() == other.first_host,
             last_host: self.last_host.as_string() == other.last_host,
-            possible_hosts: self.possible_hosts == other.possible_hosts,
+            possible_hosts: self.possible_hosts == other.possible_hosts.trim().parse().ok(),
         }
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Validator {
     pub mask: bool,
     pub network_address: bool,
@@ -55,27 +215,144 @@ pub struct Validator {
     pub possible_hosts: bool,
 }
 
+impl Validator {
+    pub fn is_correct(&self, field: Field) -> bool {
+        match field {
+            Field::Mask => self.mask,
+            Field::NetworkAddress => self.network_address,
+            Field::BroadcastAddress => self.broadcast_address,
+            Field::FirstHost => self.first_host,
+            Field::LastHost => self.last_host,
+            Field::PossibleHosts => self.possible_hosts,
+        }
+    }
+
+    // Number of correctly answered fields
+    pub fn score(&self) -> usize {
+        Field::ALL
+            .iter()
+            .filter(|field| self.is_correct(**field))
+            .count()
+    }
+
+    pub fn all_correct(&self) -> bool {
+        self.score() == Field::ALL.len()
+    }
+}
+
+// Ranges the random exercises are drawn from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneratorSettings {
+    pub min_octet: u8,
+    pub max_octet: u8,
+    pub min_prefix: u8,
+    pub max_prefix: u8,
+}
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum SettingsError {
+    #[error("Invalid octet range {min}-{max}: expected 1 <= min <= max")]
+    InvalidOctets { min: u8, max: u8 },
+
+    #[error("Invalid prefix range /{min}-/{max}: expected 1 <= min <= max <= 30")]
+    InvalidPrefixes { min: u8, max: u8 },
+}
+
+impl GeneratorSettings {
+    // Empty ranges can not be sampled and prefixes above /30 have no hosts
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        if self.min_octet == 0 || self.min_octet > self.max_octet {
+            return Err(SettingsError::InvalidOctets {
+                min: self.min_octet,
+                max: self.max_octet,
+            });
+        }
+        if self.min_prefix == 0 || self.min_prefix > self.max_prefix || self.max_prefix > 30 {
+            return Err(SettingsError::InvalidPrefixes {
+                min: self.min_prefix,
+                max: self.max_prefix,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            min_octet: 111,
+            max_octet: 254,
+            min_prefix: 18,
+            max_prefix: 27,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct IpCalculator {
-    rng: rand::rngs::ThreadRng,
+    rng: StdRng,
+    settings: GeneratorSettings,
+    classful: Option<ClassfulRules>,
+}
+
+impl Default for IpCalculator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl IpCalculator {
     pub fn new() -> Self {
         Self {
-            rng: rand::thread_rng(),
+            rng: StdRng::from_entropy(),
+            settings: GeneratorSettings::default(),
+            classful: None,
+        }
+    }
+
+    // A calculator with a fixed seed always generates the same exercises
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            settings: GeneratorSettings::default(),
+            classful: None,
         }
     }
 
+    pub fn with_settings(settings: GeneratorSettings, seed: u64) -> Result<Self, SettingsError> {
+        settings.validate()?;
+        Ok(Self {
+            settings,
+            ..Self::with_seed(seed)
+        })
+    }
+
+    pub fn settings(&self) -> &GeneratorSettings {
+        &self.settings
+    }
+
+    pub fn random_models(&mut self, count: usize) -> Vec<IpModel> {
+        (0..count).map(|_| self.random_model()).collect()
+    }
+
     pub fn random_model(&mut self) -> IpModel {
+        // Classful rules only allow class A to C networks that are actually subnetted
+        if let Some(rules) = self.classful {
+            return self.random_classful_model(rules);
+        }
+
         // Generate random IP and subnet mask
+        let octets_range = self.settings.min_octet..=self.settings.max_octet;
         let octets: [u8; 4] = [
-            self.rng.gen_range(111..255),
-            self.rng.gen_range(111..255),
-            self.rng.gen_range(111..255),
-            self.rng.gen_range(111..255),
+            self.rng.gen_range(octets_range.clone()),
+            self.rng.gen_range(octets_range.clone()),
+            self.rng.gen_range(octets_range.clone()),
+            self.rng.gen_range(octets_range),
         ];
-        let subnet_mask: u8 = self.rng.gen_range(18..28);
+        let subnet_mask: u8 = self
+            .rng
+            .gen_range(self.settings.min_prefix..=self.settings.max_prefix);
 
         self.calculate_model(&octets, subnet_mask)
     }
@@ -102,7 +379,7 @@ impl IpCalculator {
         let first_host_value = network_value + 1;
         let last_host_value = broadcast_value - 1;
 
-        let model = IpModel {
+        IpModel {
             ip,
             prefix: prefix.clone(),
             mask: prefix.to_subnet_mask(),
@@ -112,12 +389,7 @@ impl IpCalculator {
             first_host: IpAddress::from_str(&self.u32_to_ip_string(first_host_value)).unwrap(),
             last_host: IpAddress::from_str(&self.u32_to_ip_string(last_host_value)).unwrap(),
             possible_hosts: Some(prefix.get_max_hosts()),
-        };
-
-        #[cfg(debug_assertions)]
-        println!("{:?}", model.clone());
-
-        model
+        }
     }
 
     fn octets_to_u32(&self, octets: &[u8; 4]) -> u32 {
@@ -160,6 +432,15 @@ mod tests {
         assert_eq!(model.possible_hosts, Some(254));
     }
 
+    #[test]
+    fn test_zero_octet_network() {
+        // Used to panic on the network address, which has zero octets
+        let model = IpCalculator::new().calculate_model(&[10, 0, 0, 1], 8);
+        assert_eq!(model.network_address.as_string(), "10.0.0.0");
+        assert_eq!(model.first_host.as_string(), "10.0.0.1");
+        assert_eq!(model.broadcast_address.as_string(), "10.255.255.255");
+    }
+
     #[test]
     fn test_random_model() {
         let mut calculator = IpCalculator::new();
@@ -170,4 +451,91 @@ mod tests {
         assert!(model.first_host.as_string() > model.network_address.as_string());
         assert!(model.last_host.as_string() < model.broadcast_address.as_string());
     }
+
+    #[test]
+    fn test_seeded_models_are_reproducible() {
+        let first = IpCalculator::with_seed(42).random_models(5);
+        let second = IpCalculator::with_seed(42).random_models(5);
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.ip, b.ip);
+            assert_eq!(a.prefix, b.prefix);
+        }
+    }
+
+    #[test]
+    fn test_generator_settings() {
+        let settings = GeneratorSettings {
+            min_prefix: 30,
+            max_prefix: 30,
+            ..Default::default()
+        };
+        let mut calculator = IpCalculator::with_settings(settings, 7).unwrap();
+        for model in calculator.random_models(10) {
+            assert_eq!(model.prefix.value(), 30);
+            assert_eq!(model.possible_hosts, Some(2));
+        }
+
+        let invalid = [
+            (1, 254, 29, 28),
+            (0, 254, 18, 27),
+            (200, 100, 18, 27),
+            (1, 254, 18, 31),
+        ];
+        for (min_octet, max_octet, min_prefix, max_prefix) in invalid {
+            let settings = GeneratorSettings {
+                min_octet,
+                max_octet,
+                min_prefix,
+                max_prefix,
+            };
+            assert!(IpCalculator::with_settings(settings, 7).is_err());
+        }
+    }
+
+    #[test]
+    fn test_validator_score() {
+        let model = IpCalculator::new().calculate_model(&[192, 168, 1, 1], 24);
+        let mut input = InputIpModel::default();
+        input.set(Field::Mask, "255.255.255.0".to_string());
+        input.set(Field::PossibleHosts, "254".to_string());
+        input.set(Field::FirstHost, "192.168.1.0".to_string());
+        let validator = model.validate(&input);
+        assert_eq!(validator.score(), 2);
+        assert!(validator.is_correct(Field::PossibleHosts));
+        assert!(!validator.is_correct(Field::FirstHost));
+        assert!(!validator.all_correct());
+    }
+
+    #[test]
+    fn test_from_cidr() {
+        let model = IpModel::from_cidr("10.0.0.1/8").unwrap();
+        assert_eq!(model.broadcast_address.as_string(), "10.255.255.255");
+        assert_eq!(model.cidr(), "10.0.0.1/8");
+        assert!(IpModel::from_cidr("10.0.0.1").is_none());
+        assert!(IpModel::from_cidr("10.0.0.1/31").is_none());
+    }
+
+    #[test]
+    fn test_field_from_name() {
+        assert_eq!(
+            Field::from_name("network_address"),
+            Some(Field::NetworkAddress)
+        );
+        assert_eq!(Field::from_name(" first host "), Some(Field::FirstHost));
+        assert_eq!(Field::from_name("gateway"), None);
+    }
+
+    #[test]
+    fn test_accepted_answers() {
+        let model = IpCalculator::new().calculate_model(&[192, 168, 1, 1], 24);
+        assert_eq!(
+            model.accepted_answers(Field::Mask),
+            vec!["255.255.255.0", "/24", "0xFFFFFF00"]
+        );
+        assert_eq!(
+            model.accepted_answers(Field::NetworkAddress),
+            vec!["192.168.1.0", "192.168.1.0/24"]
+        );
+        assert_eq!(model.answer(Field::PossibleHosts), "254");
+    }
 }
@@ -0,0 +1,187 @@
+use std::net::Ipv4Addr;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::export::question_text;
+use crate::{Field, IpCalculator, IpModel};
+
+pub const OPTION_COUNT: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct Question {
+    pub model: IpModel,
+    pub field: Field,
+    pub text: String,
+    pub options: Vec<String>,
+    pub correct: usize,
+}
+
+impl Question {
+    pub fn new<R: Rng>(model: IpModel, field: Field, rng: &mut R) -> Self {
+        let answer = model.answer(field);
+        let mut options = vec![answer.clone()];
+        for candidate in distractors(&model, field) {
+            if options.len() == OPTION_COUNT {
+                break;
+            }
+            if !options.contains(&candidate) {
+                options.push(candidate);
+            }
+        }
+        options.shuffle(rng);
+        let correct = options.iter().position(|o| *o == answer).unwrap();
+
+        Self {
+            text: question_text(&model, field),
+            model,
+            field,
+            options,
+            correct,
+        }
+    }
+
+    pub fn is_correct(&self, choice: usize) -> bool {
+        choice == self.correct
+    }
+}
+
+fn dotted(value: u32) -> String {
+    Ipv4Addr::from(value).to_string()
+}
+
+// Wrong answers from realistic mistakes, most convincing first
+fn distractors(model: &IpModel, field: Field) -> Vec<String> {
+    let octets = model.ip.to_u32().to_be_bytes();
+    let prefix = model.prefix.value();
+    // Models are public, so a /0 with a block of 2^32 has to work too
+    let block = 1u64 << (32 - prefix);
+
+    // Same question answered with the prefix off by one
+    let mut candidates: Vec<String> = [
+        prefix.checked_sub(1),
+        Some(prefix + 1),
+        prefix.checked_sub(2),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|p| (1..=30).contains(p))
+    .map(|p| {
+        IpCalculator::new()
+            .calculate_model(&octets, p)
+            .answer(field)
+    })
+    .collect();
+
+    match field {
+        Field::Mask => {
+            // Prefix mixed up with a classful default
+            let classful = match octets[0] {
+                0..=127 => 8,
+                128..=191 => 16,
+                _ => 24,
+            };
+            candidates.push(
+                IpCalculator::new()
+                    .calculate_model(&octets, classful)
+                    .answer(field),
+            );
+        }
+        Field::PossibleHosts => {
+            // Forgot to remove network and broadcast, or only one of them
+            candidates.insert(0, block.to_string());
+            candidates.insert(1, (block - 1).to_string());
+        }
+        _ => {
+            let value = model
+                .answer(field)
+                .parse::<Ipv4Addr>()
+                .map(u32::from)
+                .unwrap();
+            // Off by one host, and the same position in the neighbouring blocks.
+            // The block of a /0 wraps to 0, the repeated answer is skipped.
+            let block = block as u32;
+            candidates.insert(0, dotted(value.wrapping_add(1)));
+            candidates.insert(1, dotted(value.wrapping_sub(1)));
+            candidates.insert(2, dotted(value.wrapping_add(block)));
+            candidates.push(dotted(value.wrapping_sub(block)));
+            candidates.push(dotted(value.wrapping_add(2 * block)));
+        }
+    }
+
+    // Fallbacks so there are always enough distinct options
+    let answer = model.answer(field);
+    match answer.parse::<u64>() {
+        Ok(n) => candidates.extend((1..=3).map(|i| (n * 2 + i).to_string())),
+        Err(_) => {
+            let value = answer.parse::<Ipv4Addr>().map(u32::from).unwrap();
+            candidates.extend((1..=3).map(|i| dotted(value ^ (1 << (31 - i * 8)))));
+        }
+    }
+    candidates
+}
+
+impl IpCalculator {
+    // Random exercise with a random field asked as multiple choice
+    pub fn random_question(&mut self) -> Question {
+        let model = self.random_model();
+        let field = *Field::ALL.choose(&mut self.rng).unwrap();
+        Question::new(model, field, &mut self.rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn question(field: Field) -> Question {
+        let model = IpModel::from_cidr("172.19.77.5/21").unwrap();
+        Question::new(model, field, &mut StdRng::seed_from_u64(1))
+    }
+
+    #[test]
+    fn test_whole_address_space() {
+        let model = IpCalculator::new().calculate_model(&[10, 0, 0, 1], 0);
+        for field in Field::ALL {
+            let q = Question::new(model.clone(), field, &mut StdRng::seed_from_u64(1));
+            assert_eq!(q.options.len(), OPTION_COUNT);
+            assert_eq!(q.options[q.correct], model.answer(field));
+        }
+    }
+
+    #[test]
+    fn test_broadcast_question() {
+        let q = question(Field::BroadcastAddress);
+        assert_eq!(q.text, "What is the broadcast address of 172.19.77.5/21?");
+        assert_eq!(q.options.len(), OPTION_COUNT);
+        assert_eq!(q.options[q.correct], "172.19.79.255");
+        assert!(q.is_correct(q.correct));
+        // Off by one host, previous address and the neighbouring block
+        for expected in ["172.19.80.0", "172.19.79.254", "172.19.87.255"] {
+            assert!(q.options.contains(&expected.to_string()), "{:?}", q.options);
+        }
+    }
+
+    #[test]
+    fn test_possible_hosts_question() {
+        let q = question(Field::PossibleHosts);
+        assert_eq!(q.options[q.correct], "2046");
+        assert!(q.options.contains(&"2048".to_string()));
+        assert!(q.options.contains(&"2047".to_string()));
+    }
+
+    #[test]
+    fn test_options_are_distinct() {
+        let mut calculator = IpCalculator::with_seed(3);
+        for _ in 0..200 {
+            let q = calculator.random_question();
+            let mut options = q.options.clone();
+            options.sort();
+            options.dedup();
+            assert_eq!(options.len(), OPTION_COUNT, "{:?}", q);
+            assert_eq!(q.options[q.correct], q.model.answer(q.field));
+        }
+    }
+}
@@ -58,7 +58,7 @@ impl NetworkAddress {
         }
 
         // Check if host bits are all 0
-        let host_bits_mask = (1u32 << (32 - prefix)) - 1;
+        let host_bits_mask = u32::MAX.checked_shr(prefix as u32).unwrap_or(0);
         if (binary_addr & host_bits_mask) != 0 {
             return Err(NetworkAddressError::InvalidHostBits { prefix });
         }
@@ -71,21 +71,15 @@ impl NetworkAddress {
     }
 
     pub fn to_u32(&self) -> u32 {
-        let octets: Vec<u8> = self
-            .0
+        self.0
             .split('.')
             .map(|x| x.parse::<u8>().unwrap())
-            .collect();
-
-        ((octets[0] as u32) << 24)
-            | ((octets[1] as u32) << 16)
-            | ((octets[2] as u32) << 8)
-            | (octets[3] as u32)
+            .fold(0, |acc, octet| (acc << 8) | octet as u32)
     }
 
     pub fn from_u32(addr: u32, prefix: u8) -> Result<Self, NetworkAddressError> {
         // Ensure host bits are 0
-        let host_bits_mask = (1u32 << (32 - prefix)) - 1;
+        let host_bits_mask = u32::MAX.checked_shr(prefix as u32).unwrap_or(0);
         let network_addr = addr & !host_bits_mask;
 
         let address = format!(
@@ -0,0 +1,222 @@
+use rand::Rng;
+
+use crate::cidr::Cidr;
+use crate::IpCalculator;
+
+#[derive(Debug, Clone)]
+pub enum NthExercise {
+    // The `ordinal`th subnet when `network` is divided into /`prefix` subnets
+    Subnet {
+        network: Cidr,
+        prefix: u8,
+        ordinal: u64,
+        subnet_zero: bool,
+    },
+    // The `ordinal`th usable host of `network`
+    Host {
+        network: Cidr,
+        ordinal: u64,
+    },
+}
+
+// 1st, 2nd, 3rd, 4th, ..., 11th, 12th, 13th, ..., 21st
+pub fn ordinal(n: u64) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+impl NthExercise {
+    pub fn text(&self) -> String {
+        match self {
+            NthExercise::Subnet {
+                network,
+                prefix,
+                ordinal: n,
+                subnet_zero: true,
+            } => format!(
+                "What is the {} subnet when {} is divided into /{}s? Subnet zero is the 1st subnet.",
+                ordinal(*n),
+                network,
+                prefix
+            ),
+            NthExercise::Subnet {
+                network,
+                prefix,
+                ordinal: n,
+                subnet_zero: false,
+            } => format!(
+                "What is the {} usable subnet when {} is divided into /{}s? Subnet zero and the all-ones subnet are not used.",
+                ordinal(*n),
+                network,
+                prefix
+            ),
+            NthExercise::Host {
+                network,
+                ordinal: n,
+            } => format!("What is the {} host of {}?", ordinal(*n), network),
+        }
+    }
+
+    // Empty if there is no such subnet or host, e.g. for a 0th one
+    pub fn answer(&self) -> String {
+        match self {
+            NthExercise::Subnet {
+                network,
+                prefix,
+                ordinal,
+                subnet_zero,
+            } => {
+                // Without subnet zero the count starts at the second subnet
+                // and stops before the all-ones subnet
+                let count = prefix
+                    .checked_sub(network.prefix.value())
+                    .map_or(0, |borrowed| 1u64 << borrowed);
+                ordinal
+                    .checked_sub(1)
+                    .map(|index| index + u64::from(!subnet_zero))
+                    .filter(|&index| *subnet_zero || index + 1 < count)
+                    .and_then(|index| network.nth_subnet(*prefix, index).ok().flatten())
+                    .map_or(String::new(), |subnet| subnet.to_string())
+            }
+            NthExercise::Host { network, ordinal } => ordinal
+                .checked_sub(1)
+                .and_then(|index| network.nth_host(index))
+                .map_or(String::new(), |host| host.as_string()),
+        }
+    }
+
+    // Subnets may be given with or without their prefix
+    pub fn check(&self, answer: &str) -> bool {
+        let answer = answer.trim();
+        let expected = self.answer();
+        if expected.is_empty() {
+            return false;
+        }
+        match self {
+            NthExercise::Subnet { .. } => {
+                answer == expected
+                    || expected.split_once('/').map(|(network, _)| network) == Some(answer)
+            }
+            NthExercise::Host { .. } => answer == expected,
+        }
+    }
+}
+
+impl IpCalculator {
+    pub fn random_nth_exercise(&mut self, subnet_zero: bool) -> NthExercise {
+        let network = self.random_model().network();
+        let prefix = network.prefix.value();
+
+        // Subnets need at least two borrowed bits so there is a usable one without subnet zero
+        if prefix <= 28 && self.rng.gen_bool(0.5) {
+            let borrowed = self.rng.gen_range(2..=(30 - prefix).min(6));
+            let count = 1u64 << borrowed;
+            let usable = if subnet_zero { count } else { count - 2 };
+            return NthExercise::Subnet {
+                network,
+                prefix: prefix + borrowed,
+                ordinal: self.rng.gen_range(1..=usable),
+                subnet_zero,
+            };
+        }
+
+        let hosts = network.hosts().len() as u64;
+        NthExercise::Host {
+            ordinal: self.rng.gen_range(1..=hosts.min(1000)),
+            network,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordinal() {
+        let ordinals: Vec<String> = [1, 2, 3, 4, 11, 12, 13, 21, 22, 100, 101, 111]
+            .into_iter()
+            .map(ordinal)
+            .collect();
+        assert_eq!(
+            ordinals,
+            [
+                "1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "100th",
+                "101st", "111th"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nth_subnet() {
+        let exercise = |ordinal, subnet_zero| NthExercise::Subnet {
+            network: "192.168.40.0/22".parse().unwrap(),
+            prefix: 26,
+            ordinal,
+            subnet_zero,
+        };
+        let with_zero = exercise(5, true);
+        assert_eq!(with_zero.answer(), "192.168.41.0/26");
+        assert!(with_zero.check("192.168.41.0"));
+        assert!(with_zero.check("192.168.41.0/26"));
+        assert!(!with_zero.check("192.168.41.0/25"));
+
+        let without_zero = exercise(5, false);
+        assert_eq!(
+            without_zero.text(),
+            "What is the 5th usable subnet when 192.168.40.0/22 is divided into /26s? Subnet zero and the all-ones subnet are not used."
+        );
+        assert_eq!(without_zero.answer(), "192.168.41.64/26");
+
+        assert_eq!(exercise(14, false).answer(), "192.168.43.128/26");
+        // The 15th usable would be the all-ones subnet
+        assert_eq!(exercise(15, false).answer(), "");
+        assert!(!exercise(15, false).check("192.168.43.192/26"));
+        assert_eq!(exercise(16, true).answer(), "192.168.43.192/26");
+    }
+
+    #[test]
+    fn test_nth_host() {
+        let exercise = NthExercise::Host {
+            network: "10.8.0.0/13".parse().unwrap(),
+            ordinal: 100,
+        };
+        assert_eq!(exercise.text(), "What is the 100th host of 10.8.0.0/13?");
+        assert_eq!(exercise.answer(), "10.8.0.100");
+        assert!(exercise.check(" 10.8.0.100 "));
+        assert!(!exercise.check("10.8.0.99"));
+
+        let zeroth = NthExercise::Host {
+            network: "10.8.0.0/13".parse().unwrap(),
+            ordinal: 0,
+        };
+        assert_eq!(zeroth.answer(), "");
+        assert!(!zeroth.check(""));
+    }
+
+    #[test]
+    fn test_random_exercises() {
+        for subnet_zero in [true, false] {
+            let mut calculator = IpCalculator::with_seed(11);
+            for _ in 0..200 {
+                let exercise = calculator.random_nth_exercise(subnet_zero);
+                assert!(!exercise.answer().is_empty(), "{}", exercise.text());
+                assert!(exercise.check(&exercise.answer()));
+                // The all-ones subnet is never asked for without subnet zero
+                if let NthExercise::Subnet {
+                    network, prefix, ..
+                } = &exercise
+                {
+                    let last = network.split(*prefix).unwrap().next_back().unwrap();
+                    assert!(subnet_zero || exercise.answer() != last.to_string());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,473 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::cidr::{self, Cidr};
+use crate::conflicts::{fixed_addresses, Conflicts};
+use crate::ip_address::IpAddress;
+
+#[derive(Debug, Error)]
+pub enum PlanError {
+    #[error("Invalid plan file: {0}")]
+    InvalidToml(#[from] toml::de::Error),
+
+    #[error("Failed to write plan file: {0}")]
+    Serialize(#[from] toml::ser::Error),
+
+    #[error("Invalid plan file: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    #[error("Unknown plan format of '{0}', expected a .toml or .json file")]
+    UnknownFormat(String),
+
+    #[error("No free /{prefix} left in {block}")]
+    NoSpace { prefix: u8, block: String },
+}
+
+// Problems in a plan, the plan can still be saved with them
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum PlanIssue {
+    #[error("{subnet} ({name}) is not inside {block}")]
+    OutsideBlock {
+        name: String,
+        subnet: String,
+        block: String,
+    },
+
+    #[error("{subnet} ({name}) overlaps {other} ({other_name})")]
+    Overlap {
+        name: String,
+        subnet: String,
+        other_name: String,
+        other: String,
+    },
+
+    #[error("VLAN {vlan} is used by {name} and {other_name}")]
+    DuplicateVlan {
+        vlan: u16,
+        name: String,
+        other_name: String,
+    },
+
+    #[error("{address} ({host}) is not a usable address of {subnet} ({name})")]
+    AddressOutsideSubnet {
+        name: String,
+        subnet: String,
+        host: String,
+        address: String,
+    },
+
+    #[error("{address} is used by {host} and {other_host}")]
+    DuplicateAddress {
+        address: String,
+        host: String,
+        other_host: String,
+    },
+
+    #[error("{host} has an invalid MAC address '{mac}'")]
+    InvalidMac { host: String, mac: String },
+
+    #[error("'{host}' has no letters or digits to use as DNS name")]
+    EmptyDnsName { host: String },
+
+    #[error("{host} and {other_host} both get the DNS name {dns_name}")]
+    DuplicateDnsName {
+        dns_name: String,
+        host: String,
+        other_host: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanFormat {
+    Toml,
+    Json,
+}
+
+impl PlanFormat {
+    // Picked by the file extension
+    pub fn from_path(path: &Path) -> Result<Self, PlanError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Ok(PlanFormat::Toml),
+            Some("json") => Ok(PlanFormat::Json),
+            _ => Err(PlanError::UnknownFormat(path.display().to_string())),
+        }
+    }
+}
+
+// A fixed address for a named host, e.g. a server or printer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reservation {
+    pub name: String,
+    pub address: IpAddress,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedSubnet {
+    pub name: String,
+    pub network: Cidr,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub purpose: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vlan: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gateway: Option<IpAddress>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reservations: Vec<Reservation>,
+}
+
+// Six hex octets separated by `:` or `-`
+pub fn is_valid_mac(mac: &str) -> bool {
+    let octets: Vec<&str> = mac.split([':', '-']).collect();
+    octets.len() == 6
+        && octets
+            .iter()
+            .all(|octet| octet.len() == 2 && octet.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+impl PlannedSubnet {
+    pub fn new(name: &str, network: Cidr) -> Self {
+        Self {
+            name: name.to_string(),
+            network,
+            purpose: String::new(),
+            vlan: None,
+            gateway: None,
+            reservations: Vec::new(),
+        }
+    }
+}
+
+// How much of a network is in use
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utilisation {
+    pub name: String,
+    pub network: Cidr,
+    pub used: u64,
+    pub total: u64,
+}
+
+impl Utilisation {
+    pub fn percent(&self) -> f64 {
+        self.used as f64 * 100.0 / self.total as f64
+    }
+}
+
+impl fmt::Display for Utilisation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}): {}/{} used ({:.1}%)",
+            self.network,
+            self.name,
+            self.used,
+            self.total,
+            self.percent()
+        )
+    }
+}
+
+// How an address block is divided into subnets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressPlan {
+    #[serde(default)]
+    pub name: String,
+    pub block: Cidr,
+    #[serde(default)]
+    pub subnets: Vec<PlannedSubnet>,
+}
+
+impl AddressPlan {
+    pub fn new(name: &str, block: Cidr) -> Self {
+        Self {
+            name: name.to_string(),
+            block,
+            subnets: Vec::new(),
+        }
+    }
+
+    pub fn parse(text: &str, format: PlanFormat) -> Result<Self, PlanError> {
+        Ok(match format {
+            PlanFormat::Toml => toml::from_str(text)?,
+            PlanFormat::Json => serde_json::from_str(text)?,
+        })
+    }
+
+    pub fn to_text(&self, format: PlanFormat) -> Result<String, PlanError> {
+        Ok(match format {
+            PlanFormat::Toml => toml::to_string(self)?,
+            PlanFormat::Json => serde_json::to_string_pretty(self)? + "\n",
+        })
+    }
+
+    // Parts of the block no subnet uses, as a minimal list of networks
+    pub fn free_space(&self) -> Vec<Cidr> {
+        let used: Vec<Cidr> = self.subnets.iter().map(|s| s.network.clone()).collect();
+        cidr::exclude(std::slice::from_ref(&self.block), &used)
+    }
+
+    // The first free network of the given size
+    pub fn find_free(&self, prefix: u8) -> Option<Cidr> {
+        // Free space consists of aligned networks, so the first subnet of one is aligned too
+        self.free_space()
+            .into_iter()
+            .find(|free| free.prefix.value() <= prefix)
+            .and_then(|free| free.nth_subnet(prefix, 0).ok().flatten())
+    }
+
+    // Adds a subnet in the first free space that fits
+    pub fn allocate(&mut self, name: &str, prefix: u8) -> Result<&mut PlannedSubnet, PlanError> {
+        let network = self.find_free(prefix).ok_or_else(|| PlanError::NoSpace {
+            prefix,
+            block: self.block.to_string(),
+        })?;
+        // Keep the subnets in address order
+        let index = self
+            .subnets
+            .partition_point(|subnet| subnet.network.first() < network.first());
+        self.subnets
+            .insert(index, PlannedSubnet::new(name, network));
+        Ok(&mut self.subnets[index])
+    }
+
+    // The block by addresses covered by subnets, each subnet by its fixed addresses
+    pub fn utilisation(&self) -> Vec<Utilisation> {
+        let free: u64 = self.free_space().iter().map(Cidr::size).sum();
+        let mut utilisation = vec![Utilisation {
+            name: self.name.clone(),
+            network: self.block.clone(),
+            used: self.block.size() - free,
+            total: self.block.size(),
+        }];
+        for subnet in &self.subnets {
+            let mut used: Vec<u32> = subnet
+                .gateway
+                .iter()
+                .chain(subnet.reservations.iter().map(|host| &host.address))
+                .filter(|address| subnet.network.contains_host(address))
+                .map(IpAddress::to_u32)
+                .collect();
+            used.sort_unstable();
+            used.dedup();
+            utilisation.push(Utilisation {
+                name: subnet.name.clone(),
+                network: subnet.network.clone(),
+                used: used.len() as u64,
+                total: subnet.network.hosts().len() as u64,
+            });
+        }
+        utilisation
+    }
+
+    pub fn check(&self) -> Vec<PlanIssue> {
+        let mut issues = Vec::new();
+        let mut conflicts = Conflicts::new();
+        let mut vlans: BTreeMap<u16, &str> = BTreeMap::new();
+
+        for (index, subnet) in self.subnets.iter().enumerate() {
+            if !self.block.contains_network(&subnet.network) {
+                issues.push(PlanIssue::OutsideBlock {
+                    name: subnet.name.clone(),
+                    subnet: subnet.network.to_string(),
+                    block: self.block.to_string(),
+                });
+            }
+            let mut overlapping = conflicts.add_network(&subnet.network, index);
+            overlapping.sort_unstable();
+            for other in overlapping.iter().map(|&other| &self.subnets[other]) {
+                issues.push(PlanIssue::Overlap {
+                    name: subnet.name.clone(),
+                    subnet: subnet.network.to_string(),
+                    other_name: other.name.clone(),
+                    other: other.network.to_string(),
+                });
+            }
+            if let Some(vlan) = subnet.vlan {
+                if let Some(other_name) = vlans.insert(vlan, &subnet.name) {
+                    issues.push(PlanIssue::DuplicateVlan {
+                        vlan,
+                        name: subnet.name.clone(),
+                        other_name: other_name.to_string(),
+                    });
+                    // Later subnets are reported against the first one
+                    vlans.insert(vlan, other_name);
+                }
+            }
+
+            // The gateway is checked like any other fixed address
+            let fixed =
+                fixed_addresses(&subnet.name, subnet.gateway.as_ref(), &subnet.reservations);
+            for (address, host) in fixed {
+                if !subnet.network.contains_host(address) {
+                    issues.push(PlanIssue::AddressOutsideSubnet {
+                        name: subnet.name.clone(),
+                        subnet: subnet.network.to_string(),
+                        host: host.clone(),
+                        address: address.as_string(),
+                    });
+                }
+                if let Some(other_host) = conflicts.add_address(address, host.clone()) {
+                    issues.push(PlanIssue::DuplicateAddress {
+                        address: address.as_string(),
+                        host,
+                        other_host,
+                    });
+                }
+            }
+            for host in &subnet.reservations {
+                if let Some(mac) = host.mac.as_ref().filter(|mac| !is_valid_mac(mac)) {
+                    issues.push(PlanIssue::InvalidMac {
+                        host: host.name.clone(),
+                        mac: mac.clone(),
+                    });
+                }
+            }
+        }
+        issues.extend(crate::dns::name_issues(self));
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = r#"
+name = "Campus"
+block = "10.20.0.0/22"
+
+[[subnets]]
+name = "Office"
+network = "10.20.0.0/24"
+purpose = "Staff workstations"
+vlan = 10
+gateway = "10.20.0.1"
+
+[[subnets.reservations]]
+name = "printer"
+address = "10.20.0.10"
+mac = "00:11:22:33:44:55"
+
+[[subnets]]
+name = "Servers"
+network = "10.20.2.0/26"
+vlan = 20
+"#;
+
+    fn plan() -> AddressPlan {
+        AddressPlan::parse(PLAN, PlanFormat::Toml).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_round_trip() {
+        let plan = plan();
+        assert_eq!(plan.subnets.len(), 2);
+        assert_eq!(
+            plan.subnets[0].reservations[0].address.as_string(),
+            "10.20.0.10"
+        );
+        assert!(plan.check().is_empty());
+
+        for format in [PlanFormat::Toml, PlanFormat::Json] {
+            let text = plan.to_text(format).unwrap();
+            assert_eq!(AddressPlan::parse(&text, format).unwrap(), plan);
+        }
+        assert!(matches!(
+            AddressPlan::parse("block = \"10.20.0.1/22\"", PlanFormat::Toml),
+            Err(PlanError::InvalidToml(_))
+        ));
+        assert_eq!(
+            PlanFormat::from_path(Path::new("plan.json")).unwrap(),
+            PlanFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_free_space_and_allocate() {
+        let mut plan = plan();
+        let free: Vec<String> = plan.free_space().iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            free,
+            [
+                "10.20.1.0/24",
+                "10.20.2.64/26",
+                "10.20.2.128/25",
+                "10.20.3.0/24"
+            ]
+        );
+
+        let utilisation: Vec<String> = plan.utilisation().iter().map(|u| u.to_string()).collect();
+        assert_eq!(
+            utilisation,
+            [
+                "10.20.0.0/22 (Campus): 320/1024 used (31.2%)",
+                "10.20.0.0/24 (Office): 2/254 used (0.8%)",
+                "10.20.2.0/26 (Servers): 0/62 used (0.0%)",
+            ]
+        );
+
+        let guests = plan.allocate("Guests", 25).unwrap();
+        assert_eq!(guests.network.to_string(), "10.20.1.0/25");
+        assert_eq!(plan.subnets[1].name, "Guests");
+        assert_eq!(plan.find_free(26).unwrap().to_string(), "10.20.1.128/26");
+        assert!(matches!(
+            plan.allocate("Huge", 22),
+            Err(PlanError::NoSpace { prefix: 22, .. })
+        ));
+    }
+
+    #[test]
+    fn test_check() {
+        let mut plan = plan();
+        let mut lab = PlannedSubnet::new("Lab", "10.20.0.128/25".parse().unwrap());
+        lab.vlan = Some(10);
+        lab.gateway = Some(IpAddress::new("10.20.0.129".to_string()));
+        plan.subnets.push(lab);
+        plan.subnets.push(PlannedSubnet::new(
+            "Outside",
+            "10.20.4.0/24".parse().unwrap(),
+        ));
+        plan.subnets[0].reservations[0].address = IpAddress::new("10.20.0.255".to_string());
+        plan.subnets[0].reservations.push(Reservation {
+            name: "camera".to_string(),
+            address: IpAddress::new("10.20.0.129".to_string()),
+            mac: Some("00:11:22:33:44".to_string()),
+            description: String::new(),
+        });
+
+        let issues: Vec<String> = plan.check().iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            issues,
+            [
+                "10.20.0.255 (printer) is not a usable address of 10.20.0.0/24 (Office)",
+                "camera has an invalid MAC address '00:11:22:33:44'",
+                "10.20.0.128/25 (Lab) overlaps 10.20.0.0/24 (Office)",
+                "VLAN 10 is used by Lab and Office",
+                "10.20.0.129 is used by gateway of Lab and camera",
+                "10.20.4.0/24 (Outside) is not inside 10.20.0.0/22",
+            ]
+        );
+
+        let mut plan = self::plan();
+        let mut host = plan.subnets[0].reservations[0].clone();
+        host.name = "Printer!".to_string();
+        plan.subnets[0].reservations.push(host.clone());
+        host.name = "???".to_string();
+        plan.subnets[0].reservations.push(host);
+        let issues: Vec<String> = plan.check().iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            &issues[issues.len() - 2..],
+            [
+                "Printer! and printer both get the DNS name printer",
+                "'???' has no letters or digits to use as DNS name",
+            ]
+        );
+    }
+}
@@ -3,13 +3,14 @@ use std::str::FromStr;
 use thiserror::Error;
 
 use crate::subnet_mask::SubnetMask;
+use crate::wildcard_mask::WildcardMask;
 
 #[derive(Debug, Clone)]
 pub struct Prefix(u8);
 
 #[derive(Debug, Error)]
 pub enum PrefixError {
-    #[error("Invalid prefix length: must be between 1 and 32, got {0}")]
+    #[error("Invalid prefix length: must be between 0 and 32, got {0}")]
     InvalidLength(u8),
 
     #[error("Failed to parse prefix: {0}")]
@@ -24,7 +25,7 @@ impl PartialEq for Prefix {
 
 impl Prefix {
     pub fn new(prefix: u8) -> Result<Self, PrefixError> {
-        if prefix < 1 || prefix > 32 {
+        if prefix > 32 {
             return Err(PrefixError::InvalidLength(prefix));
         }
         Ok(Self(prefix))
@@ -35,7 +36,7 @@ impl Prefix {
     }
 
     pub fn to_subnet_mask(&self) -> SubnetMask {
-        let mask_value = !0u32 << (32 - self.0);
+        let mask_value = self.get_network_mask();
         let mask_str = format!(
             "{}.{}.{}.{}",
             (mask_value >> 24) & 0xFF,
@@ -112,18 +113,24 @@ impl Default for Prefix {
 // Helper methods for network calculations
 impl Prefix {
     pub fn get_network_mask(&self) -> u32 {
-        !0u32 << (32 - self.0)
+        // A /0 default route has no network bits at all
+        (!0u32).checked_shl(32 - self.0 as u32).unwrap_or(0)
     }
 
     pub fn get_host_mask(&self) -> u32 {
         !self.get_network_mask()
     }
 
+    // Cisco ACLs write the host mask as wildcard mask
+    pub fn to_wildcard_mask(&self) -> WildcardMask {
+        WildcardMask::from_u32(self.get_host_mask())
+    }
+
     pub fn get_max_hosts(&self) -> u32 {
         if self.0 >= 31 {
             0
         } else {
-            (1u32 << (32 - self.0)) - 2
+            self.get_host_mask() - 1
         }
     }
 }
@@ -148,6 +155,14 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_default_route_prefix() {
+        let prefix = Prefix::new(0).unwrap();
+        assert_eq!(prefix.get_network_mask(), 0);
+        assert_eq!(prefix.to_subnet_mask().as_string(), "0.0.0.0");
+        assert_eq!(prefix.get_max_hosts(), u32::MAX - 1);
+    }
+
     #[test]
     fn test_from_str_valid() {
         let prefix: Result<Prefix, _> = "/24".parse();
@@ -186,6 +201,12 @@ mod tests {
         assert_eq!(prefix.get_host_mask(), 0x000000FF);
     }
 
+    #[test]
+    fn test_wildcard_mask() {
+        let prefix = Prefix::new(20).unwrap();
+        assert_eq!(prefix.to_wildcard_mask().as_string(), "0.0.15.255");
+    }
+
     #[test]
     fn test_from_subnet_mask() {
         let mask = SubnetMask::new("255.255.255.0".to_string());
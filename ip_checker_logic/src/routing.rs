@@ -0,0 +1,214 @@
+use std::net::Ipv4Addr;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::ip_address::IpAddress;
+use crate::network_address::NetworkAddress;
+use crate::prefix::Prefix;
+use crate::trie::PrefixTrie;
+use crate::IpCalculator;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub network: NetworkAddress,
+    pub prefix: Prefix,
+    pub next_hop: IpAddress,
+}
+
+impl Route {
+    pub fn new(network: NetworkAddress, prefix: Prefix, next_hop: IpAddress) -> Self {
+        Self {
+            network,
+            prefix,
+            next_hop,
+        }
+    }
+
+    pub fn contains(&self, address: &IpAddress) -> bool {
+        address.to_u32() & self.prefix.get_network_mask() == self.network.to_u32()
+    }
+
+    pub fn cidr(&self) -> String {
+        format!("{}{}", self.network, self.prefix)
+    }
+}
+
+// Routes ordered by address, a lookup picks the most specific route containing the address
+#[derive(Debug, Clone, Default)]
+pub struct RoutingTable {
+    routes: PrefixTrie<Route>,
+}
+
+impl RoutingTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // A route for the same network and prefix replaces the old one
+    pub fn insert(&mut self, route: Route) {
+        let (network, prefix) = (route.network.clone(), route.prefix.clone());
+        self.routes.insert(&network, &prefix, route);
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    pub fn routes(&self) -> impl Iterator<Item = &Route> {
+        self.routes.values()
+    }
+
+    pub fn lookup(&self, address: &IpAddress) -> Option<&Route> {
+        self.routes.longest_match_value(address)
+    }
+}
+
+// Which next hop do the destinations take?
+#[derive(Debug, Clone)]
+pub struct RoutingExercise {
+    pub table: RoutingTable,
+    pub destinations: Vec<IpAddress>,
+}
+
+impl RoutingExercise {
+    // Next hop of a destination, "drop" when no route matches
+    pub fn answer(&self, index: usize) -> String {
+        self.table
+            .lookup(&self.destinations[index])
+            .map_or("drop".to_string(), |route| route.next_hop.as_string())
+    }
+
+    pub fn check(&self, index: usize, answer: &str) -> bool {
+        answer.trim().eq_ignore_ascii_case(&self.answer(index))
+    }
+}
+
+fn address(value: u32) -> IpAddress {
+    IpAddress::new(Ipv4Addr::from(value).to_string())
+}
+
+fn next_hop(index: u8) -> IpAddress {
+    // Documentation range, so the next hops never clash with the routes
+    address(u32::from(Ipv4Addr::new(192, 0, 2, index)))
+}
+
+// Adds a route to a random network inside `base`, returns the network
+fn add_route<R: Rng>(rng: &mut R, table: &mut RoutingTable, base: u32, prefix: u8) -> u32 {
+    let prefix = Prefix::new(prefix).unwrap();
+    let hop = next_hop(table.len() as u8 + 1);
+    let network = (base | (rng.gen::<u32>() & prefix.get_host_mask())) & prefix.get_network_mask();
+    table.insert(Route::new(
+        NetworkAddress::from_u32(network, prefix.value()).unwrap(),
+        prefix,
+        hop,
+    ));
+    network
+}
+
+impl IpCalculator {
+    // A summary route with nested more specific routes, plus a default route
+    pub fn random_routing_exercise(&mut self) -> RoutingExercise {
+        let model = self.random_model();
+        let ip = model.ip.to_u32();
+        let mut table = RoutingTable::new();
+
+        let summary_prefix = self.rng.gen_range(12..=16);
+        let summary = add_route(&mut self.rng, &mut table, ip, summary_prefix);
+        let child_prefix = summary_prefix + self.rng.gen_range(2..=4);
+        let child = add_route(&mut self.rng, &mut table, summary, child_prefix);
+        add_route(&mut self.rng, &mut table, summary, child_prefix);
+        let nested_prefix = child_prefix + self.rng.gen_range(2..=6);
+        add_route(&mut self.rng, &mut table, child, nested_prefix);
+        table.insert(Route::new(
+            NetworkAddress::from_u32(0, 0).unwrap(),
+            Prefix::new(0).unwrap(),
+            address(u32::from(Ipv4Addr::new(192, 0, 2, 254))),
+        ));
+
+        // One random destination inside every route, it may still hit a more specific one
+        let mut destinations: Vec<IpAddress> = table
+            .routes()
+            .map(|route| {
+                let host = self.rng.gen::<u32>() & route.prefix.get_host_mask();
+                address(route.network.to_u32() | host)
+            })
+            .collect();
+        destinations.shuffle(&mut self.rng);
+
+        RoutingExercise {
+            table,
+            destinations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(cidr: &str, hop: &str) -> Route {
+        let (network, prefix) = cidr.split_once('/').unwrap();
+        let prefix = Prefix::new(prefix.parse().unwrap()).unwrap();
+        Route::new(
+            NetworkAddress::try_new(network.to_string(), prefix.value()).unwrap(),
+            prefix,
+            IpAddress::new(hop.to_string()),
+        )
+    }
+
+    fn lookup(table: &RoutingTable, ip: &str) -> Option<String> {
+        table
+            .lookup(&IpAddress::new(ip.to_string()))
+            .map(|route| route.next_hop.as_string())
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let mut table = RoutingTable::new();
+        table.insert(route("0.0.0.0/0", "192.0.2.254"));
+        table.insert(route("10.0.0.0/8", "192.0.2.1"));
+        table.insert(route("10.1.0.0/16", "192.0.2.2"));
+        table.insert(route("10.1.2.0/24", "192.0.2.3"));
+
+        assert_eq!(lookup(&table, "10.1.2.3").unwrap(), "192.0.2.3");
+        assert_eq!(lookup(&table, "10.1.3.3").unwrap(), "192.0.2.2");
+        assert_eq!(lookup(&table, "10.2.0.1").unwrap(), "192.0.2.1");
+        assert_eq!(lookup(&table, "8.8.8.8").unwrap(), "192.0.2.254");
+    }
+
+    #[test]
+    fn test_no_route() {
+        let mut table = RoutingTable::new();
+        table.insert(route("10.0.0.0/8", "192.0.2.1"));
+        assert!(lookup(&table, "11.0.0.1").is_none());
+    }
+
+    #[test]
+    fn test_insert_replaces_route() {
+        let mut table = RoutingTable::new();
+        table.insert(route("10.0.0.0/8", "192.0.2.1"));
+        table.insert(route("10.0.0.0/8", "192.0.2.9"));
+        assert_eq!(table.len(), 1);
+        assert_eq!(lookup(&table, "10.0.0.1").unwrap(), "192.0.2.9");
+    }
+
+    #[test]
+    fn test_random_exercise() {
+        let mut calculator = IpCalculator::with_seed(8);
+        for _ in 0..50 {
+            let exercise = calculator.random_routing_exercise();
+            assert!(exercise.table.len() >= 4);
+            assert_eq!(exercise.destinations.len(), exercise.table.len());
+            for index in 0..exercise.destinations.len() {
+                assert!(exercise.check(index, &exercise.answer(index)));
+            }
+            // Every destination has a route thanks to the default route
+            assert!((0..exercise.destinations.len()).all(|i| exercise.answer(i) != "drop"));
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::net::Ipv4Addr;
+
+use thiserror::Error;
+
+use crate::cidr::Cidr;
+use crate::ip_address::IpAddress;
+use crate::IpModel;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SplitError {
+    #[error(
+        "Cannot split {network} into /{prefix} subnets, the prefix must be between {min} and 32"
+    )]
+    InvalidPrefix {
+        network: String,
+        prefix: u8,
+        min: u8,
+    },
+
+    #[error("Cannot split {network} into {count} subnets")]
+    InvalidCount { network: String, count: u64 },
+}
+
+// Equal sized subnets of a network in address order, created on demand
+#[derive(Debug, Clone)]
+pub struct Subnets {
+    base: u64,
+    prefix: u8,
+    next: u64,
+    end: u64,
+}
+
+impl Subnets {
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    fn subnet(&self, index: u64) -> Cidr {
+        let size = 1u64 << (32 - self.prefix);
+        Cidr::from_u32((self.base + index * size) as u32, self.prefix).unwrap()
+    }
+}
+
+impl Iterator for Subnets {
+    type Item = Cidr;
+
+    fn next(&mut self) -> Option<Cidr> {
+        if self.next >= self.end {
+            return None;
+        }
+        self.next += 1;
+        Some(self.subnet(self.next - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = (self.end - self.next) as usize;
+        (left, Some(left))
+    }
+
+    // Jumps straight to the subnet instead of creating the ones before it
+    fn nth(&mut self, n: usize) -> Option<Cidr> {
+        self.next = self.next.saturating_add(n as u64).min(self.end);
+        self.next()
+    }
+}
+
+impl DoubleEndedIterator for Subnets {
+    fn next_back(&mut self) -> Option<Cidr> {
+        if self.next >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(self.subnet(self.end))
+    }
+}
+
+impl ExactSizeIterator for Subnets {}
+
+// Usable host addresses of a network in address order
+#[derive(Debug, Clone)]
+pub struct Hosts {
+    next: u64,
+    end: u64,
+}
+
+impl Iterator for Hosts {
+    type Item = IpAddress;
+
+    fn next(&mut self) -> Option<IpAddress> {
+        if self.next > self.end {
+            return None;
+        }
+        self.next += 1;
+        Some(IpAddress::new(
+            Ipv4Addr::from((self.next - 1) as u32).to_string(),
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = (self.end + 1 - self.next.min(self.end + 1)) as usize;
+        (left, Some(left))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<IpAddress> {
+        self.next = self.next.saturating_add(n as u64);
+        self.next()
+    }
+}
+
+impl ExactSizeIterator for Hosts {}
+
+impl Cidr {
+    // All subnets with the given prefix
+    pub fn split(&self, prefix: u8) -> Result<Subnets, SplitError> {
+        let min = self.prefix.value();
+        if prefix < min || prefix > 32 {
+            return Err(SplitError::InvalidPrefix {
+                network: self.to_string(),
+                prefix,
+                min,
+            });
+        }
+        Ok(Subnets {
+            base: self.first() as u64,
+            prefix,
+            next: 0,
+            end: 1u64 << (prefix - min),
+        })
+    }
+
+    // The largest equal subnets so there are at least `count` of them
+    pub fn split_into(&self, count: u64) -> Result<Subnets, SplitError> {
+        let invalid = || SplitError::InvalidCount {
+            network: self.to_string(),
+            count,
+        };
+        // Counts above 2^63 have no power of two in a u64
+        let borrowed = Some(count)
+            .filter(|&count| count > 0)
+            .and_then(u64::checked_next_power_of_two)
+            .ok_or_else(invalid)?
+            .trailing_zeros() as u8;
+        let prefix = self.prefix.value() + borrowed;
+        if prefix > 32 {
+            return Err(invalid());
+        }
+        self.split(prefix)
+    }
+
+    // Subnet `index` counting from 0
+    pub fn nth_subnet(&self, prefix: u8, index: u64) -> Result<Option<Cidr>, SplitError> {
+        let subnets = self.split(prefix)?;
+        Ok((index < subnets.end).then(|| subnets.subnet(index)))
+    }
+
+    // Without network and broadcast address, except for /31 and /32 where all addresses are used
+    pub fn hosts(&self) -> Hosts {
+        let (first, last) = (self.first() as u64, self.last() as u64);
+        if self.prefix.value() >= 31 {
+            Hosts {
+                next: first,
+                end: last,
+            }
+        } else {
+            Hosts {
+                next: first + 1,
+                end: last - 1,
+            }
+        }
+    }
+
+    // Whether `address` is one of the usable hosts
+    pub fn contains_host(&self, address: &IpAddress) -> bool {
+        let hosts = self.hosts();
+        (hosts.next..=hosts.end).contains(&(address.to_u32() as u64))
+    }
+
+    // Host `index` counting from 0, so index 0 is the first usable host
+    pub fn nth_host(&self, index: u64) -> Option<IpAddress> {
+        let hosts = self.hosts();
+        let address = hosts.next.checked_add(index)?;
+        (address <= hosts.end).then(|| IpAddress::new(Ipv4Addr::from(address as u32).to_string()))
+    }
+}
+
+impl IpModel {
+    // The network the IP address is in
+    pub fn network(&self) -> Cidr {
+        Cidr::new(self.network_address.clone(), self.prefix.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cidr(s: &str) -> Cidr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_split_by_prefix() {
+        let subnets: Vec<String> = cidr("192.168.40.0/22")
+            .split(24)
+            .unwrap()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            subnets,
+            [
+                "192.168.40.0/24",
+                "192.168.41.0/24",
+                "192.168.42.0/24",
+                "192.168.43.0/24"
+            ]
+        );
+        assert_eq!(
+            cidr("10.0.0.0/8").split(7).unwrap_err(),
+            SplitError::InvalidPrefix {
+                network: "10.0.0.0/8".to_string(),
+                prefix: 7,
+                min: 8
+            }
+        );
+    }
+
+    #[test]
+    fn test_split_by_count() {
+        let subnets = cidr("10.0.0.0/24").split_into(5).unwrap();
+        assert_eq!(subnets.prefix(), 27);
+        assert_eq!(subnets.len(), 8);
+        assert!(cidr("10.0.0.0/30").split_into(8).is_err());
+        assert!(cidr("10.0.0.0/24").split_into(0).is_err());
+        assert!(cidr("0.0.0.0/0").split_into(u64::MAX).is_err());
+        assert_eq!(cidr("10.0.0.0/24").split_into(1).unwrap().prefix(), 24);
+    }
+
+    #[test]
+    fn test_lazy_and_nth() {
+        // 2^24 subnets, only the requested ones are created
+        let mut subnets = cidr("10.0.0.0/8").split(32).unwrap();
+        assert_eq!(subnets.len(), 1 << 24);
+        assert_eq!(subnets.nth(300).unwrap().to_string(), "10.0.1.44/32");
+        assert_eq!(
+            subnets.next_back().unwrap().to_string(),
+            "10.255.255.255/32"
+        );
+
+        let network = cidr("192.168.40.0/22");
+        assert_eq!(
+            network.nth_subnet(26, 4).unwrap().unwrap().to_string(),
+            "192.168.41.0/26"
+        );
+        assert!(network.nth_subnet(26, 16).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_hosts() {
+        let network = cidr("10.8.0.0/29");
+        let hosts: Vec<String> = network.hosts().map(|h| h.as_string()).collect();
+        assert_eq!(hosts.len(), 6);
+        assert_eq!(hosts[0], "10.8.0.1");
+        assert_eq!(hosts[5], "10.8.0.6");
+        assert_eq!(cidr("10.0.0.0/31").hosts().len(), 2);
+        assert_eq!(cidr("10.0.0.1/32").hosts().len(), 1);
+
+        let big = cidr("10.8.0.0/13");
+        assert_eq!(big.nth_host(99).unwrap().as_string(), "10.8.0.100");
+        assert_eq!(big.hosts().nth(99).unwrap().as_string(), "10.8.0.100");
+        assert!(network.nth_host(6).is_none());
+        assert!(network.contains_host(&IpAddress::new("10.8.0.6".to_string())));
+        assert!(!network.contains_host(&IpAddress::new("10.8.0.7".to_string())));
+        assert!(cidr("10.0.0.0/31").contains_host(&IpAddress::new("10.0.0.0".to_string())));
+    }
+
+    #[test]
+    fn test_model_network() {
+        let model = IpModel::from_cidr("172.19.77.5/21").unwrap();
+        assert_eq!(model.network().to_string(), "172.19.72.0/21");
+    }
+}
@@ -0,0 +1,525 @@
+use rand::Rng;
+use thiserror::Error;
+
+use crate::cidr::Cidr;
+use crate::conflicts::Conflicts;
+use crate::ip_address::IpAddress;
+use crate::IpCalculator;
+
+// A router interface connected to a segment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    pub router: usize,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SegmentKind {
+    // A switch with `hosts` end devices behind it
+    Lan { switch: String, hosts: u32 },
+    // A point-to-point link between two routers
+    Link,
+}
+
+// Everything that needs its own subnet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub name: String,
+    pub kind: SegmentKind,
+    pub interfaces: Vec<Interface>,
+}
+
+impl Segment {
+    // Hosts plus router interfaces
+    pub fn required_addresses(&self) -> u64 {
+        let hosts = match self.kind {
+            SegmentKind::Lan { hosts, .. } => hosts as u64,
+            SegmentKind::Link => 0,
+        };
+        hosts + self.interfaces.len() as u64
+    }
+
+    // Longest prefix with enough hosts when network and broadcast address are not used
+    pub fn prefix(&self) -> u8 {
+        let mut prefix = 30;
+        while (1u64 << (32 - prefix)) - 2 < self.required_addresses() {
+            prefix -= 1;
+        }
+        prefix
+    }
+}
+
+// What the student entered for one segment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SegmentAnswer {
+    pub subnet: String,
+    // One address per interface of the segment, in the same order
+    pub interfaces: Vec<String>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum AddressingError {
+    #[error("{segment}: '{value}' is not a network like 10.0.0.0/24")]
+    InvalidSubnet { segment: String, value: String },
+
+    #[error("{segment}: {subnet} is not inside {block}")]
+    OutsideBlock {
+        segment: String,
+        subnet: String,
+        block: String,
+    },
+
+    #[error("{segment}: {subnet} has {available} usable addresses, {required} are needed")]
+    TooSmall {
+        segment: String,
+        subnet: String,
+        available: u64,
+        required: u64,
+    },
+
+    #[error("{segment}: {subnet} is larger than needed, a /{prefix} is enough")]
+    Oversized {
+        segment: String,
+        subnet: String,
+        prefix: u8,
+    },
+
+    #[error("{segment}: {subnet} overlaps {other} of {other_segment}")]
+    Overlap {
+        segment: String,
+        subnet: String,
+        other: String,
+        other_segment: String,
+    },
+
+    #[error("{interface}: '{value}' is not an IP address")]
+    InvalidInterface {
+        segment: String,
+        interface: String,
+        value: String,
+    },
+
+    #[error("{interface}: {address} is not a usable address of {subnet}")]
+    InterfaceOutsideSubnet {
+        segment: String,
+        interface: String,
+        address: String,
+        subnet: String,
+    },
+
+    #[error("{interface}: {address} is already used by {other}")]
+    DuplicateAddress {
+        segment: String,
+        interface: String,
+        address: String,
+        other: String,
+    },
+}
+
+impl AddressingError {
+    pub fn segment(&self) -> &str {
+        match self {
+            AddressingError::InvalidSubnet { segment, .. }
+            | AddressingError::OutsideBlock { segment, .. }
+            | AddressingError::TooSmall { segment, .. }
+            | AddressingError::Oversized { segment, .. }
+            | AddressingError::Overlap { segment, .. }
+            | AddressingError::InvalidInterface { segment, .. }
+            | AddressingError::InterfaceOutsideSubnet { segment, .. }
+            | AddressingError::DuplicateAddress { segment, .. } => segment,
+        }
+    }
+
+    // The interface for address errors, None if the subnet itself is wrong
+    pub fn interface(&self) -> Option<&str> {
+        match self {
+            AddressingError::InvalidInterface { interface, .. }
+            | AddressingError::InterfaceOutsideSubnet { interface, .. }
+            | AddressingError::DuplicateAddress { interface, .. } => Some(interface),
+            _ => None,
+        }
+    }
+}
+
+// Routers connected by point-to-point links, each with LANs behind it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topology {
+    // The address space all subnets have to come from
+    pub block: Cidr,
+    pub routers: Vec<String>,
+    pub segments: Vec<Segment>,
+}
+
+impl Topology {
+    pub fn text(&self) -> String {
+        format!(
+            "Assign a subnet from {} to every LAN and link, using the smallest possible size, and an address to every router interface.",
+            self.block
+        )
+    }
+
+    // Full name like "R1 G0/1"
+    pub fn interface_name(&self, interface: &Interface) -> String {
+        format!("{} {}", self.routers[interface.router], interface.name)
+    }
+
+    pub fn validate(&self, answers: &[SegmentAnswer]) -> Vec<AddressingError> {
+        let mut errors = Vec::new();
+        let mut conflicts = Conflicts::new();
+        let empty = SegmentAnswer::default();
+
+        for (index, segment) in self.segments.iter().enumerate() {
+            let answer = answers.get(index).unwrap_or(&empty);
+            let name = segment.name.clone();
+            let subnet: Cidr = match answer.subnet.parse() {
+                Ok(subnet) => subnet,
+                Err(_) => {
+                    errors.push(AddressingError::InvalidSubnet {
+                        segment: name,
+                        value: answer.subnet.trim().to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            if !self.block.contains_network(&subnet) {
+                errors.push(AddressingError::OutsideBlock {
+                    segment: name.clone(),
+                    subnet: subnet.to_string(),
+                    block: self.block.to_string(),
+                });
+            }
+            let available = subnet.hosts().len() as u64;
+            if available < segment.required_addresses() {
+                errors.push(AddressingError::TooSmall {
+                    segment: name.clone(),
+                    subnet: subnet.to_string(),
+                    available,
+                    required: segment.required_addresses(),
+                });
+            } else if subnet.prefix.value() < segment.prefix() {
+                errors.push(AddressingError::Oversized {
+                    segment: name.clone(),
+                    subnet: subnet.to_string(),
+                    prefix: segment.prefix(),
+                });
+            }
+            // Only the first segment in the way is named
+            let overlapping = conflicts.add_network(&subnet, (index, subnet.to_string()));
+            if let Some((other, other_subnet)) = overlapping.into_iter().min() {
+                errors.push(AddressingError::Overlap {
+                    segment: name.clone(),
+                    subnet: subnet.to_string(),
+                    other: other_subnet,
+                    other_segment: self.segments[other].name.clone(),
+                });
+            }
+
+            for (position, interface) in segment.interfaces.iter().enumerate() {
+                let interface = self.interface_name(interface);
+                let value = answer
+                    .interfaces
+                    .get(position)
+                    .map_or("", |value| value.trim());
+                let Ok(address) = value.parse::<IpAddress>() else {
+                    errors.push(AddressingError::InvalidInterface {
+                        segment: name.clone(),
+                        interface,
+                        value: value.to_string(),
+                    });
+                    continue;
+                };
+                if !subnet.contains_host(&address) {
+                    errors.push(AddressingError::InterfaceOutsideSubnet {
+                        segment: name.clone(),
+                        interface: interface.clone(),
+                        address: address.as_string(),
+                        subnet: subnet.to_string(),
+                    });
+                }
+                if let Some(other) = conflicts.add_address(&address, interface.clone()) {
+                    errors.push(AddressingError::DuplicateAddress {
+                        segment: name.clone(),
+                        interface,
+                        address: address.as_string(),
+                        other,
+                    });
+                }
+            }
+        }
+        errors
+    }
+
+    // Largest segments first from the start of the block, routers take the first hosts
+    pub fn solution(&self) -> Vec<SegmentAnswer> {
+        let mut order: Vec<usize> = (0..self.segments.len()).collect();
+        order.sort_by_key(|&index| self.segments[index].prefix());
+
+        let mut answers = vec![SegmentAnswer::default(); self.segments.len()];
+        let mut next = self.block.first() as u64;
+        for index in order {
+            let segment = &self.segments[index];
+            let subnet = Cidr::from_u32(next as u32, segment.prefix()).unwrap();
+            next += subnet.size();
+            answers[index] = SegmentAnswer {
+                subnet: subnet.to_string(),
+                interfaces: (0..segment.interfaces.len() as u64)
+                    .map(|host| subnet.nth_host(host).unwrap().as_string())
+                    .collect(),
+            };
+        }
+        answers
+    }
+
+    // Routers in a row with their switches below, coordinates in grid units
+    pub fn layout(&self) -> Layout {
+        let mut nodes: Vec<Node> = self
+            .routers
+            .iter()
+            .enumerate()
+            .map(|(index, router)| Node {
+                label: router.clone(),
+                kind: NodeKind::Router,
+                x: index as f32 * 2.0 + 1.0,
+                y: 0.5,
+            })
+            .collect();
+        let mut edges = Vec::new();
+        // LANs of a router are spread out centered below it
+        let mut lan_counts = vec![0usize; self.routers.len()];
+        for segment in &self.segments {
+            if let SegmentKind::Lan { .. } = segment.kind {
+                lan_counts[segment.interfaces[0].router] += 1;
+            }
+        }
+        let mut lans = vec![0usize; self.routers.len()];
+
+        for (index, segment) in self.segments.iter().enumerate() {
+            match &segment.kind {
+                SegmentKind::Lan { switch, hosts } => {
+                    let router = segment.interfaces[0].router;
+                    let x = nodes[router].x + lans[router] as f32
+                        - (lan_counts[router] - 1) as f32 / 2.0;
+                    lans[router] += 1;
+                    nodes.push(Node {
+                        label: format!("{}\n{} hosts", switch, hosts),
+                        kind: NodeKind::Switch,
+                        x,
+                        y: 2.0,
+                    });
+                    edges.push(Edge {
+                        from: router,
+                        to: nodes.len() - 1,
+                        segment: index,
+                    });
+                }
+                SegmentKind::Link => edges.push(Edge {
+                    from: segment.interfaces[0].router,
+                    to: segment.interfaces[1].router,
+                    segment: index,
+                }),
+            }
+        }
+
+        Layout {
+            nodes,
+            edges,
+            width: self.routers.len() as f32 * 2.0,
+            height: 2.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Router,
+    Switch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub label: String,
+    pub kind: NodeKind,
+    pub x: f32,
+    pub y: f32,
+}
+
+// A line between two nodes, `segment` is the index into `Topology::segments`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub segment: usize,
+}
+
+// Positions for drawing a topology
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub width: f32,
+    pub height: f32,
+}
+
+// Private ranges a topology block is taken from
+const PRIVATE: [(u32, u8); 3] = [(0x0A00_0000, 8), (0xAC10_0000, 12), (0xC0A8_0000, 16)];
+
+impl IpCalculator {
+    // Two or three routers in a chain, each with one or two LANs
+    pub fn random_topology(&mut self) -> Topology {
+        let router_count = self.rng.gen_range(2..=3);
+        let routers: Vec<String> = (1..=router_count).map(|n| format!("R{}", n)).collect();
+        let mut ports = vec![0; router_count];
+        let mut interface = |router: usize| {
+            ports[router] += 1;
+            Interface {
+                router,
+                name: format!("G0/{}", ports[router] - 1),
+            }
+        };
+
+        let mut segments = Vec::new();
+        for router in 0..router_count {
+            for _ in 0..self.rng.gen_range(1..=2) {
+                let number = segments.len() + 1;
+                segments.push(Segment {
+                    name: format!("LAN{}", number),
+                    kind: SegmentKind::Lan {
+                        switch: format!("SW{}", number),
+                        hosts: self.rng.gen_range(5..=250),
+                    },
+                    interfaces: vec![interface(router)],
+                });
+            }
+        }
+        for router in 1..router_count {
+            segments.push(Segment {
+                name: format!("{}-{}", routers[router - 1], routers[router]),
+                kind: SegmentKind::Link,
+                interfaces: vec![interface(router - 1), interface(router)],
+            });
+        }
+
+        // Power of two sized subnets always fit into the next power of two of their sum
+        let total: u64 = segments.iter().map(|s| 1u64 << (32 - s.prefix())).sum();
+        let prefix = 32 - total.next_power_of_two().trailing_zeros() as u8;
+        let (base, private) = PRIVATE[self.rng.gen_range(0..PRIVATE.len())];
+        let offset = self.rng.gen_range(0..1u32 << (prefix - private)) << (32 - prefix);
+
+        Topology {
+            block: Cidr::from_u32(base | offset, prefix).unwrap(),
+            routers,
+            segments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // R1 with a 50 host LAN, R2 with a 10 host LAN, and the link between them
+    fn topology() -> Topology {
+        let lan = |name: &str, switch: &str, hosts, router| Segment {
+            name: name.to_string(),
+            kind: SegmentKind::Lan {
+                switch: switch.to_string(),
+                hosts,
+            },
+            interfaces: vec![Interface {
+                router,
+                name: "G0/0".to_string(),
+            }],
+        };
+        Topology {
+            block: "192.168.8.0/24".parse().unwrap(),
+            routers: vec!["R1".to_string(), "R2".to_string()],
+            segments: vec![
+                lan("LAN1", "SW1", 50, 0),
+                lan("LAN2", "SW2", 10, 1),
+                Segment {
+                    name: "R1-R2".to_string(),
+                    kind: SegmentKind::Link,
+                    interfaces: vec![
+                        Interface {
+                            router: 0,
+                            name: "G0/1".to_string(),
+                        },
+                        Interface {
+                            router: 1,
+                            name: "G0/1".to_string(),
+                        },
+                    ],
+                },
+            ],
+        }
+    }
+
+    fn answer(subnet: &str, interfaces: &[&str]) -> SegmentAnswer {
+        SegmentAnswer {
+            subnet: subnet.to_string(),
+            interfaces: interfaces.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_sizes() {
+        let prefixes: Vec<u8> = topology().segments.iter().map(Segment::prefix).collect();
+        assert_eq!(prefixes, [26, 28, 30]);
+    }
+
+    #[test]
+    fn test_solution() {
+        let topology = topology();
+        let solution = topology.solution();
+        assert_eq!(
+            solution,
+            [
+                answer("192.168.8.0/26", &["192.168.8.1"]),
+                answer("192.168.8.64/28", &["192.168.8.65"]),
+                answer("192.168.8.80/30", &["192.168.8.81", "192.168.8.82"]),
+            ]
+        );
+        assert!(topology.validate(&solution).is_empty());
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let topology = topology();
+        let errors = topology.validate(&[
+            answer("192.168.8.0/25", &["192.168.8.0"]),
+            answer("192.168.8.64/29", &["192.168.8.65"]),
+            answer("192.168.9.0/31", &["192.168.9.0", "192.168.9.0"]),
+        ]);
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "LAN1: 192.168.8.0/25 is larger than needed, a /26 is enough",
+                "R1 G0/0: 192.168.8.0 is not a usable address of 192.168.8.0/25",
+                "LAN2: 192.168.8.64/29 has 6 usable addresses, 11 are needed",
+                "LAN2: 192.168.8.64/29 overlaps 192.168.8.0/25 of LAN1",
+                "R1-R2: 192.168.9.0/31 is not inside 192.168.8.0/24",
+                "R2 G0/1: 192.168.9.0 is already used by R1 G0/1",
+            ]
+        );
+        assert_eq!(errors[1].segment(), "LAN1");
+        assert_eq!(errors[1].interface(), Some("R1 G0/0"));
+        assert!(matches!(
+            topology.validate(&[])[0],
+            AddressingError::InvalidSubnet { .. }
+        ));
+    }
+
+    #[test]
+    fn test_random_topologies_are_solvable() {
+        let mut calculator = IpCalculator::with_seed(44);
+        for _ in 0..200 {
+            let topology = calculator.random_topology();
+            let errors = topology.validate(&topology.solution());
+            assert!(errors.is_empty(), "{:?}", errors);
+            let layout = topology.layout();
+            assert_eq!(layout.edges.len(), topology.segments.len());
+        }
+    }
+}
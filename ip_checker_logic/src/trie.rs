@@ -0,0 +1,487 @@
+use crate::ip_address::IpAddress;
+use crate::network_address::NetworkAddress;
+use crate::prefix::Prefix;
+
+// Path compressed binary trie (Patricia trie) of networks. Every node stores
+// the bits shared by its whole subtree, so a lookup visits at most 33 nodes
+// no matter how many prefixes are stored
+#[derive(Debug, Clone)]
+pub struct PrefixTrie<T> {
+    root: Option<Box<Node<T>>>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    key: u32,
+    prefix: u8,
+    value: Option<T>,
+    children: [Option<Box<Node<T>>>; 2],
+}
+
+impl<T> Node<T> {
+    fn new(key: u32, prefix: u8, value: Option<T>) -> Box<Self> {
+        Box::new(Self {
+            key,
+            prefix,
+            value,
+            children: [None, None],
+        })
+    }
+
+    fn contains(&self, key: u32) -> bool {
+        (self.key ^ key) & mask(self.prefix) == 0
+    }
+}
+
+fn mask(prefix: u8) -> u32 {
+    (!0u32).checked_shl(32 - prefix as u32).unwrap_or(0)
+}
+
+// The bit after the first `position` bits, decides which child to follow
+fn bit(key: u32, position: u8) -> usize {
+    ((key >> (31 - position)) & 1) as usize
+}
+
+// Number of leading bits two prefixes have in common
+fn common_length(a: u32, a_prefix: u8, b: u32, b_prefix: u8) -> u8 {
+    ((a ^ b).leading_zeros() as u8).min(a_prefix).min(b_prefix)
+}
+
+fn key_of(network: &NetworkAddress, prefix: &Prefix) -> u32 {
+    network.to_u32() & prefix.get_network_mask()
+}
+
+fn entry<T>(node: &Node<T>) -> Option<(NetworkAddress, Prefix, &T)> {
+    let value = node.value.as_ref()?;
+    Some((
+        NetworkAddress::from_u32(node.key, node.prefix).unwrap(),
+        Prefix::new(node.prefix).unwrap(),
+        value,
+    ))
+}
+
+impl<T> Default for PrefixTrie<T> {
+    fn default() -> Self {
+        Self { root: None, len: 0 }
+    }
+}
+
+impl<T> PrefixTrie<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Returns the old value if the network was already stored
+    pub fn insert(&mut self, network: &NetworkAddress, prefix: &Prefix, value: T) -> Option<T> {
+        let old = Self::insert_at(
+            &mut self.root,
+            key_of(network, prefix),
+            prefix.value(),
+            value,
+        );
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    fn insert_at(slot: &mut Option<Box<Node<T>>>, key: u32, prefix: u8, value: T) -> Option<T> {
+        let Some(node) = slot else {
+            *slot = Some(Node::new(key, prefix, Some(value)));
+            return None;
+        };
+
+        let common = common_length(node.key, node.prefix, key, prefix);
+        if common == node.prefix && common == prefix {
+            return node.value.replace(value);
+        }
+        if common == node.prefix {
+            return Self::insert_at(&mut node.children[bit(key, common)], key, prefix, value);
+        }
+
+        // The new network splits the path above this node
+        let old = slot.take().unwrap();
+        let mut parent = if common == prefix {
+            Node::new(key, prefix, Some(value))
+        } else {
+            let mut glue = Node::new(key & mask(common), common, None);
+            glue.children[bit(key, common)] = Some(Node::new(key, prefix, Some(value)));
+            glue
+        };
+        let side = bit(old.key, common);
+        parent.children[side] = Some(old);
+        *slot = Some(parent);
+        None
+    }
+
+    pub fn remove(&mut self, network: &NetworkAddress, prefix: &Prefix) -> Option<T> {
+        let removed = Self::remove_at(&mut self.root, key_of(network, prefix), prefix.value());
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn remove_at(slot: &mut Option<Box<Node<T>>>, key: u32, prefix: u8) -> Option<T> {
+        let node = slot.as_mut()?;
+        if !node.contains(key) || node.prefix > prefix {
+            return None;
+        }
+        let removed = if node.prefix == prefix {
+            node.value.take()
+        } else {
+            Self::remove_at(&mut node.children[bit(key, node.prefix)], key, prefix)
+        };
+
+        // Nodes without a value are only needed to join two subtrees
+        if node.value.is_none() {
+            match node.children.iter().filter(|c| c.is_some()).count() {
+                0 => *slot = None,
+                1 => {
+                    let [zero, one] = &mut node.children;
+                    *slot = zero.take().or(one.take());
+                }
+                _ => (),
+            }
+        }
+        removed
+    }
+
+    fn find(&self, key: u32, prefix: u8) -> Option<&Node<T>> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            if !node.contains(key) || node.prefix > prefix {
+                return None;
+            }
+            if node.prefix == prefix {
+                return Some(node);
+            }
+            current = node.children[bit(key, node.prefix)].as_deref();
+        }
+        None
+    }
+
+    // Exact match
+    pub fn get(&self, network: &NetworkAddress, prefix: &Prefix) -> Option<&T> {
+        self.find(key_of(network, prefix), prefix.value())?
+            .value
+            .as_ref()
+    }
+
+    pub fn get_mut(&mut self, network: &NetworkAddress, prefix: &Prefix) -> Option<&mut T> {
+        let (key, prefix) = (key_of(network, prefix), prefix.value());
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            if !node.contains(key) || node.prefix > prefix {
+                return None;
+            }
+            if node.prefix == prefix {
+                return node.value.as_mut();
+            }
+            current = node.children[bit(key, node.prefix)].as_deref_mut();
+        }
+        None
+    }
+
+    pub fn contains(&self, network: &NetworkAddress, prefix: &Prefix) -> bool {
+        self.get(network, prefix).is_some()
+    }
+
+    // The most specific stored network containing the address
+    pub fn longest_match(&self, address: &IpAddress) -> Option<(NetworkAddress, Prefix, &T)> {
+        self.longest_match_node(address.to_u32()).and_then(entry)
+    }
+
+    // Value of the most specific network, without building the network types
+    pub fn longest_match_value(&self, address: &IpAddress) -> Option<&T> {
+        self.longest_match_node(address.to_u32())?.value.as_ref()
+    }
+
+    fn longest_match_node(&self, address: u32) -> Option<&Node<T>> {
+        let mut best = None;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            if !node.contains(address) {
+                break;
+            }
+            if node.value.is_some() {
+                best = Some(node);
+            }
+            if node.prefix == 32 {
+                break;
+            }
+            current = node.children[bit(address, node.prefix)].as_deref();
+        }
+        best
+    }
+
+    // Stored networks containing the network or inside it, outermost first
+    pub fn overlapping(
+        &self,
+        network: &NetworkAddress,
+        prefix: &Prefix,
+    ) -> Vec<(NetworkAddress, Prefix, &T)> {
+        let (key, prefix) = (key_of(network, prefix), prefix.value());
+        let mut found = Vec::new();
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            if node.prefix >= prefix {
+                // The whole subtree is inside the network, or none of it
+                if (node.key ^ key) & mask(prefix) == 0 {
+                    found.extend(Iter { stack: vec![node] });
+                }
+                break;
+            }
+            if !node.contains(key) {
+                break;
+            }
+            found.extend(entry(node));
+            current = node.children[bit(key, node.prefix)].as_deref();
+        }
+        found
+    }
+
+    // Entries ordered by address, a network comes before the networks inside it
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stack: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, _, value)| value)
+    }
+}
+
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (NetworkAddress, Prefix, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Pre-order walk, the one child is pushed first so the zero child comes out first
+        while let Some(node) = self.stack.pop() {
+            self.stack
+                .extend(node.children.iter().rev().filter_map(|c| c.as_deref()));
+            if let Some(entry) = entry(node) {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PrefixTrie<T> {
+    type Item = (NetworkAddress, Prefix, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<(NetworkAddress, Prefix, T)> for PrefixTrie<T> {
+    fn from_iter<I: IntoIterator<Item = (NetworkAddress, Prefix, T)>>(iter: I) -> Self {
+        let mut trie = Self::new();
+        for (network, prefix, value) in iter {
+            trie.insert(&network, &prefix, value);
+        }
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn net(cidr: &str) -> (NetworkAddress, Prefix) {
+        let (network, prefix) = cidr.split_once('/').unwrap();
+        let prefix = Prefix::new(prefix.parse().unwrap()).unwrap();
+        (
+            NetworkAddress::try_new(network.to_string(), prefix.value()).unwrap(),
+            prefix,
+        )
+    }
+
+    fn trie(cidrs: &[&str]) -> PrefixTrie<String> {
+        cidrs
+            .iter()
+            .map(|cidr| {
+                let (network, prefix) = net(cidr);
+                (network, prefix, cidr.to_string())
+            })
+            .collect()
+    }
+
+    fn longest(trie: &PrefixTrie<String>, ip: &str) -> Option<String> {
+        trie.longest_match_value(&IpAddress::new(ip.to_string()))
+            .cloned()
+    }
+
+    #[test]
+    fn test_exact_match() {
+        let trie = trie(&["10.0.0.0/8", "10.1.0.0/16", "192.168.0.0/24"]);
+        assert_eq!(trie.len(), 3);
+        let (network, prefix) = net("10.1.0.0/16");
+        assert_eq!(trie.get(&network, &prefix).unwrap(), "10.1.0.0/16");
+        let (network, prefix) = net("10.1.0.0/17");
+        assert!(trie.get(&network, &prefix).is_none());
+        let (network, prefix) = net("10.0.0.0/7");
+        assert!(!trie.contains(&network, &prefix));
+    }
+
+    #[test]
+    fn test_longest_match() {
+        let trie = trie(&[
+            "0.0.0.0/0",
+            "10.0.0.0/8",
+            "10.1.0.0/16",
+            "10.1.2.0/24",
+            "10.1.2.3/32",
+        ]);
+        assert_eq!(longest(&trie, "10.1.2.3").unwrap(), "10.1.2.3/32");
+        assert_eq!(longest(&trie, "10.1.2.4").unwrap(), "10.1.2.0/24");
+        assert_eq!(longest(&trie, "10.1.3.4").unwrap(), "10.1.0.0/16");
+        assert_eq!(longest(&trie, "10.200.3.4").unwrap(), "10.0.0.0/8");
+        assert_eq!(longest(&trie, "11.0.0.0").unwrap(), "0.0.0.0/0");
+
+        let (network, prefix, _) = trie
+            .longest_match(&IpAddress::new("10.1.9.9".to_string()))
+            .unwrap();
+        assert_eq!(format!("{}{}", network, prefix), "10.1.0.0/16");
+    }
+
+    #[test]
+    fn test_sibling_networks() {
+        let trie = trie(&["10.1.2.0/24", "10.1.3.0/24"]);
+        assert_eq!(longest(&trie, "10.1.3.7").unwrap(), "10.1.3.0/24");
+        assert!(longest(&trie, "10.1.4.7").is_none());
+    }
+
+    #[test]
+    fn test_insert_replaces() {
+        let mut trie = trie(&["10.0.0.0/8"]);
+        let (network, prefix) = net("10.0.0.0/8");
+        assert_eq!(
+            trie.insert(&network, &prefix, "new".to_string()).unwrap(),
+            "10.0.0.0/8"
+        );
+        assert_eq!(trie.len(), 1);
+        *trie.get_mut(&network, &prefix).unwrap() = "changed".to_string();
+        assert_eq!(trie.get(&network, &prefix).unwrap(), "changed");
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut trie = trie(&["10.0.0.0/8", "10.1.0.0/16", "10.2.0.0/16"]);
+        let (network, prefix) = net("10.0.0.0/8");
+        assert_eq!(trie.remove(&network, &prefix).unwrap(), "10.0.0.0/8");
+        assert!(trie.remove(&network, &prefix).is_none());
+        assert_eq!(trie.len(), 2);
+        assert!(longest(&trie, "10.3.0.1").is_none());
+        assert_eq!(longest(&trie, "10.2.0.1").unwrap(), "10.2.0.0/16");
+
+        let (network, prefix) = net("10.1.0.0/16");
+        trie.remove(&network, &prefix);
+        let (network, prefix) = net("10.2.0.0/16");
+        trie.remove(&network, &prefix);
+        assert!(trie.is_empty());
+        assert!(trie.root.is_none());
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let trie = trie(&[
+            "10.0.0.0/8",
+            "10.1.0.0/16",
+            "10.1.2.0/24",
+            "10.1.3.0/24",
+            "10.2.0.0/16",
+        ]);
+        let overlapping = |cidr: &str| {
+            let (network, prefix) = net(cidr);
+            trie.overlapping(&network, &prefix)
+                .into_iter()
+                .map(|(_, _, value)| value.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            overlapping("10.1.0.0/16"),
+            ["10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/24", "10.1.3.0/24"]
+        );
+        assert_eq!(
+            overlapping("10.1.2.128/25"),
+            ["10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/24"]
+        );
+        assert_eq!(overlapping("10.0.0.0/7"), overlapping("0.0.0.0/0"));
+        assert!(overlapping("11.0.0.0/8").is_empty());
+    }
+
+    #[test]
+    fn test_iteration_order() {
+        let trie = trie(&[
+            "192.168.0.0/24",
+            "10.1.0.0/16",
+            "0.0.0.0/0",
+            "10.0.0.0/8",
+            "10.0.0.0/16",
+            "172.16.0.0/12",
+        ]);
+        let order: Vec<&String> = trie.values().collect();
+        assert_eq!(
+            order,
+            [
+                "0.0.0.0/0",
+                "10.0.0.0/8",
+                "10.0.0.0/16",
+                "10.1.0.0/16",
+                "172.16.0.0/12",
+                "192.168.0.0/24"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matches_linear_scan() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut trie = PrefixTrie::new();
+        let mut networks = Vec::new();
+        for _ in 0..2000 {
+            let prefix = Prefix::new(rng.gen_range(0..=32)).unwrap();
+            let network =
+                NetworkAddress::from_u32(rng.gen::<u32>() & 0xFF0F_FFFF, prefix.value()).unwrap();
+            trie.insert(&network, &prefix, (network.to_u32(), prefix.value()));
+            if !networks.contains(&(network.to_u32(), prefix.value())) {
+                networks.push((network.to_u32(), prefix.value()));
+            }
+        }
+        assert_eq!(trie.len(), networks.len());
+
+        for _ in 0..2000 {
+            let address = rng.gen::<u32>() & 0xFF0F_FFFF;
+            let expected = networks
+                .iter()
+                .filter(|(network, prefix)| address & mask(*prefix) == *network)
+                .max_by_key(|(_, prefix)| *prefix);
+            let ip = IpAddress::new(std::net::Ipv4Addr::from(address).to_string());
+            assert_eq!(trie.longest_match_value(&ip), expected);
+        }
+
+        let mut sorted = networks.clone();
+        sorted.sort();
+        assert_eq!(trie.values().copied().collect::<Vec<_>>(), sorted);
+    }
+}
@@ -0,0 +1,323 @@
+use std::net::Ipv4Addr;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::cidr::Cidr;
+use crate::ip_address::IpAddress;
+use crate::prefix::Prefix;
+use crate::subnet_mask::SubnetMask;
+use crate::IpCalculator;
+
+// Configuration of one host on the LAN segment
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostConfig {
+    pub name: String,
+    pub ip: IpAddress,
+    pub mask: SubnetMask,
+    pub gateway: IpAddress,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Problem {
+    GatewayOutsideSubnet,
+    WrongGateway,
+    NetworkAddress,
+    BroadcastAddress,
+    AddressOutsideSubnet,
+    MaskMismatch,
+    DuplicateIp,
+}
+
+impl Problem {
+    pub const ALL: [Problem; 7] = [
+        Problem::GatewayOutsideSubnet,
+        Problem::WrongGateway,
+        Problem::NetworkAddress,
+        Problem::BroadcastAddress,
+        Problem::AddressOutsideSubnet,
+        Problem::MaskMismatch,
+        Problem::DuplicateIp,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Problem::GatewayOutsideSubnet => "Default gateway is outside the host's subnet",
+            Problem::WrongGateway => "Default gateway is not the router",
+            Problem::NetworkAddress => "Host uses the network address",
+            Problem::BroadcastAddress => "Host uses the broadcast address",
+            Problem::AddressOutsideSubnet => "Host address is outside the segment's subnet",
+            Problem::MaskMismatch => "Subnet mask differs from the segment",
+            Problem::DuplicateIp => "IP address is used by another host",
+        }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+// A problem found on the host with the given index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    pub host: usize,
+    pub problem: Problem,
+}
+
+// One LAN segment behind a router, the router's interface defines the subnet
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub router: IpAddress,
+    pub prefix: Prefix,
+    pub hosts: Vec<HostConfig>,
+}
+
+impl Scenario {
+    pub fn network(&self) -> Cidr {
+        Cidr::from_u32(self.router.to_u32(), self.prefix.value()).unwrap()
+    }
+
+    // Every rule violated by every host
+    pub fn diagnose(&self) -> Vec<Finding> {
+        let network = self.network();
+        let broadcast = network.last();
+        let mut findings = Vec::new();
+        for (index, host) in self.hosts.iter().enumerate() {
+            let mut found = |problem| {
+                findings.push(Finding {
+                    host: index,
+                    problem,
+                })
+            };
+            let ip = host.ip.to_u32();
+
+            if host.mask != self.prefix.to_subnet_mask() {
+                found(Problem::MaskMismatch);
+            }
+            if ip == network.first() {
+                found(Problem::NetworkAddress);
+            } else if ip == broadcast {
+                found(Problem::BroadcastAddress);
+            } else if !network.contains(&host.ip) {
+                found(Problem::AddressOutsideSubnet);
+            }
+
+            // The gateway has to be reachable with the host's own mask
+            let host_mask = Prefix::from_subnet_mask(&host.mask)
+                .map_or(u32::MAX, |prefix| prefix.get_network_mask());
+            if host.gateway.to_u32() & host_mask != ip & host_mask {
+                found(Problem::GatewayOutsideSubnet);
+            } else if host.gateway != self.router {
+                found(Problem::WrongGateway);
+            }
+
+            let duplicate = self
+                .hosts
+                .iter()
+                .enumerate()
+                .any(|(other, h)| other != index && h.ip == host.ip);
+            if duplicate || host.ip == self.router {
+                found(Problem::DuplicateIp);
+            }
+        }
+        findings
+    }
+
+    pub fn problems(&self, host: usize) -> Vec<Problem> {
+        self.diagnose()
+            .into_iter()
+            .filter(|finding| finding.host == host)
+            .map(|finding| finding.problem)
+            .collect()
+    }
+
+    // One answer per host, None for a correctly configured host. An answer is
+    // right if it names any of the host's problems
+    pub fn check(&self, answers: &[Option<Problem>]) -> Vec<bool> {
+        (0..self.hosts.len())
+            .map(|host| {
+                let problems = self.problems(host);
+                match answers.get(host).copied().flatten() {
+                    Some(problem) => problems.contains(&problem),
+                    None => problems.is_empty(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn address(value: u32) -> IpAddress {
+    IpAddress::new(Ipv4Addr::from(value).to_string())
+}
+
+// Errors the generator plants, each leads to exactly one finding per host
+const PLANTED: [Problem; 5] = [
+    Problem::GatewayOutsideSubnet,
+    Problem::NetworkAddress,
+    Problem::BroadcastAddress,
+    Problem::MaskMismatch,
+    Problem::DuplicateIp,
+];
+
+impl IpCalculator {
+    // A segment with 4 to 6 hosts and one or two planted errors
+    pub fn random_scenario(&mut self) -> Scenario {
+        // Six hosts and the router need at least a /28, smaller subnets are
+        // clamped since the settings may not allow anything else
+        let mut model = self.random_model();
+        if model.prefix.value() > 28 {
+            let min_prefix = self.settings.min_prefix.min(28);
+            let prefix = self.rng.gen_range(min_prefix..=28);
+            model = self.calculate_model(&model.ip.to_u32().to_be_bytes(), prefix);
+        }
+        let network = model.network();
+        let prefix = network.prefix.clone();
+        let router = network.nth_host(0).unwrap();
+
+        // Distinct host addresses that are not the router
+        let count = self.rng.gen_range(4..=6);
+        let usable = network.hosts().len() as u64;
+        let mut indexes: Vec<u64> = Vec::new();
+        while indexes.len() < count {
+            let index = self.rng.gen_range(1..usable);
+            if !indexes.contains(&index) {
+                indexes.push(index);
+            }
+        }
+        let mut hosts: Vec<HostConfig> = indexes
+            .iter()
+            .enumerate()
+            .map(|(i, index)| HostConfig {
+                name: format!("PC{}", i + 1),
+                ip: network.nth_host(*index).unwrap(),
+                mask: prefix.to_subnet_mask(),
+                gateway: router.clone(),
+            })
+            .collect();
+
+        let errors = self.rng.gen_range(1..=2);
+        let mut victims: Vec<usize> = (0..hosts.len()).collect();
+        victims.shuffle(&mut self.rng);
+        let mut problems = PLANTED.to_vec();
+        problems.shuffle(&mut self.rng);
+
+        for (victim, problem) in victims.iter().zip(problems).take(errors) {
+            let host = &mut hosts[*victim];
+            match problem {
+                Problem::GatewayOutsideSubnet => {
+                    host.gateway = address(router.to_u32().wrapping_add(network.size() as u32))
+                }
+                Problem::NetworkAddress => host.ip = address(network.first()),
+                Problem::BroadcastAddress => host.ip = address(network.last()),
+                // A shorter mask, so the gateway stays reachable and only the mask is wrong
+                Problem::MaskMismatch => {
+                    host.mask = Prefix::new(prefix.value() - 1).unwrap().to_subnet_mask()
+                }
+                _ => host.ip = router.clone(),
+            }
+        }
+
+        Scenario {
+            router,
+            prefix,
+            hosts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GeneratorSettings;
+
+    fn host(name: &str, ip: &str, mask: &str, gateway: &str) -> HostConfig {
+        HostConfig {
+            name: name.to_string(),
+            ip: IpAddress::new(ip.to_string()),
+            mask: SubnetMask::new(mask.to_string()),
+            gateway: IpAddress::new(gateway.to_string()),
+        }
+    }
+
+    fn scenario(hosts: Vec<HostConfig>) -> Scenario {
+        Scenario {
+            router: IpAddress::new("192.168.10.1".to_string()),
+            prefix: Prefix::new(24).unwrap(),
+            hosts,
+        }
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let scenario = scenario(vec![
+            host("PC1", "192.168.10.10", "255.255.255.0", "192.168.10.1"),
+            host("PC2", "192.168.10.11", "255.255.255.0", "192.168.11.1"),
+            host("PC3", "192.168.10.255", "255.255.255.0", "192.168.10.1"),
+            host("PC4", "192.168.10.12", "255.255.0.0", "192.168.10.1"),
+            host("PC5", "192.168.10.10", "255.255.255.0", "192.168.10.1"),
+            host("PC6", "192.168.10.0", "255.255.255.0", "192.168.10.1"),
+        ]);
+        let problems: Vec<Vec<Problem>> = (0..6).map(|i| scenario.problems(i)).collect();
+        assert_eq!(
+            problems,
+            [
+                vec![Problem::DuplicateIp],
+                vec![Problem::GatewayOutsideSubnet],
+                vec![Problem::BroadcastAddress],
+                vec![Problem::MaskMismatch],
+                vec![Problem::DuplicateIp],
+                vec![Problem::NetworkAddress],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let scenario = scenario(vec![
+            host("PC1", "192.168.10.10", "255.255.255.0", "192.168.10.1"),
+            host("PC2", "192.168.10.11", "255.255.255.0", "192.168.10.2"),
+        ]);
+        assert_eq!(
+            scenario.check(&[None, Some(Problem::WrongGateway)]),
+            [true, true]
+        );
+        assert_eq!(
+            scenario.check(&[Some(Problem::MaskMismatch), None]),
+            [false, false]
+        );
+    }
+
+    #[test]
+    fn test_random_scenarios_have_planted_errors() {
+        let mut calculator = IpCalculator::with_seed(21);
+        for _ in 0..200 {
+            let scenario = calculator.random_scenario();
+            let findings = scenario.diagnose();
+            let faulty: Vec<usize> = (0..scenario.hosts.len())
+                .filter(|host| findings.iter().any(|f| f.host == *host))
+                .collect();
+            assert!((1..=2).contains(&faulty.len()), "{:?}", findings);
+            // Every faulty host has exactly one planted problem
+            for host in faulty {
+                assert_eq!(scenario.problems(host).len(), 1, "{:?}", scenario);
+                assert!(PLANTED.contains(&scenario.problems(host)[0]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_subnet_settings() {
+        let settings = GeneratorSettings {
+            min_prefix: 29,
+            max_prefix: 30,
+            ..Default::default()
+        };
+        let mut calculator = IpCalculator::with_settings(settings, 4).unwrap();
+        for _ in 0..20 {
+            let scenario = calculator.random_scenario();
+            assert!(scenario.prefix.value() <= 28);
+        }
+    }
+}
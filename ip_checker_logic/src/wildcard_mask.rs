@@ -0,0 +1,158 @@
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::ip_address::IpAddress;
+use crate::prefix::Prefix;
+
+// Cisco style wildcard mask, a 1 bit means "don't care". Unlike a subnet mask
+// the bits don't have to be contiguous, e.g. 0.0.255.254 matches even addresses
+#[derive(Debug, Clone)]
+pub struct WildcardMask(String);
+
+#[derive(Debug, Error)]
+pub enum WildcardMaskError {
+    #[error("Invalid number of octets: expected 4, got {0}")]
+    InvalidOctetCount(usize),
+
+    #[error("Invalid octet at position {position}: {reason}")]
+    InvalidOctet { position: usize, reason: String },
+
+    #[error("Empty wildcard mask")]
+    EmptyMask,
+}
+
+impl PartialEq for WildcardMask {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_u32() == other.to_u32()
+    }
+}
+
+impl WildcardMask {
+    pub fn new(mask: String) -> Self {
+        Self::try_new(mask).unwrap()
+    }
+
+    pub fn try_new(mask: String) -> Result<Self, WildcardMaskError> {
+        if mask.is_empty() {
+            return Err(WildcardMaskError::EmptyMask);
+        }
+
+        let octets: Vec<&str> = mask.split('.').collect();
+        if octets.len() != 4 {
+            return Err(WildcardMaskError::InvalidOctetCount(octets.len()));
+        }
+
+        for (i, octet) in octets.iter().enumerate() {
+            if octet.parse::<u8>().is_err() {
+                return Err(WildcardMaskError::InvalidOctet {
+                    position: i + 1,
+                    reason: format!("'{}' is not a number between 0 and 255", octet),
+                });
+            }
+        }
+
+        Ok(Self(mask))
+    }
+
+    pub fn from_u32(value: u32) -> Self {
+        Self(format!(
+            "{}.{}.{}.{}",
+            (value >> 24) & 0xFF,
+            (value >> 16) & 0xFF,
+            (value >> 8) & 0xFF,
+            value & 0xFF
+        ))
+    }
+
+    pub fn as_string(&self) -> String {
+        self.0.clone()
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        self.0
+            .split('.')
+            .map(|x| x.parse::<u8>().unwrap())
+            .fold(0, |acc, octet| (acc << 8) | octet as u32)
+    }
+
+    // Contiguous wildcards are the inverse of a subnet mask
+    pub fn is_contiguous(&self) -> bool {
+        let value = self.to_u32();
+        value & value.wrapping_add(1) == 0
+    }
+
+    // The prefix this wildcard stands for, None if it isn't contiguous
+    pub fn to_prefix(&self) -> Option<Prefix> {
+        if !self.is_contiguous() {
+            return None;
+        }
+        Prefix::new(32 - self.to_u32().count_ones() as u8).ok()
+    }
+
+    // An address matches when it equals the base address in every bit the wildcard cares about
+    pub fn matches(&self, base: &IpAddress, address: &IpAddress) -> bool {
+        let care = !self.to_u32();
+        base.to_u32() & care == address.to_u32() & care
+    }
+}
+
+impl FromStr for WildcardMask {
+    type Err = WildcardMaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_new(s.to_string())
+    }
+}
+
+impl AsRef<str> for WildcardMask {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for WildcardMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_wildcard_mask() {
+        assert!(WildcardMask::try_new("0.0.15.255".to_string()).is_ok());
+        assert!(WildcardMask::try_new("0.0.255.254".to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_octet() {
+        let mask = WildcardMask::try_new("0.0.256.0".to_string());
+        assert!(matches!(
+            mask.unwrap_err(),
+            WildcardMaskError::InvalidOctet { position: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn test_contiguous() {
+        let mask = WildcardMask::new("0.0.15.255".to_string());
+        assert!(mask.is_contiguous());
+        assert_eq!(mask.to_prefix().unwrap().value(), 20);
+
+        let mask = WildcardMask::new("0.0.255.254".to_string());
+        assert!(!mask.is_contiguous());
+        assert!(mask.to_prefix().is_none());
+    }
+
+    #[test]
+    fn test_non_contiguous_matches() {
+        let mask = WildcardMask::new("0.0.255.254".to_string());
+        let base = IpAddress::new("172.16.0.0".to_string());
+        assert!(!mask.matches(&base, &IpAddress::new("172.16.3.7".to_string())));
+        assert!(mask.matches(&base, &IpAddress::new("172.16.3.6".to_string())));
+        assert!(!mask.matches(&base, &IpAddress::new("172.17.3.6".to_string())));
+    }
+}
@@ -0,0 +1,90 @@
+use iced::widget::{button, column, row, text, text_input};
+use iced::Element;
+use ip_checker_logic::acl::AclExercise;
+use ip_checker_logic::IpCalculator;
+
+use crate::widgets::{feedback, Score};
+
+// Messages of the wildcard mask exercises
+#[derive(Debug, Clone)]
+pub enum AclMessage {
+    Input(String),  // Update the written ACL entry
+    Answer(String), // Check a yes or no answer
+    Check,          // Check the written ACL entry
+    Next,           // Generate the next exercise
+}
+
+// Practice for ACL entries and wildcard masks
+pub struct AclPractice {
+    calculator: IpCalculator,
+    exercise: AclExercise,
+    input: String,
+    result: Option<bool>,
+    score: Score,
+}
+
+impl AclPractice {
+    pub fn new() -> Self {
+        let mut calculator = IpCalculator::new();
+        Self {
+            exercise: calculator.random_acl_exercise(),
+            calculator,
+            input: String::new(),
+            result: None,
+            score: Score::default(),
+        }
+    }
+
+    pub fn update(&mut self, message: AclMessage) {
+        match message {
+            AclMessage::Input(value) => self.input = value,
+            AclMessage::Answer(answer) => self.check(&answer),
+            AclMessage::Check => self.check(&self.input.clone()),
+            AclMessage::Next => {
+                self.exercise = self.calculator.random_acl_exercise();
+                self.input.clear();
+                self.result = None;
+            }
+        }
+    }
+
+    // Every exercise counts once
+    fn check(&mut self, answer: &str) {
+        if self.result.is_none() {
+            let correct = self.exercise.check(answer);
+            self.result = Some(correct);
+            self.score.record(correct);
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, AclMessage> {
+        let open = self.result.is_none();
+        let answer: Element<'_, AclMessage> = match self.exercise {
+            AclExercise::WriteEntry { .. } => column![
+                text_input("permit <address> <wildcard>", &self.input)
+                    .on_input(AclMessage::Input)
+                    .on_submit(AclMessage::Check)
+                    .padding(5),
+                button("Check").on_press_maybe(open.then_some(AclMessage::Check)),
+            ]
+            .spacing(10)
+            .into(),
+            AclExercise::Match { .. } => row![
+                button("Yes").on_press_maybe(open.then(|| AclMessage::Answer("yes".into()))),
+                button("No").on_press_maybe(open.then(|| AclMessage::Answer("no".into()))),
+            ]
+            .spacing(10)
+            .into(),
+        };
+
+        column![
+            button("Next exercise").on_press(AclMessage::Next),
+            text(self.exercise.text()),
+            answer,
+            feedback(self.result, self.exercise.answer()),
+            self.score.view(),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
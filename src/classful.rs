@@ -0,0 +1,88 @@
+use iced::widget::{button, checkbox, column, row, text};
+use iced::Element;
+use ip_checker_logic::classful::{ClassfulExercise, ClassfulField, ClassfulRules};
+use ip_checker_logic::IpCalculator;
+
+use crate::widgets::checked_input;
+
+// Messages of the classful subnetting exercise
+#[derive(Debug, Clone)]
+pub enum ClassfulMessage {
+    Input(usize, String), // Update the answer of a field
+    Check,                // Check all fields
+    Next,                 // Generate the next exercise
+    SubnetZero(bool),     // Count subnet zero and the all-ones subnet or not
+}
+
+// Practice for classful subnet counting
+pub struct ClassfulPractice {
+    calculator: IpCalculator,
+    exercise: ClassfulExercise,
+    inputs: [String; ClassfulField::ALL.len()],
+    results: Option<Vec<bool>>,
+}
+
+impl ClassfulPractice {
+    pub fn new() -> Self {
+        let mut calculator = IpCalculator::new();
+        calculator.set_classful_rules(Some(ClassfulRules::default()));
+        Self {
+            exercise: calculator.random_classful_exercise(),
+            calculator,
+            inputs: Default::default(),
+            results: None,
+        }
+    }
+
+    pub fn update(&mut self, message: ClassfulMessage) {
+        match message {
+            ClassfulMessage::Input(index, value) => self.inputs[index] = value,
+            ClassfulMessage::Check => {
+                self.results = Some(
+                    ClassfulField::ALL
+                        .iter()
+                        .zip(&self.inputs)
+                        .map(|(field, input)| self.exercise.check(*field, input))
+                        .collect(),
+                )
+            }
+            ClassfulMessage::Next => self.next_exercise(),
+            ClassfulMessage::SubnetZero(subnet_zero) => {
+                self.calculator
+                    .set_classful_rules(Some(ClassfulRules { subnet_zero }));
+                self.next_exercise();
+            }
+        }
+    }
+
+    fn next_exercise(&mut self) {
+        self.exercise = self.calculator.random_classful_exercise();
+        self.inputs = Default::default();
+        self.results = None;
+    }
+
+    pub fn view(&self) -> Element<'_, ClassfulMessage> {
+        let mut fields = column![].spacing(5);
+        for (index, field) in ClassfulField::ALL.iter().enumerate() {
+            let result = self.results.as_ref().map(|results| results[index]);
+            fields = fields.push(
+                checked_input(field.label(), &self.inputs[index], result)
+                    .on_input(move |value| ClassfulMessage::Input(index, value)),
+            );
+        }
+
+        let subnet_zero = self.exercise.rules.subnet_zero;
+        column![
+            row![
+                button("Check").on_press(ClassfulMessage::Check),
+                button("Next exercise").on_press(ClassfulMessage::Next),
+            ]
+            .spacing(10),
+            checkbox("Use subnet zero", subnet_zero).on_toggle(ClassfulMessage::SubnetZero),
+            text(self.exercise.text()),
+            fields,
+        ]
+        .spacing(10)
+        .into()
+    }
+}
@@ -0,0 +1,145 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use iced::widget::{button, column, row, text};
+use iced::{Color, Element, Length, Subscription};
+use ip_checker_logic::classroom::{
+    exercise_model, ClassroomClient, ClassroomServer, ServerMessage,
+};
+use ip_checker_logic::{Field, IpModel, Validator};
+
+use crate::widgets::wrong;
+
+// Messages of the classroom host and student
+#[derive(Debug, Clone)]
+pub enum ClassroomMessage {
+    PushExercise, // Send a new exercise to every student
+    Tick,         // Poll the connection
+}
+
+pub fn subscription() -> Subscription<ClassroomMessage> {
+    iced::time::every(Duration::from_millis(300)).map(|_| ClassroomMessage::Tick)
+}
+
+fn new_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+// Teacher side: live dashboard of every student's answers
+pub struct ClassroomHost {
+    server: ClassroomServer,
+}
+
+impl ClassroomHost {
+    pub fn new(server: ClassroomServer) -> Self {
+        Self { server }
+    }
+
+    pub fn update(&mut self, message: ClassroomMessage) {
+        match message {
+            ClassroomMessage::PushExercise => self.server.push_exercise(new_seed()),
+            // The dashboard reads the shared state on every redraw
+            ClassroomMessage::Tick => (),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, ClassroomMessage> {
+        let exercise = self
+            .server
+            .current_exercise()
+            .map(|model| format!("Round {}: {}", self.server.round(), model.cidr()))
+            .unwrap_or("No exercise pushed yet".to_string());
+
+        let mut header = row![text("Student").width(Length::FillPortion(3))].spacing(5);
+        for field in Field::ALL {
+            header = header.push(text(short_label(field)).width(Length::FillPortion(1)));
+        }
+
+        let mut table = column![header].spacing(5);
+        for student in self.server.dashboard() {
+            let name = if student.connected {
+                student.name
+            } else {
+                format!("{} (left)", student.name)
+            };
+            let mut line = row![text(name).width(Length::FillPortion(3))].spacing(5);
+            for field in Field::ALL {
+                let cell = match &student.result {
+                    Some(result) if result.is_correct(field) => {
+                        text("✓").color(Color::from_rgb8(0, 200, 0))
+                    }
+                    Some(_) => wrong("✗"),
+                    None => text("…"),
+                };
+                line = line.push(cell.width(Length::FillPortion(1)));
+            }
+            table = table.push(line);
+        }
+
+        column![
+            text(format!("Listening on {}", self.server.local_addr())),
+            text(exercise),
+            button("Push new exercise").on_press(ClassroomMessage::PushExercise),
+            table,
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
+fn short_label(field: Field) -> &'static str {
+    match field {
+        Field::Mask => "Mask",
+        Field::NetworkAddress => "Net",
+        Field::BroadcastAddress => "Bcast",
+        Field::FirstHost => "First",
+        Field::LastHost => "Last",
+        Field::PossibleHosts => "Hosts",
+    }
+}
+
+// Student side: exercises come from the teacher instead of being generated
+pub struct ClassroomStudent {
+    client: ClassroomClient,
+    round: Option<u32>,
+}
+
+impl ClassroomStudent {
+    pub fn new(client: ClassroomClient) -> Self {
+        Self {
+            client,
+            round: None,
+        }
+    }
+
+    // Returns the newest exercise pushed by the teacher, if any arrived
+    pub fn update(&mut self, message: ClassroomMessage) -> Option<IpModel> {
+        if !matches!(message, ClassroomMessage::Tick) {
+            return None;
+        }
+        let mut exercise = None;
+        for message in self.client.poll() {
+            if let ServerMessage::Exercise {
+                round,
+                seed,
+                settings,
+            } = message
+            {
+                if let Some(model) = exercise_model(seed, &settings) {
+                    self.round = Some(round);
+                    exercise = Some(model);
+                }
+            }
+        }
+        exercise
+    }
+
+    pub fn submit(&mut self, result: &Validator) {
+        if let Some(round) = self.round {
+            // A lost connection shows up as the student leaving on the dashboard
+            let _ = self.client.send_result(round, result);
+        }
+    }
+}
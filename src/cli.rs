@@ -0,0 +1,849 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ip_checker_logic::cidr::{self, Cidr, CidrError};
+use ip_checker_logic::classful::ClassfulRules;
+use ip_checker_logic::classroom::{ClassroomClient, ClassroomServer};
+use ip_checker_logic::dhcp::{self, DhcpSubnet};
+use ip_checker_logic::dns::{self, ZoneOptions};
+use ip_checker_logic::duel::Duel;
+use ip_checker_logic::exam::{Exam, ExamError, ExamResult};
+use ip_checker_logic::export::worksheet::{self, Sheet, WorksheetOptions};
+use ip_checker_logic::export::{anki, moodle, topology};
+use ip_checker_logic::grading::{GradeReport, GradingError, Submission};
+use ip_checker_logic::interfaces::{self, InterfaceError};
+use ip_checker_logic::inventory::{Inventory, InventoryError, InventoryFormat};
+use ip_checker_logic::ipam::{self, IpamError, IpamRows};
+use ip_checker_logic::plan::{AddressPlan, PlanError, PlanFormat};
+
+use crate::classroom::{ClassroomHost, ClassroomStudent};
+use crate::duel::DuelSession;
+use crate::exam::ExamSession;
+use crate::plan::PlanEditor;
+use crate::Mode;
+use ip_checker_logic::{Field, GeneratorSettings, IpCalculator, IpModel, SettingsError};
+use thiserror::Error;
+
+const USAGE: &str = "\
+Usage: ip_checker [COMMAND] [OPTIONS]
+
+Without a command the graphical application is started.
+
+Commands:
+  quiz        Export exercises as a Moodle quiz
+                --format <moodle-xml|gift>  (default: moodle-xml)
+                --category <NAME>           (default: IP Checker)
+                --out <FILE>                (default: stdout)
+  worksheet   Write a printable worksheet and its answer key
+                --format <html|svg>         (default: html)
+                --per-page <1-4>            (default: 2)
+                --grids                     add blank binary grids for working
+                --title <TITLE>
+                --out-dir <DIR>             (default: current directory)
+  anki        Export exercises as an Anki deck (tab separated import format)
+                --deck <NAME>               (default: IP Checker)
+                --out-dir <DIR>             (default: current directory)
+                writes deck.txt and note-type.txt
+  grade       Grade student answer files (CSV or text) for an exercise set
+                --seed <SEED>               required, same as for the handed out set
+                --out-dir <DIR>             (default: current directory)
+                <FILE>...                   one answer file per student
+                writes students.csv, questions.csv and report.html
+  exam        Start the GUI locked to an exam
+                <FILE>                      exam definition (TOML) or encrypted package
+  exam-create Write an exam definition
+                --id <ID>                   required
+                --title <TITLE>
+                --time-limit <MINUTES>      (default: 30)
+                --attempts <N>              submissions per exercise (default: 1)
+                --hints                     show which answers are correct
+                --key <FILE>                required, private key results are signed with
+                --out <FILE>                (default: stdout)
+                the exam file only contains the public key results are verified with
+  exam-package Encrypt an exam so it can only be opened with a password
+                --password <PASSWORD>       required, announced at exam start
+                --key <FILE>                key from exam-create, without it results are unsigned
+                --out <FILE>                required
+                <FILE>                      exam definition (TOML)
+  verify-result Check signed exam result files and recompute their scores
+                --exam <FILE>               the exam definition or package
+                --password <PASSWORD>       required if the exam is a package
+                <FILE>...                   result files handed in
+  classroom-host Start the GUI as classroom server with a live dashboard
+                --port <PORT>               (default: 7878)
+  classroom-join Start the GUI as a student connected to a classroom
+                --server <HOST:PORT>        required
+                --name <NAME>               required
+  duel-host   Start the GUI and wait for an opponent to race against
+                --port <PORT>               (default: 7879)
+                --name <NAME>               (default: Host)
+                --best-of <N>               (default: 5)
+  duel-join   Start the GUI and join a duel
+                --server <HOST:PORT>        required
+                --name <NAME>               (default: Guest)
+  aggregate   Merge networks into the smallest list covering the same addresses
+                <CIDR>...                   e.g. 10.0.0.0/24 10.0.1.0/24
+  exclude     Remove networks from others and print what is left
+                --remove <CIDR>[,<CIDR>...] required, networks to take out
+                <CIDR>...                   e.g. 10.0.0.0/8 --remove 10.1.2.0/24
+  range       Convert an address range into a list of networks
+                <START>-<END>               e.g. 10.0.0.5-10.0.0.20
+  plan        Start the GUI with the address plan editor
+                <FILE>                      plan (.toml or .json), created on save
+  plan-check  Check an address plan for overlaps and misplaced addresses
+                <FILE>                      plan (.toml or .json)
+                prints the problems and the free space of the parent block
+  dns         Write BIND zone files for the subnets and hosts of an address plan
+                --domain <DOMAIN>           required, e.g. example.com
+                --nameserver <NAME>         (default: ns1)
+                --hostmaster <MAILBOX>      (default: hostmaster)
+                --serial <N>                (default: 1)
+                --ttl <SECONDS>             (default: 3600)
+                --out-dir <DIR>             (default: current directory)
+                <FILE>                      plan (.toml or .json)
+                writes one db.<zone> file per zone and named.conf, subnets
+                longer than /24 get RFC 2317 classless reverse zones
+  topology    Write a topology addressing exercise and its answer key
+                --format <svg|dot>          (default: svg)
+                --out-dir <DIR>             (default: current directory)
+                writes topology and topology-answer-key files, --seed picks the topology
+  dhcp        Write a DHCP server configuration for an address plan or one network
+                --format <kea|dnsmasq>      (default: kea)
+                --network <IP>/<PREFIX>     a single network, the IP is its gateway
+                --out <FILE>                (default: stdout)
+                <FILE>                      plan (.toml or .json), instead of --network
+                pools span the usable hosts without the gateway and reservations,
+                nothing is written if a fixed address is misplaced
+  ipam-import Convert a NetBox or phpIPAM CSV export into an address plan
+                --prefixes <FILE>           required, exported prefixes or subnets
+                --addresses <FILE>          exported IP addresses
+                --out <FILE>                plan to write (.toml or .json)
+                prints overlaps, misplaced addresses and the utilisation
+  netbox-export Write NetBox bulk import files for an address plan
+                --out-dir <DIR>             (default: current directory)
+                <FILE>                      plan (.toml or .json)
+                writes prefixes.csv and ip-addresses.csv
+  inventory-check Check assigned networks and host addresses for conflicts
+                <FILE>...                   CSV with name,address columns, addresses
+                                            with a prefix length are networks, or JSON
+                                            with networks and hosts lists
+                prints overlaps, duplicate and misplaced addresses and the
+                utilisation of every network
+  interfaces  Print the subnet facts of the IPv4 addresses of this machine
+                <FILE>                      saved `ip -j addr` output instead
+                loopback, /31 and /32 addresses are left out
+  help        Show this message
+
+Exercise generation options:
+  --count <N>          number of exercises (default: 10)
+  --seed <SEED>        seed for reproducible exercises (default: random)
+  --min-prefix <N>     (default: 18)
+  --max-prefix <N>     (default: 27)
+  --min-octet <N>      (default: 111)
+  --max-octet <N>      (default: 254)
+  --classful           only subnetted class A to C networks
+  --no-subnet-zero     with --classful, leave subnet zero and the all-ones subnet unused";
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("Unknown command: {0}")]
+    UnknownCommand(String),
+
+    #[error("Missing value for option --{0}")]
+    MissingValue(String),
+
+    #[error("Invalid value for option --{option}: '{value}'")]
+    InvalidValue { option: String, value: String },
+
+    #[error("Missing required option --{0}")]
+    MissingOption(String),
+
+    #[error("{file}: {source}")]
+    Grading { file: String, source: GradingError },
+
+    #[error(transparent)]
+    Exam(#[from] ExamError),
+
+    #[error(transparent)]
+    Cidr(#[from] CidrError),
+
+    #[error(transparent)]
+    Plan(#[from] PlanError),
+
+    #[error("The plan has {0} problem(s)")]
+    PlanIssues(usize),
+
+    #[error("The DHCP configuration has {0} problem(s)")]
+    DhcpIssues(usize),
+
+    #[error("{file}: {source}")]
+    Ipam { file: String, source: IpamError },
+
+    #[error("{0} contains no prefixes")]
+    NoPrefixes(String),
+
+    #[error("{file}: {source}")]
+    Inventory {
+        file: String,
+        source: InventoryError,
+    },
+
+    #[error("The inventory has {0} problem(s)")]
+    InventoryIssues(usize),
+
+    #[error(transparent)]
+    Interface(#[from] InterfaceError),
+
+    #[error("{0} result file(s) failed verification")]
+    VerificationFailed(usize),
+
+    #[error(transparent)]
+    Settings(#[from] SettingsError),
+
+    #[error("Unexpected argument: {0}")]
+    UnexpectedArgument(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+// Switches never take a value, the argument after them stays positional
+const SWITCHES: [&str; 4] = ["grids", "hints", "classful", "no-subnet-zero"];
+
+// Parsed `--name value` options, `--flag` switches and positional arguments of a command
+struct Args {
+    options: HashMap<String, String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut options = HashMap::new();
+        let mut positional = Vec::new();
+        let mut iter = args.iter().peekable();
+        while let Some(arg) = iter.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                let value = if SWITCHES.contains(&name) {
+                    "true".to_string()
+                } else {
+                    iter.next_if(|next| !next.starts_with("--"))
+                        .ok_or_else(|| CliError::MissingValue(name.to_string()))?
+                        .clone()
+                };
+                options.insert(name.to_string(), value);
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Self {
+            options,
+            positional,
+        })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn require(&self, name: &str) -> Result<&str, CliError> {
+        self.get(name)
+            .ok_or_else(|| CliError::MissingOption(name.to_string()))
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.get(name) == Some("true")
+    }
+
+    fn invalid(&self, name: &str) -> CliError {
+        CliError::InvalidValue {
+            option: name.to_string(),
+            value: self.get(name).unwrap_or_default().to_string(),
+        }
+    }
+
+    fn get_or<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.get(name).unwrap_or(default)
+    }
+
+    fn parse_or<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T, CliError> {
+        match self.get(name) {
+            Some(value) => value.parse().map_err(|_| self.invalid(name)),
+            None => Ok(default),
+        }
+    }
+
+    // Seed from --seed, or a fresh one that is reported so the run can be repeated
+    fn seed(&self) -> Result<u64, CliError> {
+        if self.get("seed").is_some() {
+            return self.parse_or("seed", 0);
+        }
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        eprintln!("Using seed {}", seed);
+        Ok(seed)
+    }
+
+    fn generator_settings(&self) -> Result<GeneratorSettings, CliError> {
+        let defaults = GeneratorSettings::default();
+        let settings = GeneratorSettings {
+            min_octet: self.parse_or("min-octet", defaults.min_octet)?,
+            max_octet: self.parse_or("max-octet", defaults.max_octet)?,
+            min_prefix: self.parse_or("min-prefix", defaults.min_prefix)?,
+            max_prefix: self.parse_or("max-prefix", defaults.max_prefix)?,
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    fn calculator(&self) -> Result<IpCalculator, CliError> {
+        let mut calculator = IpCalculator::with_settings(self.generator_settings()?, self.seed()?)?;
+        if self.flag("classful") {
+            calculator.set_classful_rules(Some(ClassfulRules {
+                subnet_zero: !self.flag("no-subnet-zero"),
+            }));
+        }
+        Ok(calculator)
+    }
+
+    fn no_positional(&self) -> Result<(), CliError> {
+        match self.positional.first() {
+            Some(arg) => Err(CliError::UnexpectedArgument(arg.clone())),
+            None => Ok(()),
+        }
+    }
+
+    // Write to --out, or to stdout if no file was given
+    fn write_output(&self, content: &str) -> Result<(), CliError> {
+        match self.get("out") {
+            Some(path) => std::fs::write(path, content)?,
+            None => print!("{}", content),
+        }
+        Ok(())
+    }
+}
+
+// Commands that start the GUI in a special mode
+pub fn gui_mode(args: &[String]) -> Result<Option<Mode>, CliError> {
+    let (command, rest) = args.split_first().expect("gui_mode requires a command");
+    let args = Args::parse(rest)?;
+    let mode = match command.as_str() {
+        "exam" => Mode::Exam(Box::new(load_exam(&args)?)),
+        "classroom-host" => {
+            args.no_positional()?;
+            let port = args.parse_or("port", 7878u16)?;
+            let server = ClassroomServer::bind(("0.0.0.0", port), args.generator_settings()?)?;
+            Mode::ClassroomHost(ClassroomHost::new(server))
+        }
+        "classroom-join" => {
+            args.no_positional()?;
+            let client = ClassroomClient::connect(args.require("server")?, args.require("name")?)?;
+            Mode::ClassroomStudent(ClassroomStudent::new(client))
+        }
+        "duel-host" => {
+            args.no_positional()?;
+            let duel = Duel::host(
+                ("0.0.0.0", args.parse_or("port", 7879u16)?),
+                args.get_or("name", "Host"),
+                args.parse_or("best-of", 5u32)?,
+                args.generator_settings()?,
+                args.seed()?,
+            )?;
+            Mode::Duel(DuelSession::new(duel))
+        }
+        "duel-join" => {
+            args.no_positional()?;
+            let duel = Duel::join(args.require("server")?, args.get_or("name", "Guest"))?;
+            Mode::Duel(DuelSession::new(duel))
+        }
+        "plan" => Mode::Plan(Box::new(PlanEditor::new(Some(single_file(&args)?.into())))),
+        _ => return Ok(None),
+    };
+    Ok(Some(mode))
+}
+
+// Reads the exam file given to the `exam` command
+fn load_exam(args: &Args) -> Result<ExamSession, CliError> {
+    let path = single_file(args)?;
+    let bytes = std::fs::read(path)?;
+    if Exam::is_package(&bytes) {
+        return Ok(ExamSession::locked(path.into(), bytes));
+    }
+    let exam = Exam::from_toml(&String::from_utf8_lossy(&bytes))?;
+    // Fail early instead of after the student pressed start
+    exam.exercises()?;
+    exam.signing_key()?;
+    Ok(ExamSession::new(path.into(), exam))
+}
+
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let (command, rest) = args.split_first().expect("run requires a command");
+    let args = Args::parse(rest)?;
+    match command.as_str() {
+        "quiz" => quiz(&args),
+        "worksheet" => worksheet(&args),
+        "anki" => anki_deck(&args),
+        "grade" => grade(&args),
+        "exam-create" => exam_create(&args),
+        "exam-package" => exam_package(&args),
+        "verify-result" => verify_result(&args),
+        "aggregate" => aggregate(&args),
+        "exclude" => exclude(&args),
+        "range" => range(&args),
+        "topology" => topology_exercise(&args),
+        "plan-check" => plan_check(&args),
+        "dns" => dns_zones(&args),
+        "dhcp" => dhcp_config(&args),
+        "ipam-import" => ipam_import(&args),
+        "netbox-export" => netbox_export(&args),
+        "inventory-check" => inventory_check(&args),
+        "interfaces" => local_interfaces(&args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(CliError::UnknownCommand(other.to_string())),
+    }
+}
+
+fn quiz(args: &Args) -> Result<(), CliError> {
+    args.no_positional()?;
+    let count = args.parse_or("count", 10usize)?;
+    let category = args.get_or("category", "IP Checker");
+    let models = args.calculator()?.random_models(count);
+
+    let content = match args.get_or("format", "moodle-xml") {
+        "moodle-xml" => moodle::to_moodle_xml(&models, category),
+        "gift" => moodle::to_gift(&models, category),
+        _ => return Err(args.invalid("format")),
+    };
+    args.write_output(&content)
+}
+
+fn worksheet(args: &Args) -> Result<(), CliError> {
+    args.no_positional()?;
+    let count = args.parse_or("count", 10usize)?;
+    let per_page = args.parse_or("per-page", 2usize)?;
+    if !(1..=4).contains(&per_page) {
+        return Err(args.invalid("per-page"));
+    }
+    let defaults = WorksheetOptions::default();
+    let options = WorksheetOptions {
+        title: args.get_or("title", &defaults.title).to_string(),
+        exercises_per_page: per_page,
+        binary_grids: args.flag("grids"),
+    };
+    let models = args.calculator()?.random_models(count);
+    let out_dir = Path::new(args.get_or("out-dir", "."));
+    std::fs::create_dir_all(out_dir)?;
+
+    for (sheet, name) in [
+        (Sheet::Student, "worksheet"),
+        (Sheet::AnswerKey, "answer-key"),
+    ] {
+        match args.get_or("format", "html") {
+            "html" => {
+                let html = worksheet::render_html(&models, &options, sheet);
+                std::fs::write(out_dir.join(format!("{}.html", name)), html)?;
+            }
+            "svg" => {
+                let pages = worksheet::render_svg_pages(&models, &options, sheet);
+                for (page, svg) in pages.iter().enumerate() {
+                    std::fs::write(out_dir.join(format!("{}-{}.svg", name, page + 1)), svg)?;
+                }
+            }
+            _ => return Err(args.invalid("format")),
+        }
+    }
+    Ok(())
+}
+
+fn anki_deck(args: &Args) -> Result<(), CliError> {
+    args.no_positional()?;
+    let count = args.parse_or("count", 10usize)?;
+    let models = args.calculator()?.random_models(count);
+    let out_dir = Path::new(args.get_or("out-dir", "."));
+    std::fs::create_dir_all(out_dir)?;
+
+    let deck = anki::to_anki_tsv(&models, args.get_or("deck", "IP Checker"));
+    std::fs::write(out_dir.join("deck.txt"), deck)?;
+    std::fs::write(out_dir.join("note-type.txt"), anki::note_type_definition())?;
+    Ok(())
+}
+
+fn grade(args: &Args) -> Result<(), CliError> {
+    args.require("seed")?;
+    let count = args.parse_or("count", 10usize)?;
+    let exercises = args.calculator()?.random_models(count);
+
+    let mut submissions = Vec::new();
+    for file in &args.positional {
+        let path = Path::new(file);
+        let student = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| file.clone());
+        let text = std::fs::read_to_string(path)?;
+        let submission =
+            Submission::parse(&student, &text).map_err(|source| CliError::Grading {
+                file: file.clone(),
+                source,
+            })?;
+        submissions.push(submission);
+    }
+
+    let report = GradeReport::new(exercises, &submissions);
+    let out_dir = Path::new(args.get_or("out-dir", "."));
+    std::fs::create_dir_all(out_dir)?;
+    std::fs::write(out_dir.join("students.csv"), report.students_csv())?;
+    std::fs::write(out_dir.join("questions.csv"), report.questions_csv())?;
+    std::fs::write(out_dir.join("report.html"), report.to_html())?;
+    Ok(())
+}
+
+fn exam_create(args: &Args) -> Result<(), CliError> {
+    args.no_positional()?;
+    let defaults = Exam::default();
+    let mut exam = Exam {
+        id: args.require("id")?.to_string(),
+        title: args.get_or("title", &defaults.title).to_string(),
+        seed: args.seed()?,
+        count: args.parse_or("count", defaults.count)?,
+        settings: args.generator_settings()?,
+        exercises: Vec::new(),
+        time_limit_minutes: args.parse_or("time-limit", defaults.time_limit_minutes)?,
+        attempts: args.parse_or("attempts", defaults.attempts)?,
+        hints: args.flag("hints"),
+        verifying_key: None,
+        signing_key: None,
+    };
+    exam.validate()?;
+    // The private key stays with the teacher, it only reaches students inside a package
+    let key_file = args.require("key")?;
+    std::fs::write(key_file, exam.generate_key_pair() + "\n")?;
+    eprintln!(
+        "Results will be verified with public key {}",
+        exam.verifying_key.as_deref().unwrap_or_default()
+    );
+    args.write_output(&exam.to_toml()?)
+}
+
+fn exam_package(args: &Args) -> Result<(), CliError> {
+    let [file] = args.positional.as_slice() else {
+        return Err(CliError::MissingOption("FILE".to_string()));
+    };
+    let mut exam = Exam::from_toml(&std::fs::read_to_string(file)?)?;
+    exam.exercises()?;
+    if let Some(key_file) = args.get("key") {
+        exam.set_signing_key(&std::fs::read_to_string(key_file)?)?;
+    }
+    let package = exam.to_package(args.require("password")?)?;
+    std::fs::write(args.require("out")?, package)?;
+    Ok(())
+}
+
+fn verify_result(args: &Args) -> Result<(), CliError> {
+    let bytes = std::fs::read(args.require("exam")?)?;
+    let exam = if Exam::is_package(&bytes) {
+        Exam::from_package(&bytes, args.require("password")?)?
+    } else {
+        Exam::from_toml(&String::from_utf8_lossy(&bytes))?
+    };
+    let mut failed = 0;
+    for file in &args.positional {
+        let outcome = std::fs::read_to_string(file)
+            .map_err(CliError::from)
+            .and_then(|text| Ok(ExamResult::from_toml(&text)?));
+        match outcome {
+            Ok(result) => match result.verify(&exam) {
+                Ok(points) => println!(
+                    "{}: OK, {} scored {} of {}",
+                    file, result.student, points, result.max_points
+                ),
+                Err(e) => {
+                    failed += 1;
+                    println!("{}: FAILED, {}", file, e);
+                }
+            },
+            Err(e) => {
+                failed += 1;
+                println!("{}: FAILED, {}", file, e);
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(CliError::VerificationFailed(failed));
+    }
+    Ok(())
+}
+
+// Networks given as positional arguments, at least one is required
+fn positional_cidrs(args: &Args) -> Result<Vec<Cidr>, CliError> {
+    if args.positional.is_empty() {
+        return Err(CliError::MissingOption("CIDR".to_string()));
+    }
+    Ok(args
+        .positional
+        .iter()
+        .map(|cidr| cidr.parse())
+        .collect::<Result<_, _>>()?)
+}
+
+fn print_cidrs(cidrs: &[Cidr]) {
+    for cidr in cidrs {
+        println!("{}", cidr);
+    }
+}
+
+fn aggregate(args: &Args) -> Result<(), CliError> {
+    print_cidrs(&cidr::aggregate(&positional_cidrs(args)?));
+    Ok(())
+}
+
+fn exclude(args: &Args) -> Result<(), CliError> {
+    let removed = args
+        .require("remove")?
+        .split(',')
+        .map(|cidr| cidr.parse())
+        .collect::<Result<Vec<Cidr>, _>>()?;
+    print_cidrs(&cidr::exclude(&positional_cidrs(args)?, &removed));
+    Ok(())
+}
+
+fn range(args: &Args) -> Result<(), CliError> {
+    // Both `START-END` and `START - END` are accepted
+    let range = args.positional.join(" ");
+    if range.is_empty() {
+        return Err(CliError::MissingOption("START-END".to_string()));
+    }
+    let (start, end) = cidr::parse_range(&range)?;
+    print_cidrs(&cidr::range_to_cidrs(&start, &end)?);
+    Ok(())
+}
+
+fn topology_exercise(args: &Args) -> Result<(), CliError> {
+    args.no_positional()?;
+    let topology = args.calculator()?.random_topology();
+    let out_dir = Path::new(args.get_or("out-dir", "."));
+    std::fs::create_dir_all(out_dir)?;
+
+    // Named apart from the worksheet files, both may share one directory
+    for (sheet, name) in [
+        (Sheet::Student, "topology"),
+        (Sheet::AnswerKey, "topology-answer-key"),
+    ] {
+        let (content, extension) = match args.get_or("format", "svg") {
+            "svg" => (topology::render_svg(&topology, sheet), "svg"),
+            "dot" => (topology::render_dot(&topology, sheet), "dot"),
+            _ => return Err(args.invalid("format")),
+        };
+        std::fs::write(out_dir.join(format!("{}.{}", name, extension)), content)?;
+    }
+    Ok(())
+}
+
+// The only positional argument of a command
+fn single_file(args: &Args) -> Result<&str, CliError> {
+    match args.positional.as_slice() {
+        [file] => Ok(file),
+        [] => Err(CliError::MissingOption("FILE".to_string())),
+        [_, extra, ..] => Err(CliError::UnexpectedArgument(extra.clone())),
+    }
+}
+
+// Reads the plan file given as only positional argument
+fn load_plan(args: &Args) -> Result<AddressPlan, CliError> {
+    let path = Path::new(single_file(args)?);
+    Ok(AddressPlan::parse(
+        &std::fs::read_to_string(path)?,
+        PlanFormat::from_path(path)?,
+    )?)
+}
+
+fn plan_check(args: &Args) -> Result<(), CliError> {
+    let plan = load_plan(args)?;
+    let issues = plan.check();
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    println!("Free space in {}:", plan.block);
+    print_cidrs(&plan.free_space());
+    match issues.len() {
+        0 => Ok(()),
+        count => Err(CliError::PlanIssues(count)),
+    }
+}
+
+fn dns_zones(args: &Args) -> Result<(), CliError> {
+    let plan = load_plan(args)?;
+    let defaults = ZoneOptions::default();
+    let options = ZoneOptions {
+        domain: args.require("domain")?.to_string(),
+        nameserver: args.get_or("nameserver", &defaults.nameserver).to_string(),
+        hostmaster: args.get_or("hostmaster", &defaults.hostmaster).to_string(),
+        serial: args.parse_or("serial", defaults.serial)?,
+        ttl: args.parse_or("ttl", defaults.ttl)?,
+    };
+    // Empty or shared names would give broken or ambiguous records
+    let issues = dns::name_issues(&plan);
+    for issue in &issues {
+        eprintln!("{}", issue);
+    }
+    if !issues.is_empty() {
+        return Err(CliError::PlanIssues(issues.len()));
+    }
+    let out_dir = args.get_or("out-dir", ".");
+    std::fs::create_dir_all(out_dir)?;
+
+    let zones = dns::plan_zones(&plan, &options);
+    for zone in &zones {
+        std::fs::write(Path::new(out_dir).join(zone.file_name()), &zone.content)?;
+    }
+    std::fs::write(
+        Path::new(out_dir).join("named.conf"),
+        dns::named_conf(&zones, out_dir),
+    )?;
+    Ok(())
+}
+
+fn dhcp_config(args: &Args) -> Result<(), CliError> {
+    let subnets = match args.get("network") {
+        Some(network) => {
+            args.no_positional()?;
+            let model = IpModel::from_cidr(network).ok_or_else(|| args.invalid("network"))?;
+            vec![DhcpSubnet::from_model(&model)]
+        }
+        None => dhcp::plan_subnets(&load_plan(args)?),
+    };
+    let issues: Vec<_> = subnets.iter().flat_map(DhcpSubnet::check).collect();
+    for issue in &issues {
+        if issue.is_warning() {
+            eprintln!("warning: {}", issue);
+        } else {
+            eprintln!("{}", issue);
+        }
+    }
+    let errors = issues.iter().filter(|issue| !issue.is_warning()).count();
+    if errors > 0 {
+        return Err(CliError::DhcpIssues(errors));
+    }
+    let config = match args.get_or("format", "kea") {
+        "kea" => dhcp::render_kea(&subnets),
+        "dnsmasq" => dhcp::render_dnsmasq(&subnets),
+        _ => return Err(args.invalid("format")),
+    };
+    args.write_output(&config)
+}
+
+// Reads an IPAM export, errors name the file
+fn read_ipam<T>(
+    file: &str,
+    parse: fn(&str) -> Result<IpamRows<T>, IpamError>,
+) -> Result<Vec<T>, CliError> {
+    let ipam = parse(&std::fs::read_to_string(file)?).map_err(|source| CliError::Ipam {
+        file: file.to_string(),
+        source,
+    })?;
+    if ipam.skipped_ipv6 > 0 {
+        println!("{}: skipped {} IPv6 row(s)", file, ipam.skipped_ipv6);
+    }
+    Ok(ipam.rows)
+}
+
+fn ipam_import(args: &Args) -> Result<(), CliError> {
+    args.no_positional()?;
+    let file = args.require("prefixes")?;
+    let prefixes = read_ipam(file, ipam::parse_prefixes)?;
+    let addresses = match args.get("addresses") {
+        Some(file) => read_ipam(file, ipam::parse_addresses)?,
+        None => Vec::new(),
+    };
+    let plan =
+        ipam::to_plan(&prefixes, &addresses).ok_or_else(|| CliError::NoPrefixes(file.into()))?;
+
+    for issue in plan.check() {
+        println!("{}", issue);
+    }
+    for address in ipam::unassigned(&plan, &addresses) {
+        println!(
+            "{} ({}) is not inside any imported prefix",
+            address.address.as_string(),
+            address.name
+        );
+    }
+    println!("Utilisation:");
+    for utilisation in plan.utilisation() {
+        println!("{}", utilisation);
+    }
+    if let Some(out) = args.get("out") {
+        let path = Path::new(out);
+        std::fs::write(path, plan.to_text(PlanFormat::from_path(path)?)?)?;
+    }
+    Ok(())
+}
+
+fn netbox_export(args: &Args) -> Result<(), CliError> {
+    let plan = load_plan(args)?;
+    let out_dir = Path::new(args.get_or("out-dir", "."));
+    std::fs::create_dir_all(out_dir)?;
+    std::fs::write(
+        out_dir.join("prefixes.csv"),
+        ipam::render_netbox_prefixes(&plan),
+    )?;
+    std::fs::write(
+        out_dir.join("ip-addresses.csv"),
+        ipam::render_netbox_addresses(&plan),
+    )?;
+    Ok(())
+}
+
+fn inventory_check(args: &Args) -> Result<(), CliError> {
+    if args.positional.is_empty() {
+        return Err(CliError::MissingOption("FILE".to_string()));
+    }
+    let mut inventory = Inventory::default();
+    for file in &args.positional {
+        let path = Path::new(file);
+        let text = std::fs::read_to_string(path)?;
+        let parsed = InventoryFormat::from_path(path)
+            .and_then(|format| Inventory::parse(&text, format))
+            .map_err(|source| CliError::Inventory {
+                file: file.clone(),
+                source,
+            })?;
+        inventory.merge(parsed);
+    }
+
+    let issues = inventory.check();
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    println!("Utilisation:");
+    for utilisation in inventory.utilisation() {
+        println!("{}", utilisation);
+    }
+    match issues.len() {
+        0 => Ok(()),
+        count => Err(CliError::InventoryIssues(count)),
+    }
+}
+
+fn local_interfaces(args: &Args) -> Result<(), CliError> {
+    let addresses = match args.positional.first() {
+        Some(_) => interfaces::parse_ip_json(&std::fs::read_to_string(single_file(args)?)?)?,
+        None => interfaces::read_local()?,
+    };
+    for address in interfaces::practice_addresses(addresses) {
+        let Some(model) = address.model() else {
+            continue;
+        };
+        println!("{}", address);
+        for field in Field::ALL {
+            println!("  {:<18} {}", field.label(), model.answer(field));
+        }
+        println!("  {:<18} {}", "Wildcard Mask", model.wildcard_mask());
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use iced::widget::{column, text};
+use iced::{Element, Subscription};
+use ip_checker_logic::duel::{Duel, DuelEvent, Player};
+use ip_checker_logic::{IpModel, Validator};
+
+// Messages of a running duel
+#[derive(Debug, Clone)]
+pub enum DuelMessage {
+    Tick, // Poll the connection
+}
+
+pub fn subscription() -> Subscription<DuelMessage> {
+    iced::time::every(Duration::from_millis(200)).map(|_| DuelMessage::Tick)
+}
+
+// Scoreboard and status of a duel against another instance
+pub struct DuelSession {
+    duel: Duel,
+    status: String,
+    finished: bool,
+}
+
+impl DuelSession {
+    pub fn new(duel: Duel) -> Self {
+        let status = match duel.local_addr() {
+            Some(addr) => format!("Waiting for an opponent on {}", addr),
+            None => "Waiting for the host".to_string(),
+        };
+        Self {
+            duel,
+            status,
+            finished: false,
+        }
+    }
+
+    // Returns the exercise of a new round, if one started
+    pub fn update(&mut self, message: DuelMessage) -> Option<IpModel> {
+        let DuelMessage::Tick = message;
+        let mut exercise = None;
+        for event in self.duel.poll() {
+            match event {
+                DuelEvent::Connected { opponent } => {
+                    self.status = format!("Playing against {}", opponent)
+                }
+                DuelEvent::Round { round, model } => {
+                    exercise = Some(model);
+                    self.status = format!("Round {}", round);
+                }
+                DuelEvent::RoundWon { round, winner } => {
+                    self.status = format!("{} won round {}", self.describe(winner), round)
+                }
+                DuelEvent::MatchOver { winner } => {
+                    self.finished = true;
+                    self.status = format!("{} won the match!", self.describe(winner));
+                }
+                DuelEvent::Disconnected if !self.finished => {
+                    self.finished = true;
+                    self.status = "Opponent disconnected".to_string();
+                }
+                DuelEvent::Disconnected => (),
+            }
+        }
+        exercise
+    }
+
+    fn describe(&self, player: Player) -> String {
+        if player == self.duel.me() {
+            "You".to_string()
+        } else {
+            self.duel.scoreboard().name(player).to_string()
+        }
+    }
+
+    // Only a fully correct answer counts for the round
+    pub fn submit(&mut self, result: &Validator) {
+        if result.all_correct() && !self.finished {
+            let round = self.duel.round();
+            self.duel.solve(round);
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, DuelMessage> {
+        let scoreboard = self.duel.scoreboard();
+        column![
+            text(format!(
+                "{} {} : {} {} (best of {})",
+                scoreboard.host_name,
+                scoreboard.host_score,
+                scoreboard.guest_score,
+                scoreboard.guest_name,
+                scoreboard.best_of
+            )),
+            text(&self.status),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
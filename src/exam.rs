@@ -0,0 +1,340 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use iced::widget::{button, column, row, text, text_input};
+use iced::{Element, Length, Subscription};
+use ip_checker_logic::exam::{Exam, ExamAttempt, ExamResult, FinishReason};
+use ip_checker_logic::{Field, InputIpModel, Validator};
+
+use crate::widgets::{checked_input, wrong};
+
+// Messages handled while an exam is loaded
+#[derive(Debug, Clone)]
+pub enum ExamMessage {
+    PasswordInput(String),     // Update the package password
+    Unlock,                    // Decrypt the exam package
+    StudentInput(String),      // Update the student's name
+    Start,                     // Start the exam and its timer
+    FieldInput(Field, String), // Update an answer field
+    Submit,                    // Submit the current exercise
+    Previous,                  // Go to the previous exercise
+    Next,                      // Go to the next exercise
+    Finish,                    // Hand in the exam early
+    Tick,                      // Timer update
+}
+
+enum Stage {
+    Locked {
+        package: Vec<u8>,
+        password: String,
+        error: Option<String>,
+    },
+    Welcome {
+        exam: Exam,
+        student: String,
+        error: Option<String>,
+    },
+    Running {
+        attempt: ExamAttempt,
+        drafts: Vec<InputIpModel>,
+        feedback: Vec<Option<Validator>>,
+        current: usize,
+        deadline: Instant,
+    },
+    Finished {
+        result: ExamResult,
+        saved: Result<PathBuf, String>,
+        show_score: bool,
+    },
+}
+
+// A loaded exam file and the student's progress through it
+pub struct ExamSession {
+    path: PathBuf,
+    stage: Stage,
+}
+
+impl ExamSession {
+    pub fn new(path: PathBuf, exam: Exam) -> Self {
+        Self {
+            path,
+            stage: Stage::Welcome {
+                exam,
+                student: String::new(),
+                error: None,
+            },
+        }
+    }
+
+    // An encrypted exam that is only readable once the password is announced
+    pub fn locked(path: PathBuf, package: Vec<u8>) -> Self {
+        Self {
+            path,
+            stage: Stage::Locked {
+                package,
+                password: String::new(),
+                error: None,
+            },
+        }
+    }
+
+    pub fn update(&mut self, message: ExamMessage) {
+        match (&mut self.stage, message) {
+            (Stage::Locked { password, .. }, ExamMessage::PasswordInput(value)) => {
+                *password = value
+            }
+            (
+                Stage::Locked {
+                    package,
+                    password,
+                    error,
+                },
+                ExamMessage::Unlock,
+            ) => {
+                let unlocked = Exam::from_package(package, password).and_then(|exam| {
+                    exam.exercises()?;
+                    exam.signing_key()?;
+                    Ok(exam)
+                });
+                match unlocked {
+                    Ok(exam) => *self = Self::new(self.path.clone(), exam),
+                    Err(e) => *error = Some(e.to_string()),
+                }
+            }
+            (Stage::Welcome { student, .. }, ExamMessage::StudentInput(value)) => *student = value,
+            (
+                Stage::Welcome {
+                    exam,
+                    student,
+                    error,
+                },
+                ExamMessage::Start,
+            ) => {
+                if student.trim().is_empty() {
+                    *error = Some("Please enter your name".to_string());
+                    return;
+                }
+                match ExamAttempt::start(exam.clone(), student.trim().to_string()) {
+                    Ok(attempt) => {
+                        let count = attempt.exercises.len();
+                        let limit = Duration::from_secs(exam.time_limit_minutes as u64 * 60);
+                        self.stage = Stage::Running {
+                            attempt,
+                            drafts: vec![InputIpModel::default(); count],
+                            feedback: vec![None; count],
+                            current: 0,
+                            deadline: Instant::now() + limit,
+                        };
+                    }
+                    Err(e) => *error = Some(e.to_string()),
+                }
+            }
+            (
+                Stage::Running {
+                    attempt,
+                    drafts,
+                    current,
+                    ..
+                },
+                ExamMessage::FieldInput(field, value),
+            ) if attempt.can_submit(*current) => drafts[*current].set(field, value),
+            (
+                Stage::Running {
+                    attempt,
+                    drafts,
+                    feedback,
+                    current,
+                    ..
+                },
+                ExamMessage::Submit,
+            ) => {
+                let accepted = attempt.submit(*current, drafts[*current].clone());
+                // Feedback after submitting counts as a hint
+                if accepted && attempt.exam.hints {
+                    feedback[*current] =
+                        Some(attempt.exercises[*current].validate(&drafts[*current]));
+                }
+            }
+            (Stage::Running { current, .. }, ExamMessage::Previous) => {
+                *current = current.saturating_sub(1);
+            }
+            (
+                Stage::Running {
+                    attempt, current, ..
+                },
+                ExamMessage::Next,
+            ) => {
+                *current = (*current + 1).min(attempt.exercises.len() - 1);
+            }
+            (Stage::Running { .. }, ExamMessage::Finish) => self.finish(FinishReason::Submitted),
+            (Stage::Running { deadline, .. }, ExamMessage::Tick) if Instant::now() >= *deadline => {
+                self.finish(FinishReason::TimeUp)
+            }
+            _ => (),
+        }
+    }
+
+    fn finish(&mut self, reason: FinishReason) {
+        if let Stage::Running { attempt, .. } = &self.stage {
+            let result = attempt.finish(reason);
+            let saved = self.save_result(&result);
+            self.stage = Stage::Finished {
+                result,
+                saved,
+                show_score: attempt.exam.hints,
+            };
+        }
+    }
+
+    // Results are written next to the exam file
+    fn save_result(&self, result: &ExamResult) -> Result<PathBuf, String> {
+        let path = result_path(&self.path, &result.student);
+        let content = result.to_toml().map_err(|e| e.to_string())?;
+        std::fs::write(&path, content).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+
+    pub fn subscription(&self) -> Subscription<ExamMessage> {
+        match self.stage {
+            Stage::Running { .. } => {
+                iced::time::every(Duration::from_millis(500)).map(|_| ExamMessage::Tick)
+            }
+            _ => Subscription::none(),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, ExamMessage> {
+        match &self.stage {
+            Stage::Locked {
+                password, error, ..
+            } => {
+                let mut content = column![
+                    text("This exam is locked"),
+                    text_input("Password", password)
+                        .secure(true)
+                        .on_input(ExamMessage::PasswordInput)
+                        .on_submit(ExamMessage::Unlock)
+                        .padding(5),
+                    button("Unlock").on_press(ExamMessage::Unlock),
+                ]
+                .spacing(10);
+                if let Some(error) = error {
+                    content = content.push(wrong(error));
+                }
+                content.into()
+            }
+            Stage::Welcome {
+                exam,
+                student,
+                error,
+            } => {
+                let mut content = column![
+                    text(&exam.title).size(24),
+                    text(format!(
+                        "Time limit: {} minutes, {} attempt(s) per exercise",
+                        exam.time_limit_minutes, exam.attempts
+                    )),
+                    text_input("Your name", student)
+                        .on_input(ExamMessage::StudentInput)
+                        .on_submit(ExamMessage::Start)
+                        .padding(5),
+                    button("Start exam").on_press(ExamMessage::Start),
+                ]
+                .spacing(10);
+                if let Some(error) = error {
+                    content = content.push(wrong(error));
+                }
+                content.into()
+            }
+            Stage::Running {
+                attempt,
+                drafts,
+                feedback,
+                current,
+                deadline,
+            } => {
+                let remaining = deadline.saturating_duration_since(Instant::now()).as_secs();
+                let model = &attempt.exercises[*current];
+                let editable = attempt.can_submit(*current);
+                let feedback = feedback[*current].as_ref();
+
+                let mut fields = column![].spacing(5);
+                for field in Field::ALL {
+                    let mut input = checked_input(
+                        field.label(),
+                        &drafts[*current].get(field),
+                        feedback.map(|feedback| feedback.is_correct(field)),
+                    );
+                    if editable {
+                        input = input.on_input(move |value| ExamMessage::FieldInput(field, value));
+                    }
+                    fields = fields.push(input);
+                }
+
+                let submit =
+                    button("Submit answer").on_press_maybe(editable.then_some(ExamMessage::Submit));
+                let previous = button("Previous")
+                    .on_press_maybe((*current > 0).then_some(ExamMessage::Previous));
+                let next = button("Next").on_press_maybe(
+                    (*current + 1 < attempt.exercises.len()).then_some(ExamMessage::Next),
+                );
+
+                column![
+                    row![
+                        text(format!(
+                            "Exercise {} of {}",
+                            current + 1,
+                            attempt.exercises.len()
+                        ))
+                        .width(Length::Fill),
+                        text(format!("{:02}:{:02}", remaining / 60, remaining % 60)),
+                    ],
+                    text(format!("IP: {}", model.cidr())),
+                    fields,
+                    text(format!(
+                        "Attempts used: {} of {}",
+                        attempt.attempts[*current], attempt.exam.attempts
+                    )),
+                    row![previous, submit, next].spacing(10),
+                    button("Finish exam").on_press(ExamMessage::Finish),
+                ]
+                .spacing(10)
+                .into()
+            }
+            Stage::Finished {
+                result,
+                saved,
+                show_score,
+            } => {
+                let reason = match result.reason {
+                    FinishReason::Submitted => "The exam has been handed in.",
+                    FinishReason::TimeUp => "Time is up, the exam has been handed in.",
+                };
+                let saved = match saved {
+                    Ok(path) => format!("Result saved to {}", path.display()),
+                    Err(e) => format!("Failed to save result: {}", e),
+                };
+                let mut content = column![text(reason), text(saved)].spacing(10);
+                if *show_score {
+                    content = content.push(text(format!(
+                        "Score: {} of {}",
+                        result.points, result.max_points
+                    )));
+                }
+                content.into()
+            }
+        }
+    }
+}
+
+fn result_path(exam_path: &Path, student: &str) -> PathBuf {
+    let stem = exam_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "exam".to_string());
+    let student: String = student
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    exam_path.with_file_name(format!("{}-{}-result.toml", stem, student))
+}
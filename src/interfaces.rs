@@ -0,0 +1,183 @@
+use iced::widget::{button, column, pick_list, row, text, text_input};
+use iced::{Element, Length};
+use ip_checker_logic::interfaces::{self, LocalAddress};
+use ip_checker_logic::{Field, InputIpModel, IpModel, Validator};
+
+use crate::widgets::{checked_input, wrong};
+
+// Messages of the exercises with the addresses of this machine
+#[derive(Debug, Clone)]
+pub enum InterfaceMessage {
+    Select(LocalAddress),    // Pick one of the local addresses
+    Reload,                  // Read the interfaces again
+    Input(Field, String),    // Update the answer of a field
+    Check,                   // Check all fields
+    Calculator(bool),        // Switch between exercise and calculator
+    CalculatorInput(String), // Update the address of the calculator
+}
+
+// Compute the subnet facts of your own interfaces, or look them up in the calculator
+pub struct InterfacePractice {
+    addresses: Result<Vec<LocalAddress>, String>,
+    selected: Option<LocalAddress>,
+    input: InputIpModel,
+    valid: Option<Validator>,
+    calculator: bool,
+    calculator_input: String,
+}
+
+impl InterfacePractice {
+    pub fn new() -> Self {
+        let mut practice = Self {
+            addresses: Ok(Vec::new()),
+            selected: None,
+            input: InputIpModel::default(),
+            valid: None,
+            calculator: false,
+            calculator_input: String::new(),
+        };
+        practice.reload();
+        practice
+    }
+
+    fn reload(&mut self) {
+        self.addresses = interfaces::read_local()
+            .map(interfaces::practice_addresses)
+            .map_err(|error| error.to_string());
+        let first = self
+            .addresses
+            .as_ref()
+            .ok()
+            .and_then(|a| a.first())
+            .cloned();
+        match first {
+            Some(address) => self.select(address),
+            None => self.selected = None,
+        }
+    }
+
+    fn select(&mut self, address: LocalAddress) {
+        self.calculator_input = address.cidr();
+        self.selected = Some(address);
+        self.input = InputIpModel::default();
+        self.valid = None;
+    }
+
+    fn model(&self) -> Option<IpModel> {
+        self.selected.as_ref().and_then(LocalAddress::model)
+    }
+
+    pub fn update(&mut self, message: InterfaceMessage) {
+        match message {
+            InterfaceMessage::Select(address) => self.select(address),
+            InterfaceMessage::Reload => self.reload(),
+            InterfaceMessage::Input(field, value) => self.input.set(field, value),
+            InterfaceMessage::Check => {
+                self.valid = self.model().map(|model| model.validate(&self.input))
+            }
+            InterfaceMessage::Calculator(calculator) => self.calculator = calculator,
+            InterfaceMessage::CalculatorInput(value) => self.calculator_input = value,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, InterfaceMessage> {
+        let addresses = match &self.addresses {
+            Ok(addresses) if addresses.is_empty() => {
+                return column![
+                    text("No interface with an IPv4 subnet found"),
+                    button("Reload").on_press(InterfaceMessage::Reload),
+                ]
+                .spacing(10)
+                .into();
+            }
+            Ok(addresses) => addresses.clone(),
+            Err(error) => {
+                return column![
+                    wrong(error.clone()),
+                    button("Reload").on_press(InterfaceMessage::Reload),
+                ]
+                .spacing(10)
+                .into();
+            }
+        };
+
+        let header = column![
+            row![
+                pick_list(addresses, self.selected.clone(), InterfaceMessage::Select)
+                    .width(Length::Fill),
+                button("Reload").on_press(InterfaceMessage::Reload),
+            ]
+            .spacing(5),
+            row![
+                button("Exercise").on_press_maybe(
+                    self.calculator
+                        .then_some(InterfaceMessage::Calculator(false))
+                ),
+                button("Calculator").on_press_maybe(
+                    (!self.calculator).then_some(InterfaceMessage::Calculator(true))
+                ),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10);
+
+        let content = if self.calculator {
+            self.calculator_view()
+        } else {
+            self.exercise_view()
+        };
+        column![header, content].spacing(10).into()
+    }
+
+    fn exercise_view(&self) -> Element<'_, InterfaceMessage> {
+        let Some(selected) = &self.selected else {
+            return column![].into();
+        };
+        let mut fields = column![].spacing(5);
+        for field in Field::ALL {
+            let valid = self.valid.as_ref().map(|valid| valid.is_correct(field));
+            fields = fields.push(
+                checked_input(field.label(), &self.input.get(field), valid)
+                    .on_input(move |value| InterfaceMessage::Input(field, value)),
+            );
+        }
+        column![
+            text(format!(
+                "Your interface {} has the address {}. Compute the facts of its subnet.",
+                selected.interface,
+                selected.cidr()
+            )),
+            fields,
+            button("Check").on_press(InterfaceMessage::Check),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn calculator_view(&self) -> Element<'_, InterfaceMessage> {
+        let input = text_input("IP address/prefix", &self.calculator_input)
+            .on_input(InterfaceMessage::CalculatorInput)
+            .padding(5);
+        let Some(model) = IpModel::from_cidr(&self.calculator_input) else {
+            return column![
+                input,
+                text("Enter an address like 192.168.1.10/24, the prefix between 1 and 30")
+            ]
+            .spacing(10)
+            .into();
+        };
+
+        let mut facts = column![].spacing(5);
+        for field in Field::ALL {
+            facts = facts.push(row![
+                text(field.label()).width(Length::FillPortion(1)),
+                text(model.answer(field)).width(Length::FillPortion(1)),
+            ]);
+        }
+        facts = facts.push(row![
+            text("Wildcard Mask").width(Length::FillPortion(1)),
+            text(model.wildcard_mask().to_string()).width(Length::FillPortion(1)),
+        ]);
+        column![input, facts].spacing(10).into()
+    }
+}
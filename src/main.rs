@@ -1,12 +1,168 @@
 use iced::widget::{button, column, container, pick_list, row, text, text_input};
-use iced::{Color, Element, Length, Size, Task, Theme};
+use iced::{Color, Element, Length, Size, Subscription, Task, Theme};
 use iced_anim::{Animation, Spring, SpringEvent};
 use ip_checker_logic::*;
 
+mod acl;
+mod classful;
+mod classroom;
+mod cli;
+mod duel;
+mod exam;
+mod interfaces;
+mod multiple_choice;
+mod nth;
+mod plan;
+mod routing;
+mod subnetting;
+mod topology;
+mod troubleshooting;
+mod widgets;
+
+use acl::{AclMessage, AclPractice};
+use classful::{ClassfulMessage, ClassfulPractice};
+use classroom::{ClassroomHost, ClassroomMessage, ClassroomStudent};
+use duel::{DuelMessage, DuelSession};
+use exam::{ExamMessage, ExamSession};
+use interfaces::{InterfaceMessage, InterfacePractice};
+use multiple_choice::{ChoiceMessage, MultipleChoice};
+use nth::{NthMessage, NthPractice};
+use plan::{PlanEditor, PlanMessage};
+use routing::{RoutingMessage, RoutingPractice};
+use subnetting::{SplitterMessage, SubnetSplitter};
+use topology::{TopologyMessage, TopologyPractice};
+use troubleshooting::{TroubleshootingMessage, TroubleshootingPractice};
+
 // Main entry point of the application
 pub fn main() -> iced::Result {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        return run(Mode::Practice);
+    }
+
+    // Some commands start the GUI in a special mode, the others are command line tools
+    let result = match cli::gui_mode(&args) {
+        Ok(Some(mode)) => return run(mode),
+        Ok(None) => cli::run(&args),
+        Err(error) => Err(error),
+    };
+    if let Err(error) = result {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// What the window is used for, practice kinds are picked in the GUI, the others on the command line
+enum Mode {
+    Practice,                                      // Free practice with generated IPs
+    MultipleChoice(Box<MultipleChoice>),           // Practice with multiple choice questions
+    Acl(Box<AclPractice>),                         // Practice wildcard masks and ACL entries
+    Routing(Box<RoutingPractice>),                 // Practice longest prefix matching
+    Splitter(Box<SubnetSplitter>),                 // Split a network into equal subnets
+    Nth(Box<NthPractice>),                         // Practice nth subnet and nth host questions
+    Classful(Box<ClassfulPractice>),               // Practice classful subnet counting
+    Troubleshooting(Box<TroubleshootingPractice>), // Find misconfigured hosts
+    Topology(Box<TopologyPractice>),               // Address a routed topology
+    Plan(Box<PlanEditor>),                         // Edit an address plan
+    Interfaces(Box<InterfacePractice>),            // Practice with the addresses of this machine
+    Exam(Box<ExamSession>),                        // Locked down exam
+    ClassroomHost(ClassroomHost),                  // Teacher dashboard
+    ClassroomStudent(ClassroomStudent),            // Practice with exercises from the teacher
+    Duel(DuelSession),                             // Race against another instance
+}
+
+// Kinds of practice that can be picked in the GUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PracticeKind {
+    Fields,
+    MultipleChoice,
+    Acl,
+    Routing,
+    Splitter,
+    Nth,
+    Classful,
+    Troubleshooting,
+    Topology,
+    Plan,
+    Interfaces,
+}
+
+impl PracticeKind {
+    const ALL: [PracticeKind; 11] = [
+        PracticeKind::Fields,
+        PracticeKind::MultipleChoice,
+        PracticeKind::Acl,
+        PracticeKind::Routing,
+        PracticeKind::Splitter,
+        PracticeKind::Nth,
+        PracticeKind::Classful,
+        PracticeKind::Troubleshooting,
+        PracticeKind::Topology,
+        PracticeKind::Plan,
+        PracticeKind::Interfaces,
+    ];
+}
+
+impl std::fmt::Display for PracticeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PracticeKind::Fields => "Subnet Fields",
+            PracticeKind::MultipleChoice => "Multiple Choice",
+            PracticeKind::Acl => "Wildcard Masks",
+            PracticeKind::Routing => "Routing Tables",
+            PracticeKind::Splitter => "Subnet Splitter",
+            PracticeKind::Nth => "Nth Subnet / Host",
+            PracticeKind::Classful => "Classful Subnetting",
+            PracticeKind::Troubleshooting => "Troubleshooting",
+            PracticeKind::Topology => "Topology Addressing",
+            PracticeKind::Plan => "Address Plan",
+            PracticeKind::Interfaces => "My Interfaces",
+        })
+    }
+}
+
+impl Mode {
+    // The practice kind if this mode is one of them
+    fn practice_kind(&self) -> Option<PracticeKind> {
+        match self {
+            Mode::Practice => Some(PracticeKind::Fields),
+            Mode::MultipleChoice(_) => Some(PracticeKind::MultipleChoice),
+            Mode::Acl(_) => Some(PracticeKind::Acl),
+            Mode::Routing(_) => Some(PracticeKind::Routing),
+            Mode::Splitter(_) => Some(PracticeKind::Splitter),
+            Mode::Nth(_) => Some(PracticeKind::Nth),
+            Mode::Classful(_) => Some(PracticeKind::Classful),
+            Mode::Troubleshooting(_) => Some(PracticeKind::Troubleshooting),
+            Mode::Topology(_) => Some(PracticeKind::Topology),
+            Mode::Plan(_) => Some(PracticeKind::Plan),
+            Mode::Interfaces(_) => Some(PracticeKind::Interfaces),
+            _ => None,
+        }
+    }
+
+    fn window_size(&self) -> Size {
+        match self {
+            Mode::ClassroomHost(_) => Size {
+                width: 600.,
+                height: 500.,
+            },
+            Mode::Plan(_) => Size {
+                width: 1000.,
+                height: 600.,
+            },
+            _ => Size {
+                width: 300.,
+                height: 400.,
+            },
+        }
+    }
+}
+
+fn run(mode: Mode) -> iced::Result {
     // Initialize the iced application with window settings and theme
     iced::application(IpChecker::title, IpChecker::update, IpChecker::view)
+        .subscription(IpChecker::subscription)
         .window(iced::window::Settings {
             min_size: Some(Size {
                 width: 300.,
@@ -14,12 +170,9 @@ pub fn main() -> iced::Result {
             }),
             ..Default::default()
         })
-        .window_size(Size {
-            width: 300.,
-            height: 400.,
-        })
+        .window_size(mode.window_size())
         .theme(|state| state.theme.value().clone())
-        .run_with(IpChecker::new)
+        .run_with(move || IpChecker::new(mode))
 }
 
 // Main application state struct
@@ -29,29 +182,46 @@ struct IpChecker {
     user_input: InputIpModel, // User's input values
     valid: Validator,         // Validation results
     theme: Spring<Theme>,     // Animated theme switcher
+    mode: Mode,               // Practice, exam or classroom
 }
 
 // Enum defining all possible messages in the application
 #[derive(Debug, Clone)]
 enum Message {
-    GenerateIp,                      // Generate new random IP
-    CheckIp,                         // Validate user input
-    MaskInput(String),               // Update subnet mask input
-    NetworkAddressInput(String),     // Update network address input
-    BroadcastAddressInput(String),   // Update broadcast address input
-    FirstHostInput(String),          // Update first host input
-    LastHostInput(String),           // Update last host input
-    PossibleHostsInput(String),      // Update possible hosts input
-    ChangeTheme(SpringEvent<Theme>), // Handle theme change animation
+    GenerateIp,                              // Generate new random IP
+    CheckIp,                                 // Validate user input
+    MaskInput(String),                       // Update subnet mask input
+    NetworkAddressInput(String),             // Update network address input
+    BroadcastAddressInput(String),           // Update broadcast address input
+    FirstHostInput(String),                  // Update first host input
+    LastHostInput(String),                   // Update last host input
+    PossibleHostsInput(String),              // Update possible hosts input
+    ChangeTheme(SpringEvent<Theme>),         // Handle theme change animation
+    Exam(ExamMessage),                       // Exam mode interaction
+    Classroom(ClassroomMessage),             // Classroom host or student interaction
+    Duel(DuelMessage),                       // Duel interaction
+    ChangePractice(PracticeKind),            // Switch to another kind of practice
+    MultipleChoice(ChoiceMessage),           // Multiple choice interaction
+    Acl(AclMessage),                         // Wildcard mask exercise interaction
+    Routing(RoutingMessage),                 // Routing table exercise interaction
+    Splitter(SplitterMessage),               // Subnet splitter interaction
+    Nth(NthMessage),                         // Nth subnet and host exercise interaction
+    Classful(ClassfulMessage),               // Classful subnetting exercise interaction
+    Troubleshooting(TroubleshootingMessage), // Troubleshooting exercise interaction
+    Topology(TopologyMessage),               // Topology exercise interaction
+    Plan(PlanMessage),                       // Address plan editor interaction
+    Interfaces(InterfaceMessage),            // Local interface exercise interaction
 }
 
 impl IpChecker {
     // Initialize the application state
-    fn new() -> (Self, Task<Message>) {
+    fn new(mode: Mode) -> (Self, Task<Message>) {
         let mut calculator = IpCalculator::new();
         (
             Self {
-                ip: Some(calculator.random_model()),
+                // Only free practice starts with a generated exercise
+                ip: matches!(mode, Mode::Practice).then(|| calculator.random_model()),
+                mode,
                 calculator,
                 theme: Spring::new(Theme::Dracula),
                 user_input: InputIpModel::default(),
@@ -70,11 +240,18 @@ impl IpChecker {
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::GenerateIp => {
-                self.ip = Some(self.calculator.random_model());
+                if matches!(self.mode, Mode::Practice) {
+                    self.ip = Some(self.calculator.random_model());
+                }
             }
             Message::CheckIp => {
                 if let Some(ip) = &self.ip {
-                    self.valid = ip.validate(&self.user_input)
+                    self.valid = ip.validate(&self.user_input);
+                    match &mut self.mode {
+                        Mode::ClassroomStudent(student) => student.submit(&self.valid),
+                        Mode::Duel(duel) => duel.submit(&self.valid),
+                        _ => (),
+                    }
                 }
             }
             Message::MaskInput(value) => self.user_input.mask = value,
@@ -82,22 +259,197 @@ impl IpChecker {
             Message::BroadcastAddressInput(value) => self.user_input.broadcast_address = value,
             Message::FirstHostInput(value) => self.user_input.first_host = value,
             Message::LastHostInput(value) => self.user_input.last_host = value,
-            Message::PossibleHostsInput(value) => {
-                self.user_input.possible_hosts = if value.is_empty() {
-                    None
-                } else {
-                    value.parse().ok()
+            Message::PossibleHostsInput(value) => self.user_input.set(Field::PossibleHosts, value),
+            Message::ChangeTheme(event) => self.theme.update(event),
+            Message::Exam(message) => {
+                if let Mode::Exam(exam) = &mut self.mode {
+                    exam.update(message)
+                }
+            }
+            Message::Classroom(message) => match &mut self.mode {
+                Mode::ClassroomHost(host) => host.update(message),
+                Mode::ClassroomStudent(student) => {
+                    if let Some(model) = student.update(message) {
+                        self.ip = Some(model);
+                        self.user_input = InputIpModel::default();
+                        self.valid = Validator::default();
+                    }
+                }
+                _ => (),
+            },
+            Message::ChangePractice(kind) => {
+                self.mode = match kind {
+                    PracticeKind::Fields => Mode::Practice,
+                    PracticeKind::MultipleChoice => {
+                        Mode::MultipleChoice(Box::new(MultipleChoice::new()))
+                    }
+                    PracticeKind::Acl => Mode::Acl(Box::new(AclPractice::new())),
+                    PracticeKind::Routing => Mode::Routing(Box::new(RoutingPractice::new())),
+                    // The splitter starts with the network of the current exercise
+                    PracticeKind::Splitter => Mode::Splitter(Box::new(SubnetSplitter::new(
+                        self.ip.as_ref().map(IpModel::network),
+                    ))),
+                    PracticeKind::Nth => Mode::Nth(Box::new(NthPractice::new())),
+                    PracticeKind::Classful => Mode::Classful(Box::new(ClassfulPractice::new())),
+                    PracticeKind::Troubleshooting => {
+                        Mode::Troubleshooting(Box::new(TroubleshootingPractice::new()))
+                    }
+                    PracticeKind::Topology => Mode::Topology(Box::new(TopologyPractice::new())),
+                    PracticeKind::Plan => Mode::Plan(Box::new(PlanEditor::new(None))),
+                    PracticeKind::Interfaces => {
+                        Mode::Interfaces(Box::new(InterfacePractice::new()))
+                    }
+                };
+                if matches!(self.mode, Mode::Practice) && self.ip.is_none() {
+                    self.ip = Some(self.calculator.random_model());
+                }
+            }
+            Message::MultipleChoice(message) => {
+                if let Mode::MultipleChoice(choice) = &mut self.mode {
+                    choice.update(message)
+                }
+            }
+            Message::Acl(message) => {
+                if let Mode::Acl(acl) = &mut self.mode {
+                    acl.update(message)
+                }
+            }
+            Message::Routing(message) => {
+                if let Mode::Routing(routing) = &mut self.mode {
+                    routing.update(message)
+                }
+            }
+            Message::Splitter(message) => {
+                if let Mode::Splitter(splitter) = &mut self.mode {
+                    splitter.update(message)
+                }
+            }
+            Message::Nth(message) => {
+                if let Mode::Nth(nth) = &mut self.mode {
+                    nth.update(message)
+                }
+            }
+            Message::Classful(message) => {
+                if let Mode::Classful(classful) = &mut self.mode {
+                    classful.update(message)
+                }
+            }
+            Message::Troubleshooting(message) => {
+                if let Mode::Troubleshooting(troubleshooting) = &mut self.mode {
+                    troubleshooting.update(message)
+                }
+            }
+            Message::Topology(message) => {
+                if let Mode::Topology(topology) = &mut self.mode {
+                    topology.update(message)
+                }
+            }
+            Message::Plan(message) => {
+                if let Mode::Plan(plan) = &mut self.mode {
+                    plan.update(message)
+                }
+            }
+            Message::Interfaces(message) => {
+                if let Mode::Interfaces(interfaces) = &mut self.mode {
+                    interfaces.update(message)
+                }
+            }
+            Message::Duel(message) => {
+                if let Mode::Duel(duel) = &mut self.mode {
+                    if let Some(model) = duel.update(message) {
+                        self.ip = Some(model);
+                        self.user_input = InputIpModel::default();
+                        self.valid = Validator::default();
+                    }
                 }
             }
-            Message::ChangeTheme(event) => self.theme.update(event),
         }
         Task::none()
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        match &self.mode {
+            Mode::Exam(exam) => exam.subscription().map(Message::Exam),
+            Mode::ClassroomHost(_) | Mode::ClassroomStudent(_) => {
+                classroom::subscription().map(Message::Classroom)
+            }
+            Mode::Duel(_) => duel::subscription().map(Message::Duel),
+            Mode::Practice
+            | Mode::MultipleChoice(_)
+            | Mode::Acl(_)
+            | Mode::Routing(_)
+            | Mode::Splitter(_)
+            | Mode::Nth(_)
+            | Mode::Classful(_)
+            | Mode::Troubleshooting(_)
+            | Mode::Topology(_)
+            | Mode::Plan(_)
+            | Mode::Interfaces(_) => Subscription::none(),
+        }
+    }
+
     // Render the application UI
-    fn view(&self) -> Element<Message> {
-        // Create buttons for generating IP and checking input
-        let generate_button = button("Generate IP").on_press(Message::GenerateIp);
+    fn view(&self) -> Element<'_, Message> {
+        // Theme picker
+        let theme_picker = pick_list(Theme::ALL, Some(self.theme.target().clone()), |theme| {
+            Message::ChangeTheme(theme.into())
+        });
+
+        // Practice kinds can be switched, the other modes are fixed
+        let practice_picker = self
+            .mode
+            .practice_kind()
+            .map(|kind| pick_list(PracticeKind::ALL, Some(kind), Message::ChangePractice));
+
+        // Combine the pickers with the view of the current mode
+        let content = column![
+            theme_picker,
+            column(practice_picker.map(Element::from)),
+            match &self.mode {
+                Mode::Practice | Mode::ClassroomStudent(_) => self.practice_view(),
+                Mode::MultipleChoice(choice) => choice.view().map(Message::MultipleChoice),
+                Mode::Acl(acl) => acl.view().map(Message::Acl),
+                Mode::Routing(routing) => routing.view().map(Message::Routing),
+                Mode::Splitter(splitter) => splitter.view().map(Message::Splitter),
+                Mode::Nth(nth) => nth.view().map(Message::Nth),
+                Mode::Classful(classful) => classful.view().map(Message::Classful),
+                Mode::Troubleshooting(troubleshooting) => {
+                    troubleshooting.view().map(Message::Troubleshooting)
+                }
+                Mode::Topology(topology) => topology.view().map(Message::Topology),
+                Mode::Plan(plan) => plan.view().map(Message::Plan),
+                Mode::Interfaces(interfaces) => interfaces.view().map(Message::Interfaces),
+                Mode::Exam(exam) => exam.view().map(Message::Exam),
+                Mode::ClassroomHost(host) => host.view().map(Message::Classroom),
+                Mode::Duel(duel) => column![duel.view().map(Message::Duel), self.practice_view()]
+                    .spacing(10)
+                    .into(),
+            }
+        ]
+        .spacing(10)
+        .height(Length::Fill)
+        .padding(2);
+
+        // Apply theme animation and return final element
+        Animation::new(
+            &self.theme,
+            container(content)
+                .style(move |theme: &Theme| container::Style {
+                    background: Some(theme.palette().background.into()),
+                    ..Default::default()
+                })
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .on_update(Message::ChangeTheme)
+        .into()
+    }
+
+    // Exercise with input fields, used for practice and classroom students
+    fn practice_view(&self) -> Element<'_, Message> {
+        // Create buttons for generating IP and checking input, students get exercises from the teacher
+        let generate_button = button("Generate IP")
+            .on_press_maybe(matches!(self.mode, Mode::Practice).then_some(Message::GenerateIp));
         let check_button = button("Check IP").on_press(Message::CheckIp);
 
         // Create button container with layout
@@ -117,7 +469,11 @@ impl IpChecker {
             self.ip
                 .as_ref()
                 .map(|ip| ip.ip.to_string())
-                .unwrap_or("Press Generate IP to get an IP".to_string()),
+                .unwrap_or(match self.mode {
+                    Mode::ClassroomStudent(_) => "Waiting for the teacher".to_string(),
+                    Mode::Duel(_) => "Waiting for the first round".to_string(),
+                    _ => "Press Generate IP to get an IP".to_string(),
+                }),
             self.ip
                 .as_ref()
                 .map(|ip| ip.prefix.to_string().clone())
@@ -191,54 +547,24 @@ impl IpChecker {
                     style
                 })
                 .padding(5),
-            text_input(
-                "Possible Hosts",
-                &self
-                    .user_input
-                    .possible_hosts
-                    .map_or(String::new(), |n| n.to_string())
-            )
-            .on_input(Message::PossibleHostsInput)
-            .style(|theme: &Theme, status| {
-                let mut style = text_input::default(theme, status);
-                style.border.color = if self.valid.possible_hosts {
-                    Color::from_rgb8(0, 255, 0)
-                } else {
-                    Color::from_rgb8(255, 0, 0)
-                };
-                style.border.width = 2.0;
-                style
-            })
-            .padding(5),
+            text_input("Possible Hosts", &self.user_input.possible_hosts)
+                .on_input(Message::PossibleHostsInput)
+                .style(|theme: &Theme, status| {
+                    let mut style = text_input::default(theme, status);
+                    style.border.color = if self.valid.possible_hosts {
+                        Color::from_rgb8(0, 255, 0)
+                    } else {
+                        Color::from_rgb8(255, 0, 0)
+                    };
+                    style.border.width = 2.0;
+                    style
+                })
+                .padding(5),
         ]
         .spacing(5);
 
-        // Combine all elements into main content
-        let content = column![
-            // Theme picker
-            pick_list(Theme::ALL, Some(self.theme.target().clone()), |theme| {
-                Message::ChangeTheme(theme.into())
-            }),
-            button_container,
-            ip_text,
-            input_fields
-        ]
-        .spacing(10)
-        .height(Length::Fill)
-        .padding(2);
-
-        // Apply theme animation and return final element
-        Animation::new(
-            &self.theme,
-            container(content)
-                .style(move |theme: &Theme| container::Style {
-                    background: Some(theme.palette().background.into()),
-                    ..Default::default()
-                })
-                .width(Length::Fill)
-                .height(Length::Fill),
-        )
-        .on_update(Message::ChangeTheme)
-        .into()
+        column![button_container, ip_text, input_fields]
+            .spacing(10)
+            .into()
     }
 }
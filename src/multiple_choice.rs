@@ -0,0 +1,87 @@
+use iced::widget::{button, column, radio, row, text};
+use iced::Element;
+use ip_checker_logic::multiple_choice::Question;
+use ip_checker_logic::IpCalculator;
+
+use crate::widgets::{feedback, Score};
+
+// Messages of the multiple choice exercise
+#[derive(Debug, Clone)]
+pub enum ChoiceMessage {
+    Select(usize), // Pick one of the options
+    Check,         // Check the picked option
+    Next,          // Generate the next question
+}
+
+// Multiple choice practice for beginners
+pub struct MultipleChoice {
+    calculator: IpCalculator,
+    question: Question,
+    selected: Option<usize>,
+    checked: bool,
+    score: Score,
+}
+
+impl MultipleChoice {
+    pub fn new() -> Self {
+        let mut calculator = IpCalculator::new();
+        Self {
+            question: calculator.random_question(),
+            calculator,
+            selected: None,
+            checked: false,
+            score: Score::default(),
+        }
+    }
+
+    pub fn update(&mut self, message: ChoiceMessage) {
+        match message {
+            ChoiceMessage::Select(choice) if !self.checked => self.selected = Some(choice),
+            ChoiceMessage::Select(_) => (),
+            ChoiceMessage::Check => {
+                if let (Some(choice), false) = (self.selected, self.checked) {
+                    self.checked = true;
+                    self.score.record(self.question.is_correct(choice));
+                }
+            }
+            ChoiceMessage::Next => {
+                self.question = self.calculator.random_question();
+                self.selected = None;
+                self.checked = false;
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, ChoiceMessage> {
+        let mut options = column![].spacing(8);
+        for (index, option) in self.question.options.iter().enumerate() {
+            options = options.push(radio(
+                option.as_str(),
+                index,
+                self.selected,
+                ChoiceMessage::Select,
+            ));
+        }
+
+        let result = self
+            .selected
+            .filter(|_| self.checked)
+            .map(|choice| self.question.is_correct(choice));
+
+        column![
+            row![
+                button("Check").on_press_maybe(
+                    (self.selected.is_some() && !self.checked).then_some(ChoiceMessage::Check)
+                ),
+                button("Next question").on_press(ChoiceMessage::Next),
+            ]
+            .spacing(10),
+            text(&self.question.text),
+            options,
+            feedback(result, &self.question.options[self.question.correct]),
+            self.score.view(),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
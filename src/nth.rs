@@ -0,0 +1,84 @@
+use iced::widget::{button, checkbox, column, row, text, text_input};
+use iced::Element;
+use ip_checker_logic::nth::NthExercise;
+use ip_checker_logic::IpCalculator;
+
+use crate::widgets::{feedback, Score};
+
+// Messages of the nth subnet and nth host exercises
+#[derive(Debug, Clone)]
+pub enum NthMessage {
+    Input(String),    // Update the answer
+    Check,            // Check the answer
+    Next,             // Generate the next exercise
+    SubnetZero(bool), // Count subnet zero or not
+}
+
+// Practice for "what is the nth subnet / host" questions
+pub struct NthPractice {
+    calculator: IpCalculator,
+    subnet_zero: bool,
+    exercise: NthExercise,
+    input: String,
+    result: Option<bool>,
+    score: Score,
+}
+
+impl NthPractice {
+    pub fn new() -> Self {
+        let mut calculator = IpCalculator::new();
+        Self {
+            exercise: calculator.random_nth_exercise(true),
+            calculator,
+            subnet_zero: true,
+            input: String::new(),
+            result: None,
+            score: Score::default(),
+        }
+    }
+
+    pub fn update(&mut self, message: NthMessage) {
+        match message {
+            NthMessage::Input(value) => self.input = value,
+            NthMessage::Check => {
+                if self.result.is_none() {
+                    let correct = self.exercise.check(&self.input);
+                    self.result = Some(correct);
+                    self.score.record(correct);
+                }
+            }
+            NthMessage::Next => self.next_exercise(),
+            // The current question was worded for the other rule
+            NthMessage::SubnetZero(subnet_zero) => {
+                self.subnet_zero = subnet_zero;
+                self.next_exercise();
+            }
+        }
+    }
+
+    fn next_exercise(&mut self) {
+        self.exercise = self.calculator.random_nth_exercise(self.subnet_zero);
+        self.input.clear();
+        self.result = None;
+    }
+
+    pub fn view(&self) -> Element<'_, NthMessage> {
+        column![
+            row![
+                button("Check").on_press_maybe(self.result.is_none().then_some(NthMessage::Check)),
+                button("Next exercise").on_press(NthMessage::Next),
+            ]
+            .spacing(10),
+            checkbox("Count subnet zero", self.subnet_zero).on_toggle(NthMessage::SubnetZero),
+            text(self.exercise.text()),
+            text_input("Answer", &self.input)
+                .on_input(NthMessage::Input)
+                .on_submit(NthMessage::Check)
+                .padding(5),
+            feedback(self.result, self.exercise.answer()),
+            self.score.view(),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
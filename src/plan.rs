@@ -0,0 +1,449 @@
+use std::path::{Path, PathBuf};
+
+use iced::widget::{button, column, row, scrollable, text, text_input};
+use iced::{Color, Element, Length};
+use ip_checker_logic::cidr::Cidr;
+use ip_checker_logic::plan::{is_valid_mac, AddressPlan, PlanFormat, PlannedSubnet, Reservation};
+use ip_checker_logic::IpAddress;
+
+use crate::widgets::{checked_input, wrong};
+
+// Messages of the address plan editor
+#[derive(Debug, Clone)]
+pub enum PlanMessage {
+    Path(String),                                     // Update the file path
+    Load,                                             // Load the plan from the file
+    Save,                                             // Save the plan to the file
+    Name(String),                                     // Update the plan name
+    Block(String),                                    // Update the parent block
+    Edit(usize, SubnetField, String),                 // Edit a field of a subnet
+    Remove(usize),                                    // Remove a subnet
+    Select(usize),                                    // Show the reservations of a subnet
+    NewName(String),                                  // Update the name of the next subnet
+    NewPrefix(String),                                // Update the size of the next subnet
+    Allocate,                                         // Add a subnet in free space
+    EditReservation(usize, ReservationField, String), // Edit a reservation of the selected subnet
+    AddReservation,                                   // Add a reservation to the selected subnet
+    RemoveReservation(usize),                         // Remove a reservation of the selected subnet
+}
+
+// Editable columns of the subnet table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubnetField {
+    Name,
+    Network,
+    Vlan,
+    Purpose,
+    Gateway,
+}
+
+impl SubnetField {
+    const ALL: [SubnetField; 5] = [
+        SubnetField::Name,
+        SubnetField::Network,
+        SubnetField::Vlan,
+        SubnetField::Purpose,
+        SubnetField::Gateway,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SubnetField::Name => "Name",
+            SubnetField::Network => "Network",
+            SubnetField::Vlan => "VLAN",
+            SubnetField::Purpose => "Purpose",
+            SubnetField::Gateway => "Gateway",
+        }
+    }
+
+    fn value(&self, subnet: &PlannedSubnet) -> String {
+        match self {
+            SubnetField::Name => subnet.name.clone(),
+            SubnetField::Network => subnet.network.to_string(),
+            SubnetField::Vlan => subnet.vlan.map_or(String::new(), |vlan| vlan.to_string()),
+            SubnetField::Purpose => subnet.purpose.clone(),
+            SubnetField::Gateway => subnet
+                .gateway
+                .as_ref()
+                .map_or(String::new(), IpAddress::as_string),
+        }
+    }
+
+    // Stores the value if it is valid, empty optional fields are cleared
+    fn apply(&self, subnet: &mut PlannedSubnet, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            SubnetField::Name => subnet.name = value.to_string(),
+            SubnetField::Purpose => subnet.purpose = value.to_string(),
+            SubnetField::Network => match value.parse() {
+                Ok(network) => subnet.network = network,
+                Err(_) => return false,
+            },
+            SubnetField::Vlan if value.is_empty() => subnet.vlan = None,
+            SubnetField::Vlan => match value.parse() {
+                Ok(vlan @ 1..=4094) => subnet.vlan = Some(vlan),
+                _ => return false,
+            },
+            SubnetField::Gateway if value.is_empty() => subnet.gateway = None,
+            SubnetField::Gateway => match value.parse() {
+                Ok(gateway) => subnet.gateway = Some(gateway),
+                Err(_) => return false,
+            },
+        }
+        true
+    }
+}
+
+// Editable columns of the reservation table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReservationField {
+    Name,
+    Address,
+    Mac,
+}
+
+impl ReservationField {
+    const ALL: [ReservationField; 3] = [
+        ReservationField::Name,
+        ReservationField::Address,
+        ReservationField::Mac,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ReservationField::Name => "Name",
+            ReservationField::Address => "Address",
+            ReservationField::Mac => "MAC",
+        }
+    }
+
+    fn value(&self, reservation: &Reservation) -> String {
+        match self {
+            ReservationField::Name => reservation.name.clone(),
+            ReservationField::Address => reservation.address.as_string(),
+            ReservationField::Mac => reservation.mac.clone().unwrap_or_default(),
+        }
+    }
+
+    fn apply(&self, reservation: &mut Reservation, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            ReservationField::Name => reservation.name = value.to_string(),
+            ReservationField::Address => match value.parse() {
+                Ok(address) => reservation.address = address,
+                Err(_) => return false,
+            },
+            ReservationField::Mac if value.is_empty() => reservation.mac = None,
+            ReservationField::Mac if is_valid_mac(value) => {
+                reservation.mac = Some(value.to_string())
+            }
+            ReservationField::Mac => return false,
+        }
+        true
+    }
+}
+
+// Text of the inputs, kept separately so invalid values can be corrected
+struct SubnetRow {
+    fields: Vec<String>,
+    reservations: Vec<Vec<String>>,
+}
+
+impl SubnetRow {
+    fn new(subnet: &PlannedSubnet) -> Self {
+        Self {
+            fields: SubnetField::ALL.iter().map(|f| f.value(subnet)).collect(),
+            reservations: subnet
+                .reservations
+                .iter()
+                .map(|r| ReservationField::ALL.iter().map(|f| f.value(r)).collect())
+                .collect(),
+        }
+    }
+}
+
+// Editor for an address plan file
+pub struct PlanEditor {
+    path: String,
+    status: Option<Result<String, String>>,
+    plan: AddressPlan,
+    block: String,
+    rows: Vec<SubnetRow>,
+    selected: Option<usize>,
+    new_name: String,
+    new_prefix: String,
+}
+
+impl PlanEditor {
+    // Starts with the plan in `path`, or an empty plan if there is none
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let plan = AddressPlan::new("New plan", "10.0.0.0/16".parse().unwrap());
+        let mut editor = Self {
+            path: String::new(),
+            status: None,
+            block: plan.block.to_string(),
+            rows: Vec::new(),
+            plan,
+            selected: None,
+            new_name: String::new(),
+            new_prefix: "/24".to_string(),
+        };
+        if let Some(path) = path {
+            editor.path = path.display().to_string();
+            if path.exists() {
+                editor.load();
+            }
+        }
+        editor
+    }
+
+    pub fn update(&mut self, message: PlanMessage) {
+        match message {
+            PlanMessage::Path(path) => self.path = path,
+            PlanMessage::Load => self.load(),
+            PlanMessage::Save => self.save(),
+            PlanMessage::Name(name) => self.plan.name = name,
+            PlanMessage::Block(block) => {
+                if let Ok(cidr) = block.trim().parse::<Cidr>() {
+                    self.plan.block = cidr;
+                }
+                self.block = block;
+            }
+            PlanMessage::Edit(index, field, value) => {
+                field.apply(&mut self.plan.subnets[index], &value);
+                self.rows[index].fields[field as usize] = value;
+            }
+            PlanMessage::Remove(index) => {
+                self.plan.subnets.remove(index);
+                self.rows.remove(index);
+                self.selected = None;
+            }
+            PlanMessage::Select(index) => self.selected = Some(index),
+            PlanMessage::NewName(name) => self.new_name = name,
+            PlanMessage::NewPrefix(prefix) => self.new_prefix = prefix,
+            PlanMessage::Allocate => {
+                let Some(prefix) = self.new_prefix_value() else {
+                    return;
+                };
+                let name = match self.new_name.trim() {
+                    "" => format!("Subnet {}", self.plan.subnets.len() + 1),
+                    name => name.to_string(),
+                };
+                self.status = Some(match self.plan.allocate(&name, prefix) {
+                    Ok(subnet) => Ok(format!("Allocated {} for {}", subnet.network, name)),
+                    Err(error) => Err(error.to_string()),
+                });
+                self.new_name.clear();
+                self.reset_rows();
+            }
+            PlanMessage::EditReservation(index, field, value) => {
+                if let Some(selected) = self.selected {
+                    let reservation = &mut self.plan.subnets[selected].reservations[index];
+                    field.apply(reservation, &value);
+                    self.rows[selected].reservations[index][field as usize] = value;
+                }
+            }
+            PlanMessage::AddReservation => {
+                if let Some(selected) = self.selected {
+                    let subnet = &mut self.plan.subnets[selected];
+                    // The next address after the gateway and the other reservations
+                    let used = subnet
+                        .gateway
+                        .iter()
+                        .chain(subnet.reservations.iter().map(|r| &r.address));
+                    let address = subnet
+                        .network
+                        .hosts()
+                        .find(|host| !used.clone().any(|used| used == host))
+                        .unwrap_or_else(|| subnet.network.nth_host(0).unwrap());
+                    let reservation = Reservation {
+                        name: format!("host{}", subnet.reservations.len() + 1),
+                        address,
+                        mac: None,
+                        description: String::new(),
+                    };
+                    self.rows[selected].reservations.push(
+                        ReservationField::ALL
+                            .iter()
+                            .map(|f| f.value(&reservation))
+                            .collect(),
+                    );
+                    subnet.reservations.push(reservation);
+                }
+            }
+            PlanMessage::RemoveReservation(index) => {
+                if let Some(selected) = self.selected {
+                    self.plan.subnets[selected].reservations.remove(index);
+                    self.rows[selected].reservations.remove(index);
+                }
+            }
+        }
+    }
+
+    // Accepts `/26` and `26`
+    fn new_prefix_value(&self) -> Option<u8> {
+        let prefix = self.new_prefix.trim().trim_start_matches('/');
+        prefix.parse().ok().filter(|prefix| *prefix <= 32)
+    }
+
+    fn reset_rows(&mut self) {
+        self.rows = self.plan.subnets.iter().map(SubnetRow::new).collect();
+        self.block = self.plan.block.to_string();
+        self.selected = None;
+    }
+
+    fn load(&mut self) {
+        let path = PathBuf::from(self.path.trim());
+        let result = PlanFormat::from_path(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|format| {
+                let text = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+                AddressPlan::parse(&text, format).map_err(|e| e.to_string())
+            });
+        self.status = Some(match result {
+            Ok(plan) => {
+                self.plan = plan;
+                self.reset_rows();
+                Ok(format!("Loaded {}", path.display()))
+            }
+            Err(error) => Err(error),
+        });
+    }
+
+    fn save(&mut self) {
+        let path = Path::new(self.path.trim());
+        let result = PlanFormat::from_path(path)
+            .and_then(|format| self.plan.to_text(format))
+            .map_err(|e| e.to_string())
+            .and_then(|text| std::fs::write(path, text).map_err(|e| e.to_string()));
+        self.status = Some(result.map(|_| format!("Saved {}", path.display())));
+    }
+
+    pub fn view(&self) -> Element<'_, PlanMessage> {
+        let status = match &self.status {
+            Some(Ok(message)) => text(message).color(Color::from_rgb8(0, 200, 0)),
+            Some(Err(error)) => wrong(error),
+            None => text(""),
+        };
+        let file = row![
+            text_input("plan.toml or plan.json", &self.path)
+                .on_input(PlanMessage::Path)
+                .padding(5),
+            button("Load").on_press(PlanMessage::Load),
+            button("Save").on_press(PlanMessage::Save),
+        ]
+        .spacing(5);
+        let header = row![
+            text_input("Plan name", &self.plan.name)
+                .on_input(PlanMessage::Name)
+                .padding(5),
+            checked_input(
+                "Parent block",
+                &self.block,
+                invalid(self.block.trim().parse::<Cidr>().is_ok())
+            )
+            .on_input(PlanMessage::Block),
+        ]
+        .spacing(5);
+
+        // One row per subnet, invalid inputs get a red border
+        let mut labels = row![].spacing(5);
+        for field in SubnetField::ALL {
+            labels = labels.push(text(field.label()).width(Length::FillPortion(2)));
+        }
+        let mut subnets = column![labels.push(text("").width(Length::Fixed(185.0)))].spacing(5);
+        for (index, subnet_row) in self.rows.iter().enumerate() {
+            let mut inputs = row![].spacing(5);
+            for field in SubnetField::ALL {
+                let value = &subnet_row.fields[field as usize];
+                // Applied to a copy only to find out whether the text is valid
+                let valid = field.apply(&mut self.plan.subnets[index].clone(), value);
+                inputs = inputs.push(
+                    checked_input(field.label(), value, invalid(valid))
+                        .on_input(move |value| PlanMessage::Edit(index, field, value))
+                        .width(Length::FillPortion(2)),
+                );
+            }
+            let hosts = format!("Hosts ({})", self.plan.subnets[index].reservations.len());
+            subnets = subnets.push(
+                inputs
+                    .push(
+                        button(text(hosts))
+                            .on_press(PlanMessage::Select(index))
+                            .width(Length::Fixed(100.0)),
+                    )
+                    .push(
+                        button("Remove")
+                            .on_press(PlanMessage::Remove(index))
+                            .width(Length::Fixed(80.0)),
+                    ),
+            );
+        }
+
+        let allocate = row![
+            text_input("New subnet name", &self.new_name)
+                .on_input(PlanMessage::NewName)
+                .padding(5),
+            checked_input(
+                "Prefix",
+                &self.new_prefix,
+                invalid(self.new_prefix_value().is_some())
+            )
+            .on_input(PlanMessage::NewPrefix)
+            .on_submit(PlanMessage::Allocate)
+            .width(Length::Fixed(80.0)),
+            button("Allocate").on_press(PlanMessage::Allocate),
+        ]
+        .spacing(5);
+
+        let mut content = column![file, status, header, subnets, allocate].spacing(10);
+        if let Some(selected) = self.selected {
+            content = content.push(self.reservations_view(selected));
+        }
+
+        // Problems and free space of the current plan
+        let issues = self.plan.check();
+        let mut summary = column![].spacing(2);
+        if issues.is_empty() {
+            summary = summary.push(
+                text("No overlaps or misplaced addresses").color(Color::from_rgb8(0, 200, 0)),
+            );
+        }
+        for issue in issues {
+            summary = summary.push(wrong(issue.to_string()));
+        }
+        let free: Vec<String> = self.plan.free_space().iter().map(Cidr::to_string).collect();
+        summary = summary.push(text(format!("Free: {}", free.join(", "))));
+
+        scrollable(content.push(summary))
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn reservations_view(&self, selected: usize) -> Element<'_, PlanMessage> {
+        let subnet = &self.plan.subnets[selected];
+        let mut table = column![text(format!("Reservations of {}", subnet.name))].spacing(5);
+        for (index, values) in self.rows[selected].reservations.iter().enumerate() {
+            let mut inputs = row![].spacing(5);
+            for field in ReservationField::ALL {
+                let value = &values[field as usize];
+                let valid = field.apply(&mut subnet.reservations[index].clone(), value);
+                inputs = inputs.push(
+                    checked_input(field.label(), value, invalid(valid))
+                        .on_input(move |value| PlanMessage::EditReservation(index, field, value)),
+                );
+            }
+            table = table.push(
+                inputs.push(button("Remove").on_press(PlanMessage::RemoveReservation(index))),
+            );
+        }
+        table
+            .push(button("Add reservation").on_press(PlanMessage::AddReservation))
+            .into()
+    }
+}
+
+// Inputs of the editor only get a red border while the value is invalid
+fn invalid(valid: bool) -> Option<bool> {
+    (!valid).then_some(false)
+}
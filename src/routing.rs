@@ -0,0 +1,98 @@
+use iced::widget::{button, column, row, text};
+use iced::{Element, Length};
+use ip_checker_logic::routing::RoutingExercise;
+use ip_checker_logic::IpCalculator;
+
+use crate::widgets::checked_input;
+
+// Messages of the routing table exercise
+#[derive(Debug, Clone)]
+pub enum RoutingMessage {
+    Input(usize, String), // Update the next hop of a destination
+    Check,                // Check all next hops
+    Next,                 // Generate the next routing table
+}
+
+// Longest prefix match practice with a small routing table
+pub struct RoutingPractice {
+    calculator: IpCalculator,
+    exercise: RoutingExercise,
+    answers: Vec<String>,
+    results: Option<Vec<bool>>,
+}
+
+impl RoutingPractice {
+    pub fn new() -> Self {
+        let mut calculator = IpCalculator::new();
+        let exercise = calculator.random_routing_exercise();
+        Self {
+            answers: vec![String::new(); exercise.destinations.len()],
+            exercise,
+            calculator,
+            results: None,
+        }
+    }
+
+    pub fn update(&mut self, message: RoutingMessage) {
+        match message {
+            RoutingMessage::Input(index, value) => self.answers[index] = value,
+            RoutingMessage::Check => {
+                self.results = Some(
+                    self.answers
+                        .iter()
+                        .enumerate()
+                        .map(|(index, answer)| self.exercise.check(index, answer))
+                        .collect(),
+                )
+            }
+            RoutingMessage::Next => {
+                self.exercise = self.calculator.random_routing_exercise();
+                self.answers = vec![String::new(); self.exercise.destinations.len()];
+                self.results = None;
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, RoutingMessage> {
+        // Routing table with one line per route
+        let mut table = column![row![
+            text("Network").width(Length::FillPortion(1)),
+            text("Next Hop").width(Length::FillPortion(1)),
+        ]]
+        .spacing(2);
+        for route in self.exercise.table.routes() {
+            table = table.push(row![
+                text(route.cidr()).width(Length::FillPortion(1)),
+                text(route.next_hop.as_string()).width(Length::FillPortion(1)),
+            ]);
+        }
+
+        // Destinations with an input for their next hop
+        let mut destinations =
+            column![text("Which next hop does each destination take?")].spacing(5);
+        for (index, destination) in self.exercise.destinations.iter().enumerate() {
+            let result = self.results.as_ref().map(|results| results[index]);
+            destinations = destinations.push(
+                row![
+                    text(destination.as_string()).width(Length::FillPortion(1)),
+                    checked_input("Next hop", &self.answers[index], result)
+                        .on_input(move |value| RoutingMessage::Input(index, value))
+                        .width(Length::FillPortion(1)),
+                ]
+                .spacing(5),
+            );
+        }
+
+        column![
+            row![
+                button("Check").on_press(RoutingMessage::Check),
+                button("Next table").on_press(RoutingMessage::Next),
+            ]
+            .spacing(10),
+            table,
+            destinations,
+        ]
+        .spacing(10)
+        .into()
+    }
+}
@@ -0,0 +1,159 @@
+use iced::widget::{column, pick_list, row, scrollable, text, text_input};
+use iced::{Element, Length};
+use ip_checker_logic::cidr::Cidr;
+
+use crate::widgets::wrong;
+
+// Only this many subnets are listed, a /8 split into /30s would freeze the window
+const MAX_ROWS: usize = 256;
+
+// Messages of the subnet splitter
+#[derive(Debug, Clone)]
+pub enum SplitterMessage {
+    NetworkInput(String), // Update the network to split
+    SplitBy(SplitBy),     // Split by prefix or by number of subnets
+    ValueInput(String),   // Update the prefix or number of subnets
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitBy {
+    Prefix,
+    Count,
+}
+
+impl SplitBy {
+    const ALL: [SplitBy; 2] = [SplitBy::Prefix, SplitBy::Count];
+}
+
+impl std::fmt::Display for SplitBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SplitBy::Prefix => "Target prefix",
+            SplitBy::Count => "Number of subnets",
+        })
+    }
+}
+
+// One line of the subnet list
+struct SubnetRow {
+    network: String,
+    range: String,
+    broadcast: String,
+}
+
+// The first rows of the subnet list and the total number of subnets
+fn split(network: &str, split_by: SplitBy, value: &str) -> Result<(Vec<SubnetRow>, usize), String> {
+    let network: Cidr = network.parse().map_err(|e| format!("{}", e))?;
+    let value: u64 = value
+        .trim()
+        .trim_start_matches('/')
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    let subnets = match split_by {
+        SplitBy::Prefix => network.split(value.min(u8::MAX as u64) as u8),
+        SplitBy::Count => network.split_into(value),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let total = subnets.len();
+    let rows = subnets
+        .take(MAX_ROWS)
+        .map(|subnet| {
+            let hosts = subnet.hosts().len() as u64;
+            let host = |index| subnet.nth_host(index).unwrap().as_string();
+            SubnetRow {
+                network: subnet.to_string(),
+                range: format!("{} - {}", host(0), host(hosts - 1)),
+                // /31 and /32 have no broadcast address
+                broadcast: if subnet.prefix.value() >= 31 {
+                    "-".to_string()
+                } else {
+                    std::net::Ipv4Addr::from(subnet.last()).to_string()
+                },
+            }
+        })
+        .collect();
+    Ok((rows, total))
+}
+
+// Splits a network into equal subnets and lists their ranges
+pub struct SubnetSplitter {
+    network: String,
+    split_by: SplitBy,
+    value: String,
+    result: Result<(Vec<SubnetRow>, usize), String>,
+}
+
+impl SubnetSplitter {
+    // Starts with the network of the current exercise if there is one
+    pub fn new(network: Option<Cidr>) -> Self {
+        let network = network.map_or("192.168.0.0/24".to_string(), |n| n.to_string());
+        let mut splitter = Self {
+            network,
+            split_by: SplitBy::Count,
+            value: "4".to_string(),
+            result: Ok((Vec::new(), 0)),
+        };
+        splitter.split();
+        splitter
+    }
+
+    pub fn update(&mut self, message: SplitterMessage) {
+        match message {
+            SplitterMessage::NetworkInput(value) => self.network = value,
+            SplitterMessage::SplitBy(split_by) => self.split_by = split_by,
+            SplitterMessage::ValueInput(value) => self.value = value,
+        }
+        self.split();
+    }
+
+    fn split(&mut self) {
+        self.result = split(&self.network, self.split_by, &self.value);
+    }
+
+    pub fn view(&self) -> Element<'_, SplitterMessage> {
+        let inputs = column![
+            text_input("Network, e.g. 10.0.0.0/16", &self.network)
+                .on_input(SplitterMessage::NetworkInput)
+                .padding(5),
+            row![
+                pick_list(SplitBy::ALL, Some(self.split_by), SplitterMessage::SplitBy),
+                text_input("Value", &self.value)
+                    .on_input(SplitterMessage::ValueInput)
+                    .padding(5),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10);
+
+        let list: Element<'_, SplitterMessage> = match &self.result {
+            Err(error) => wrong(error).into(),
+            Ok((rows, total)) => {
+                let mut list = column![row![
+                    text("Subnet").width(Length::FillPortion(2)),
+                    text("Hosts").width(Length::FillPortion(3)),
+                    text("Broadcast").width(Length::FillPortion(2)),
+                ]]
+                .spacing(2);
+                for subnet in rows {
+                    list = list.push(row![
+                        text(&subnet.network).width(Length::FillPortion(2)),
+                        text(&subnet.range).width(Length::FillPortion(3)),
+                        text(&subnet.broadcast).width(Length::FillPortion(2)),
+                    ]);
+                }
+                if *total > rows.len() {
+                    list = list.push(text(format!("... and {} more", total - rows.len())));
+                }
+                column![
+                    text(format!("{} subnets", total)),
+                    scrollable(list).height(Length::Fill)
+                ]
+                .spacing(5)
+                .into()
+            }
+        };
+
+        column![inputs, list].spacing(10).into()
+    }
+}
@@ -0,0 +1,217 @@
+use iced::alignment::{Horizontal, Vertical};
+use iced::widget::canvas::{self, Canvas, Frame, Geometry, Path, Stroke};
+use iced::widget::{button, column, row, scrollable, text};
+use iced::{mouse, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme};
+use ip_checker_logic::topology::{AddressingError, NodeKind, SegmentAnswer, SegmentKind, Topology};
+use ip_checker_logic::IpCalculator;
+
+use crate::widgets::{checked_input, correct, wrong};
+
+// Messages of the topology addressing exercise
+#[derive(Debug, Clone)]
+pub enum TopologyMessage {
+    Subnet(usize, String),           // Update the subnet of a segment
+    Interface(usize, usize, String), // Update an interface address of a segment
+    Check,                           // Validate the addressing
+    Solution,                        // Fill in a possible solution
+    Next,                            // Generate the next topology
+}
+
+// Address a small routed network with VLSM
+pub struct TopologyPractice {
+    calculator: IpCalculator,
+    topology: Topology,
+    answers: Vec<SegmentAnswer>,
+    errors: Option<Vec<AddressingError>>,
+}
+
+impl TopologyPractice {
+    pub fn new() -> Self {
+        let mut calculator = IpCalculator::new();
+        let topology = calculator.random_topology();
+        Self {
+            answers: empty_answers(&topology),
+            topology,
+            calculator,
+            errors: None,
+        }
+    }
+
+    pub fn update(&mut self, message: TopologyMessage) {
+        match message {
+            TopologyMessage::Subnet(segment, value) => self.answers[segment].subnet = value,
+            TopologyMessage::Interface(segment, interface, value) => {
+                self.answers[segment].interfaces[interface] = value
+            }
+            TopologyMessage::Check => self.errors = Some(self.topology.validate(&self.answers)),
+            TopologyMessage::Solution => {
+                self.answers = self.topology.solution();
+                self.errors = None;
+            }
+            TopologyMessage::Next => {
+                self.topology = self.calculator.random_topology();
+                self.answers = empty_answers(&self.topology);
+                self.errors = None;
+            }
+        }
+    }
+
+    // None before checking, otherwise whether the subnet or interface has no errors
+    fn result(&self, segment: &str, interface: Option<&str>) -> Option<bool> {
+        self.errors.as_ref().map(|errors| {
+            !errors
+                .iter()
+                .any(|error| error.segment() == segment && error.interface() == interface)
+        })
+    }
+
+    pub fn view(&self) -> Element<'_, TopologyMessage> {
+        let mut inputs = column![].spacing(5);
+        for (index, segment) in self.topology.segments.iter().enumerate() {
+            let label = match &segment.kind {
+                SegmentKind::Lan { hosts, .. } => format!("{} ({} hosts)", segment.name, hosts),
+                SegmentKind::Link => segment.name.clone(),
+            };
+            inputs = inputs.push(
+                row![
+                    text(label).width(Length::FillPortion(1)),
+                    checked_input(
+                        "Subnet",
+                        &self.answers[index].subnet,
+                        self.result(&segment.name, None)
+                    )
+                    .on_input(move |value| TopologyMessage::Subnet(index, value))
+                    .width(Length::FillPortion(2)),
+                ]
+                .spacing(5),
+            );
+            for (position, interface) in segment.interfaces.iter().enumerate() {
+                let name = self.topology.interface_name(interface);
+                let result = self.result(&segment.name, Some(&name));
+                inputs = inputs.push(
+                    row![
+                        text(name).width(Length::FillPortion(1)),
+                        checked_input(
+                            "Interface address",
+                            &self.answers[index].interfaces[position],
+                            result
+                        )
+                        .on_input(move |value| TopologyMessage::Interface(index, position, value))
+                        .width(Length::FillPortion(2)),
+                    ]
+                    .spacing(5),
+                );
+            }
+        }
+
+        let feedback = match &self.errors {
+            Some(errors) if errors.is_empty() => column![correct()],
+            Some(errors) => column(errors.iter().map(|error| wrong(error.to_string()).into())),
+            None => column![],
+        };
+
+        column![
+            row![
+                button("Check").on_press(TopologyMessage::Check),
+                button("Show solution").on_press(TopologyMessage::Solution),
+                button("Next topology").on_press(TopologyMessage::Next),
+            ]
+            .spacing(10),
+            text(self.topology.text()),
+            Canvas::new(Diagram {
+                topology: &self.topology
+            })
+            .width(Length::Fill)
+            .height(Length::Fixed(260.0)),
+            scrollable(column![inputs, feedback].spacing(10)).height(Length::Fill),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
+fn empty_answers(topology: &Topology) -> Vec<SegmentAnswer> {
+    topology
+        .segments
+        .iter()
+        .map(|segment| SegmentAnswer {
+            subnet: String::new(),
+            interfaces: vec![String::new(); segment.interfaces.len()],
+        })
+        .collect()
+}
+
+// Draws the layout of a topology scaled to the available space
+struct Diagram<'a> {
+    topology: &'a Topology,
+}
+
+impl<Message> canvas::Program<Message> for Diagram<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let layout = self.topology.layout();
+        let scale = (bounds.width / layout.width).min(bounds.height / layout.height);
+        let point = |x: f32, y: f32| Point::new(x * scale, y * scale);
+        let palette = theme.palette();
+        let line = Stroke::default().with_width(2.0).with_color(palette.text);
+
+        for edge in &layout.edges {
+            let (from, to) = (&layout.nodes[edge.from], &layout.nodes[edge.to]);
+            let (start, end) = (point(from.x, from.y), point(to.x, to.y));
+            frame.stroke(&Path::line(start, end), line);
+            let middle = Point::new(
+                (start.x + end.x) / 2.0 + 6.0,
+                (start.y + end.y) / 2.0 - 10.0,
+            );
+            draw_text(
+                &mut frame,
+                &self.topology.segments[edge.segment].name,
+                middle,
+                palette.text,
+            );
+        }
+
+        for node in &layout.nodes {
+            let center = point(node.x, node.y);
+            let path = match node.kind {
+                NodeKind::Router => Path::circle(center, 22.0),
+                NodeKind::Switch => Path::rectangle(
+                    Point::new(center.x - 35.0, center.y - 18.0),
+                    Size::new(70.0, 36.0),
+                ),
+            };
+            frame.fill(&path, palette.background);
+            frame.stroke(&path, line.with_color(palette.primary));
+            draw_text(&mut frame, &node.label, center, palette.text);
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+// Centered text, one line below the other
+fn draw_text(frame: &mut Frame, content: &str, center: Point, color: Color) {
+    let lines = content.lines().count() as f32;
+    for (index, line) in content.lines().enumerate() {
+        frame.fill_text(canvas::Text {
+            content: line.to_string(),
+            position: Point::new(
+                center.x,
+                center.y + (index as f32 - (lines - 1.0) / 2.0) * 14.0,
+            ),
+            color,
+            size: 12.0.into(),
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Center,
+            ..canvas::Text::default()
+        });
+    }
+}
@@ -0,0 +1,138 @@
+use iced::widget::{button, column, pick_list, row, text};
+use iced::{Element, Length};
+use ip_checker_logic::troubleshooting::{Problem, Scenario};
+use ip_checker_logic::IpCalculator;
+
+use crate::widgets::{correct, wrong};
+
+// Messages of the troubleshooting exercise
+#[derive(Debug, Clone)]
+pub enum TroubleshootingMessage {
+    Verdict(usize, Verdict), // Pick what is wrong with a host
+    Check,                   // Check all verdicts
+    Next,                    // Generate the next scenario
+}
+
+// What the student thinks of a host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Ok,
+    Problem(Problem),
+}
+
+impl Verdict {
+    fn all() -> Vec<Verdict> {
+        std::iter::once(Verdict::Ok)
+            .chain(Problem::ALL.into_iter().map(Verdict::Problem))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Ok => f.write_str("Configured correctly"),
+            Verdict::Problem(problem) => problem.fmt(f),
+        }
+    }
+}
+
+// Find the misconfigured hosts on a LAN segment
+pub struct TroubleshootingPractice {
+    calculator: IpCalculator,
+    scenario: Scenario,
+    verdicts: Vec<Option<Verdict>>,
+    results: Option<Vec<bool>>,
+}
+
+impl TroubleshootingPractice {
+    pub fn new() -> Self {
+        let mut calculator = IpCalculator::new();
+        let scenario = calculator.random_scenario();
+        Self {
+            verdicts: vec![None; scenario.hosts.len()],
+            scenario,
+            calculator,
+            results: None,
+        }
+    }
+
+    pub fn update(&mut self, message: TroubleshootingMessage) {
+        match message {
+            TroubleshootingMessage::Verdict(index, verdict) => self.verdicts[index] = Some(verdict),
+            TroubleshootingMessage::Check => {
+                let answers: Vec<Option<Problem>> = self
+                    .verdicts
+                    .iter()
+                    .map(|verdict| match verdict {
+                        Some(Verdict::Problem(problem)) => Some(*problem),
+                        _ => None,
+                    })
+                    .collect();
+                self.results = Some(self.scenario.check(&answers));
+            }
+            TroubleshootingMessage::Next => {
+                self.scenario = self.calculator.random_scenario();
+                self.verdicts = vec![None; self.scenario.hosts.len()];
+                self.results = None;
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, TroubleshootingMessage> {
+        let header = row![
+            text("Host").width(Length::FillPortion(1)),
+            text("IP Address").width(Length::FillPortion(2)),
+            text("Subnet Mask").width(Length::FillPortion(2)),
+            text("Gateway").width(Length::FillPortion(2)),
+            text("Diagnosis").width(Length::FillPortion(4)),
+        ];
+        let mut table = column![header].spacing(5);
+        for (index, host) in self.scenario.hosts.iter().enumerate() {
+            table = table.push(
+                row![
+                    text(&host.name).width(Length::FillPortion(1)),
+                    text(host.ip.as_string()).width(Length::FillPortion(2)),
+                    text(host.mask.as_string()).width(Length::FillPortion(2)),
+                    text(host.gateway.as_string()).width(Length::FillPortion(2)),
+                    pick_list(Verdict::all(), self.verdicts[index], move |verdict| {
+                        TroubleshootingMessage::Verdict(index, verdict)
+                    })
+                    .placeholder("Pick a diagnosis")
+                    .width(Length::FillPortion(4)),
+                ]
+                .spacing(5),
+            );
+
+            // Wrong verdicts show what is actually wrong with the host
+            if let Some(results) = &self.results {
+                let problems = self.scenario.problems(index);
+                let feedback = match (results[index], problems.is_empty()) {
+                    (true, _) => correct(),
+                    (false, true) => wrong("Wrong, this host is configured correctly"),
+                    (false, false) => {
+                        let problems: Vec<&str> =
+                            problems.iter().map(Problem::description).collect();
+                        wrong(format!("Wrong: {}", problems.join(", ")))
+                    }
+                };
+                table = table.push(feedback);
+            }
+        }
+
+        column![
+            row![
+                button("Check").on_press(TroubleshootingMessage::Check),
+                button("Next scenario").on_press(TroubleshootingMessage::Next),
+            ]
+            .spacing(10),
+            text(format!(
+                "The router's LAN interface is {}{}. Which hosts are misconfigured and why?",
+                self.scenario.router, self.scenario.prefix
+            )),
+            table,
+        ]
+        .spacing(10)
+        .into()
+    }
+}
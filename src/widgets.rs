@@ -0,0 +1,65 @@
+use std::fmt::Display;
+
+use iced::widget::text::{IntoFragment, Text};
+use iced::widget::text_input::TextInput;
+use iced::widget::{text, text_input};
+use iced::{Color, Theme};
+
+// Correct and answered exercises of a practice session
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Score {
+    correct: usize,
+    answered: usize,
+}
+
+impl Score {
+    pub fn record(&mut self, correct: bool) {
+        self.answered += 1;
+        if correct {
+            self.correct += 1;
+        }
+    }
+
+    pub fn view<'a>(&self) -> Text<'a> {
+        text(format!("Score: {} of {}", self.correct, self.answered))
+    }
+}
+
+pub fn correct<'a>() -> Text<'a> {
+    text("Correct!").color(Color::from_rgb8(0, 200, 0))
+}
+
+pub fn wrong<'a>(message: impl IntoFragment<'a>) -> Text<'a> {
+    text(message).color(Color::from_rgb8(255, 0, 0))
+}
+
+// Nothing before checking, then either praise or the expected answer
+pub fn feedback<'a>(result: Option<bool>, answer: impl Display) -> Text<'a> {
+    match result {
+        Some(true) => correct(),
+        Some(false) => wrong(format!("Wrong, the answer is {}", answer)),
+        None => text(""),
+    }
+}
+
+// Text input with a green or red border once checked
+pub fn checked_input<'a, Message: Clone + 'a>(
+    placeholder: &str,
+    value: &str,
+    result: Option<bool>,
+) -> TextInput<'a, Message> {
+    text_input(placeholder, value)
+        .style(move |theme: &Theme, status| {
+            let mut style = text_input::default(theme, status);
+            if let Some(correct) = result {
+                style.border.color = if correct {
+                    Color::from_rgb8(0, 255, 0)
+                } else {
+                    Color::from_rgb8(255, 0, 0)
+                };
+                style.border.width = 2.0;
+            }
+            style
+        })
+        .padding(5)
+}